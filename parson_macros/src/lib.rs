@@ -0,0 +1,160 @@
+//! # Parson Macros
+//!
+//! `parson_macros` checks JSON at compile time, so that a typo in an embedded document
+//! fails the build instead of panicking at startup.
+//!
+//! Each macro parses its JSON with `parson` while compiling,
+//! then expands to the code that builds the same [`JSONValue`](parson::JSONValue) at runtime.
+
+use std::{env, fs, path::PathBuf};
+
+use parson::{JSONRef, JSONValue};
+use proc_macro::TokenStream;
+use proc_macro2::{Literal, TokenStream as TokenStream2};
+use quote::quote;
+use syn::{parse_macro_input, LitStr};
+
+/// Parse a JSON string literal at compile time into a `JSONValue`
+///
+/// Invalid JSON fails the build with the same message `str::parse::<JSONValue>()` would return.
+///
+/// # Example
+///
+/// ```
+/// use parson_macros::json_str;
+///
+/// let json_value = json_str!(r#"{ "name": "parson", "tags": ["json", "parser"], "stars": 5 }"#);
+/// assert_eq!(json_value["tags"][1].as_str(), Some("parser"));
+/// assert_eq!(json_value["stars"].as_f64(), Some(5.0));
+///
+/// let json_value = json_str!(r#"[-0, -2.5e-3, "tab\tend", null, true, 1e999]"#);
+/// assert_eq!(json_value.to_string(), r#"[ -0, -0.0025, "tab\tend", null, true, inf ]"#);
+/// ```
+///
+/// ```compile_fail
+/// let json_value = parson_macros::json_str!(r#"{ "name": "parson", }"#);
+/// ```
+#[proc_macro]
+pub fn json_str(input: TokenStream) -> TokenStream {
+    let literal = parse_macro_input!(input as LitStr);
+
+    match literal.value().parse::<JSONValue>() {
+        Ok(json_value) => construct_value(&json_value).into(),
+        Err(error) => syn::Error::new(literal.span(), error.get_message())
+            .to_compile_error()
+            .into(),
+    }
+}
+
+/// Parse a JSON file at compile time into a `JSONValue`
+///
+/// The path is relative to the directory holding the calling crate's `Cargo.toml`.
+/// The crate is rebuilt whenever the file changes,
+/// and invalid JSON fails the build with the file name and the position of the error.
+///
+/// # Example
+///
+/// ```
+/// use parson_macros::include_json;
+///
+/// let config = include_json!("fixtures/config.json");
+/// assert_eq!(config["server"]["port"].as_f64(), Some(8080.0));
+/// assert_eq!(config["features"].as_array().map(|features| features.len()), Some(2));
+/// ```
+///
+/// ```compile_fail
+/// let config = parson_macros::include_json!("fixtures/missing.json");
+/// ```
+#[proc_macro]
+pub fn include_json(input: TokenStream) -> TokenStream {
+    let literal = parse_macro_input!(input as LitStr);
+
+    let mut path = env::var_os("CARGO_MANIFEST_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_default();
+    path.push(literal.value());
+
+    let json = match fs::read_to_string(&path) {
+        Ok(json) => json,
+        Err(error) => {
+            let message = format!("Could not read <{}>: {}", path.display(), error);
+            return syn::Error::new(literal.span(), message)
+                .to_compile_error()
+                .into();
+        }
+    };
+
+    match json.parse::<JSONValue>() {
+        Ok(json_value) => {
+            // Depending on the file through `include_bytes!` makes cargo rebuild when it changes
+            let path = path.to_string_lossy();
+            let construct = construct_value(&json_value);
+            quote!({
+                const _: &[u8] = ::core::include_bytes!(#path);
+                #construct
+            })
+            .into()
+        }
+        Err(error) => {
+            let message = format!("In <{}>: {}", path.display(), error.get_message());
+            syn::Error::new(literal.span(), message)
+                .to_compile_error()
+                .into()
+        }
+    }
+}
+
+/// Generate the expression that builds the JSON Value at runtime
+fn construct_value(json_value: &JSONValue) -> TokenStream2 {
+    match json_value.get_ref() {
+        JSONRef::String(json_string) => {
+            let string = json_string.as_str();
+            quote!(::parson::JSONValue::from_string(::parson::JSONString::new(
+                ::std::string::String::from(#string)
+            )))
+        }
+        JSONRef::Number(_) => {
+            let number = construct_number(json_value.as_f64().unwrap_or_default());
+            quote!(::parson::JSONValue::from_number(::parson::JSONNumber::new(#number)))
+        }
+        JSONRef::Boolean(_) => {
+            let boolean = json_value.as_bool().unwrap_or_default();
+            quote!(::parson::JSONValue::from_boolean(::parson::JSONBoolean::new(#boolean)))
+        }
+        JSONRef::Null(_) => {
+            quote!(::parson::JSONValue::from_null(::parson::JSONNull::new()))
+        }
+        JSONRef::Array(json_array) => {
+            let items = json_array.iter().map(construct_value);
+            quote!(::parson::JSONValue::from_array({
+                let mut array = ::parson::JSONArray::new();
+                #(array.push(#items);)*
+                array
+            }))
+        }
+        JSONRef::Object(json_object) => {
+            let keys = json_object.keys();
+            let values = json_object.values().map(construct_value);
+            quote!(::parson::JSONValue::from_object({
+                let mut object = ::parson::JSONObject::new();
+                #(object.set(::std::string::String::from(#keys), #values);)*
+                object
+            }))
+        }
+    }
+}
+
+/// Numbers too large for an f64 parse to infinity, which has no literal,
+/// so those are rebuilt from their bits
+fn construct_number(number: f64) -> TokenStream2 {
+    if !number.is_finite() {
+        let bits = Literal::u64_suffixed(number.to_bits());
+        quote!(::core::primitive::f64::from_bits(#bits))
+    } else if number.is_sign_negative() {
+        let literal = Literal::f64_suffixed(-number);
+        quote!(-#literal)
+    } else {
+        let literal = Literal::f64_suffixed(number);
+        quote!(#literal)
+    }
+}
@@ -0,0 +1,186 @@
+use std::fmt;
+
+use crate::{serializer::Serializer, Formatter, JSONError, JSONValue};
+
+impl JSONValue {
+    /// Convert JSON Value to its canonical form, as defined by the
+    /// JSON Canonicalization Scheme of RFC 8785.
+    ///
+    /// Object keys are sorted by their UTF-16 code units no matter which order they were added in,
+    /// numbers are written the way ECMAScript writes them,
+    /// and only the characters JSON requires are escaped.
+    /// Fails if the JSON Value holds a number that is NaN or infinite.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use parson::{json, JSONNumber, JSONValue};
+    ///
+    /// let json = r#"{
+    ///     "numbers": [333333333.33333329, 1E30, 4.50, 2e-3, 0.000000000000000000000000001],
+    ///     "string": "\u20ac$\u000F\u000aA'\u0042\u0022\u005c\\\"\/",
+    ///     "literals": [null, true, false]
+    /// }"#;
+    /// assert_eq!(
+    ///     json.parse::<JSONValue>().unwrap().to_canonical_string().unwrap(),
+    ///     r#"{"literals":[null,true,false],"numbers":[333333333.3333333,1e+30,4.5,0.002,1e-27],"string":"€$\u000f\nA'B\"\\\\\"/"}"#
+    /// );
+    ///
+    /// let json = r#"{
+    ///     "\u20ac": "Euro Sign",
+    ///     "\r": "Carriage Return",
+    ///     "\ufb33": "Hebrew Letter Dalet With Dagesh",
+    ///     "1": "One",
+    ///     "\ud83d\ude00": "Emoji: Grinning Face",
+    ///     "\u0080": "Control",
+    ///     "\u00f6": "Latin Small Letter O With Diaeresis"
+    /// }"#;
+    /// assert_eq!(
+    ///     json.parse::<JSONValue>().unwrap().to_canonical_string().unwrap(),
+    ///     "{\"\\r\":\"Carriage Return\",\"1\":\"One\",\"\u{80}\":\"Control\",\
+    ///     \"ö\":\"Latin Small Letter O With Diaeresis\",\"€\":\"Euro Sign\",\
+    ///     \"😀\":\"Emoji: Grinning Face\",\"\u{fb33}\":\"Hebrew Letter Dalet With Dagesh\"}"
+    /// );
+    ///
+    /// let canonical = |bits: u64| {
+    ///     JSONValue::from_number(JSONNumber::new(f64::from_bits(bits))).to_canonical_string()
+    /// };
+    /// assert_eq!(canonical(0x0000000000000000).unwrap(), "0");
+    /// assert_eq!(canonical(0x8000000000000000).unwrap(), "0");
+    /// assert_eq!(canonical(0x0000000000000001).unwrap(), "5e-324");
+    /// assert_eq!(canonical(0x8000000000000001).unwrap(), "-5e-324");
+    /// assert_eq!(canonical(0x7fefffffffffffff).unwrap(), "1.7976931348623157e+308");
+    /// assert_eq!(canonical(0xffefffffffffffff).unwrap(), "-1.7976931348623157e+308");
+    /// assert_eq!(canonical(0x4340000000000000).unwrap(), "9007199254740992");
+    /// assert_eq!(canonical(0xc340000000000000).unwrap(), "-9007199254740992");
+    /// assert_eq!(canonical(0x4430000000000000).unwrap(), "295147905179352830000");
+    /// assert_eq!(canonical(0x44b52d02c7e14af5).unwrap(), "9.999999999999997e+22");
+    /// assert_eq!(canonical(0x44b52d02c7e14af6).unwrap(), "1e+23");
+    /// assert_eq!(canonical(0x44b52d02c7e14af7).unwrap(), "1.0000000000000001e+23");
+    /// assert_eq!(canonical(0x444b1ae4d6e2ef4e).unwrap(), "999999999999999700000");
+    /// assert_eq!(canonical(0x444b1ae4d6e2ef4f).unwrap(), "999999999999999900000");
+    /// assert_eq!(canonical(0x444b1ae4d6e2ef50).unwrap(), "1e+21");
+    /// assert_eq!(canonical(0x3eb0c6f7a0b5ed8c).unwrap(), "9.999999999999997e-7");
+    /// assert_eq!(canonical(0x3eb0c6f7a0b5ed8d).unwrap(), "0.000001");
+    /// assert_eq!(canonical(0x41b3de4355555553).unwrap(), "333333333.3333332");
+    /// assert_eq!(canonical(0x41b3de4355555554).unwrap(), "333333333.33333325");
+    /// assert_eq!(canonical(0x41b3de4355555555).unwrap(), "333333333.3333333");
+    /// assert_eq!(canonical(0x41b3de4355555556).unwrap(), "333333333.3333334");
+    /// assert_eq!(canonical(0x41b3de4355555557).unwrap(), "333333333.33333343");
+    /// assert_eq!(canonical(0xbecbf647612f3696).unwrap(), "-0.0000033333333333333333");
+    /// assert_eq!(canonical(0x43143ff3c1cb0959).unwrap(), "1424953923781206.2");
+    /// assert!(canonical(0x7fffffffffffffff).is_err());
+    /// assert!(canonical(0x7ff0000000000000).is_err());
+    ///
+    /// let json_value = json!({ "sizes": [1, f64::INFINITY] });
+    /// assert_eq!(
+    ///     json_value.to_canonical_string().unwrap_err().get_message(),
+    ///     "Cannot canonicalize non-finite number <inf> (at /sizes/1)"
+    /// );
+    /// ```
+    pub fn to_canonical_string(&self) -> Result<String, JSONError> {
+        let mut result = String::new();
+        write_canonical(&mut result, self)?;
+        Ok(result)
+    }
+}
+
+fn write_canonical(result: &mut String, value: &JSONValue) -> Result<(), JSONError> {
+    if let Ok(json_array) = value.get_array() {
+        result.push('[');
+        for (index, item) in json_array.iter().enumerate() {
+            if index > 0 {
+                result.push(',');
+            }
+            write_canonical(result, item).map_err(|error| error.prefix_path(&index.to_string()))?;
+        }
+        result.push(']');
+    } else if let Ok(json_object) = value.get_object() {
+        let mut entries = json_object.iter().collect::<Vec<_>>();
+        entries.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+
+        result.push('{');
+        for (index, (key, item)) in entries.into_iter().enumerate() {
+            if index > 0 {
+                result.push(',');
+            }
+            CanonicalFormatter
+                .write_string(result, key)
+                .expect("writing to a String cannot fail");
+            result.push(':');
+            write_canonical(result, item).map_err(|error| error.prefix_path(key))?;
+        }
+        result.push('}');
+    } else {
+        if let Ok(number) = value.get_number() {
+            if !number.is_finite() {
+                return Err(JSONError::with_path(
+                    &format!("Cannot canonicalize non-finite number <{}>", number),
+                    "",
+                ));
+            }
+        }
+        value
+            .serialize(&mut Serializer::new(result, &mut CanonicalFormatter))
+            .expect("writing to a String cannot fail");
+    }
+    Ok(())
+}
+
+/// Writes scalars the way RFC 8785 expects them
+struct CanonicalFormatter;
+
+impl Formatter for CanonicalFormatter {
+    fn write_number<W: ?Sized + fmt::Write>(&mut self, writer: &mut W, value: f64) -> fmt::Result {
+        write_ecmascript_number(writer, value)
+    }
+}
+
+/// Write a finite number the way ECMAScript's `Number.prototype.toString()` does
+fn write_ecmascript_number<W: ?Sized + fmt::Write>(writer: &mut W, value: f64) -> fmt::Result {
+    if value == 0.0 {
+        return writer.write_char('0');
+    }
+    if value < 0.0 {
+        writer.write_char('-')?;
+    }
+
+    // Rust finds how many digits are needed to round trip, but when two candidates of that
+    // length are equally close, ECMAScript picks the even one, as exact formatting does
+    let shortest = format!("{:e}", value.abs());
+    let precision = shortest.split_once('e').unwrap().0.replace('.', "").len() - 1;
+    let scientific = format!("{:.*e}", precision, value.abs());
+    let (mantissa, exponent) = scientific.split_once('e').unwrap();
+    let digits = mantissa.replace('.', "");
+    let length = digits.len() as i32;
+    let point = exponent.parse::<i32>().unwrap() + 1;
+
+    if length <= point && point <= 21 {
+        writer.write_str(&digits)?;
+        for _ in 0..point - length {
+            writer.write_char('0')?;
+        }
+        Ok(())
+    } else if 0 < point && point <= 21 {
+        let (integer, fraction) = digits.split_at(point as usize);
+        write!(writer, "{}.{}", integer, fraction)
+    } else if -6 < point && point <= 0 {
+        writer.write_str("0.")?;
+        for _ in 0..-point {
+            writer.write_char('0')?;
+        }
+        writer.write_str(&digits)
+    } else {
+        let (first, rest) = digits.split_at(1);
+        writer.write_str(first)?;
+        if !rest.is_empty() {
+            write!(writer, ".{}", rest)?;
+        }
+        write!(
+            writer,
+            "e{}{}",
+            if point > 0 { '+' } else { '-' },
+            (point - 1).abs()
+        )
+    }
+}
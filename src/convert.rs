@@ -0,0 +1,492 @@
+use std::collections::{BTreeMap, HashMap};
+use std::hash::BuildHasher;
+
+use crate::{
+    JSONArray, JSONBoolean, JSONError, JSONNull, JSONNumber, JSONObject, JSONRef, JSONString,
+    JSONValue,
+};
+
+impl From<JSONString> for JSONValue {
+    fn from(json_string: JSONString) -> Self {
+        JSONValue::from_string(json_string)
+    }
+}
+
+impl From<JSONNumber> for JSONValue {
+    fn from(json_number: JSONNumber) -> Self {
+        JSONValue::from_number(json_number)
+    }
+}
+
+impl From<JSONBoolean> for JSONValue {
+    fn from(json_boolean: JSONBoolean) -> Self {
+        JSONValue::from_boolean(json_boolean)
+    }
+}
+
+impl From<JSONNull> for JSONValue {
+    fn from(json_null: JSONNull) -> Self {
+        JSONValue::from_null(json_null)
+    }
+}
+
+impl From<JSONArray> for JSONValue {
+    fn from(json_array: JSONArray) -> Self {
+        JSONValue::from_array(json_array)
+    }
+}
+
+impl From<JSONObject> for JSONValue {
+    fn from(json_object: JSONObject) -> Self {
+        JSONValue::from_object(json_object)
+    }
+}
+
+/// Convert a Rust type into a JSON Value
+///
+/// # Example
+///
+/// ```
+/// use std::collections::BTreeMap;
+/// use parson::JSONValue;
+///
+/// assert_eq!(JSONValue::from("value").to_string(), r#""value""#);
+/// assert_eq!(JSONValue::from(42u8).to_string(), "42");
+/// assert_eq!(JSONValue::from(Some(true)).to_string(), "true");
+/// assert_eq!(JSONValue::from(None::<bool>).to_string(), "null");
+/// assert_eq!(JSONValue::from(vec![1, 2]).to_string(), "[ 1, 2 ]");
+/// assert_eq!(
+///     JSONValue::from(BTreeMap::from([("key".to_string(), ())])).to_string(),
+///     r#"{ "key": null }"#
+/// );
+/// ```
+impl From<&str> for JSONValue {
+    fn from(string: &str) -> Self {
+        JSONValue::from_string(JSONString::new(string.to_string()))
+    }
+}
+
+impl From<String> for JSONValue {
+    fn from(string: String) -> Self {
+        JSONValue::from_string(JSONString::new(string))
+    }
+}
+
+impl From<&String> for JSONValue {
+    fn from(string: &String) -> Self {
+        JSONValue::from_string(JSONString::new(string.clone()))
+    }
+}
+
+impl From<bool> for JSONValue {
+    fn from(boolean: bool) -> Self {
+        JSONValue::from_boolean(JSONBoolean::new(boolean))
+    }
+}
+
+impl From<()> for JSONValue {
+    fn from(_: ()) -> Self {
+        JSONValue::from_null(JSONNull::new())
+    }
+}
+
+macro_rules! from_number {
+    ($($number:ty),*) => {
+        $(
+            impl From<$number> for JSONValue {
+                fn from(number: $number) -> Self {
+                    JSONValue::from_number(JSONNumber::new(number as f64))
+                }
+            }
+        )*
+    };
+}
+
+from_number!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64);
+
+impl<T: Into<JSONValue>> From<Option<T>> for JSONValue {
+    fn from(option: Option<T>) -> Self {
+        match option {
+            Some(value) => value.into(),
+            None => JSONValue::from_null(JSONNull::new()),
+        }
+    }
+}
+
+impl<T: Into<JSONValue>> From<Vec<T>> for JSONValue {
+    fn from(vec: Vec<T>) -> Self {
+        JSONValue::from_array(vec.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<T: Clone + Into<JSONValue>> From<&[T]> for JSONValue {
+    fn from(slice: &[T]) -> Self {
+        JSONValue::from_array(slice.iter().cloned().map(Into::into).collect())
+    }
+}
+
+impl<T: Into<JSONValue>, const N: usize> From<[T; N]> for JSONValue {
+    fn from(array: [T; N]) -> Self {
+        JSONValue::from_array(array.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<K: Into<String>, T: Into<JSONValue>, S> From<HashMap<K, T, S>> for JSONValue {
+    fn from(map: HashMap<K, T, S>) -> Self {
+        JSONValue::from_object(
+            map.into_iter()
+                .map(|(key, value)| (key.into(), value.into()))
+                .collect(),
+        )
+    }
+}
+
+impl<K: Into<String>, T: Into<JSONValue>> From<BTreeMap<K, T>> for JSONValue {
+    fn from(map: BTreeMap<K, T>) -> Self {
+        JSONValue::from_object(
+            map.into_iter()
+                .map(|(key, value)| (key.into(), value.into()))
+                .collect(),
+        )
+    }
+}
+
+/// Describe the value for an error message, without writing out a whole container
+fn describe(value: &JSONValue) -> String {
+    match value.get_ref() {
+        JSONRef::Array(_) => "array".to_string(),
+        JSONRef::Object(_) => "object".to_string(),
+        _ => value.to_string(),
+    }
+}
+
+fn expected(type_name: &str, value: &JSONValue) -> JSONError {
+    JSONError::with_path(
+        &format!("Expected <{}>, got <{}>", type_name, describe(value)),
+        "",
+    )
+}
+
+/// Convert a JSON Value back into a Rust type, failing with the path of the value
+/// that could not be converted
+///
+/// # Example
+///
+/// ```
+/// use std::collections::HashMap;
+/// use parson::JSONValue;
+///
+/// let json_value = r#"{ "ids": [1, 2, 300] }"#.parse::<JSONValue>().unwrap();
+/// let ids = Vec::<u16>::try_from(&json_value["ids"]).unwrap();
+/// assert_eq!(ids, [1, 2, 300]);
+///
+/// let error = HashMap::<String, Vec<u8>>::try_from(&json_value).unwrap_err();
+/// assert_eq!(error.get_path(), Some("/ids/2"));
+/// assert_eq!(error.get_message(), "Expected <u8>, got <300> (at /ids/2)");
+///
+/// assert_eq!(i8::try_from(JSONValue::from(-128)).unwrap(), -128);
+/// assert!(i8::try_from(JSONValue::from(128)).is_err());
+/// assert!(u64::try_from(JSONValue::from(1.5)).is_err());
+/// assert_eq!(Option::<String>::try_from(JSONValue::from(())).unwrap(), None);
+/// assert_eq!(<[bool; 2]>::try_from(JSONValue::from([true, false])).unwrap(), [true, false]);
+/// ```
+impl<'a> TryFrom<&'a JSONValue> for &'a str {
+    type Error = JSONError;
+
+    fn try_from(value: &'a JSONValue) -> Result<Self, Self::Error> {
+        value.as_str().ok_or_else(|| expected("string", value))
+    }
+}
+
+impl TryFrom<&JSONValue> for String {
+    type Error = JSONError;
+
+    fn try_from(value: &JSONValue) -> Result<Self, Self::Error> {
+        <&str>::try_from(value).map(str::to_string)
+    }
+}
+
+impl TryFrom<JSONValue> for String {
+    type Error = JSONError;
+
+    fn try_from(value: JSONValue) -> Result<Self, Self::Error> {
+        if value.is_string() {
+            Ok(value.into_string().unwrap())
+        } else {
+            Err(expected("string", &value))
+        }
+    }
+}
+
+impl TryFrom<&JSONValue> for bool {
+    type Error = JSONError;
+
+    fn try_from(value: &JSONValue) -> Result<Self, Self::Error> {
+        value.as_bool().ok_or_else(|| expected("bool", value))
+    }
+}
+
+impl TryFrom<&JSONValue> for () {
+    type Error = JSONError;
+
+    fn try_from(value: &JSONValue) -> Result<Self, Self::Error> {
+        value
+            .as_null()
+            .map(|_| ())
+            .ok_or_else(|| expected("null", value))
+    }
+}
+
+impl TryFrom<&JSONValue> for f64 {
+    type Error = JSONError;
+
+    fn try_from(value: &JSONValue) -> Result<Self, Self::Error> {
+        value.as_f64().ok_or_else(|| expected("f64", value))
+    }
+}
+
+impl TryFrom<&JSONValue> for f32 {
+    type Error = JSONError;
+
+    fn try_from(value: &JSONValue) -> Result<Self, Self::Error> {
+        value
+            .as_f64()
+            .map(|number| number as f32)
+            .ok_or_else(|| expected("f32", value))
+    }
+}
+
+macro_rules! try_from_integer {
+    ($($integer:ty),*) => {
+        $(
+            impl TryFrom<&JSONValue> for $integer {
+                type Error = JSONError;
+
+                fn try_from(value: &JSONValue) -> Result<Self, Self::Error> {
+                    // Both bounds are powers of two, so they convert to f64 exactly
+                    let min = <$integer>::MIN as f64;
+                    let max = (<$integer>::MAX as f64) + 1.0;
+                    match value.as_f64() {
+                        Some(number) if number.fract() == 0.0 && min <= number && number < max => {
+                            Ok(number as $integer)
+                        }
+                        _ => Err(expected(stringify!($integer), value)),
+                    }
+                }
+            }
+
+            impl TryFrom<JSONValue> for $integer {
+                type Error = JSONError;
+
+                fn try_from(value: JSONValue) -> Result<Self, Self::Error> {
+                    <$integer>::try_from(&value)
+                }
+            }
+        )*
+    };
+}
+
+try_from_integer!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+macro_rules! try_from_owned {
+    ($($type:ty),*) => {
+        $(
+            impl TryFrom<JSONValue> for $type {
+                type Error = JSONError;
+
+                fn try_from(value: JSONValue) -> Result<Self, Self::Error> {
+                    <$type>::try_from(&value)
+                }
+            }
+        )*
+    };
+}
+
+try_from_owned!(bool, (), f64, f32);
+
+impl<'a, T> TryFrom<&'a JSONValue> for Option<T>
+where
+    T: TryFrom<&'a JSONValue, Error = JSONError>,
+{
+    type Error = JSONError;
+
+    fn try_from(value: &'a JSONValue) -> Result<Self, Self::Error> {
+        if value.is_null() {
+            Ok(None)
+        } else {
+            T::try_from(value).map(Some)
+        }
+    }
+}
+
+impl<T> TryFrom<JSONValue> for Option<T>
+where
+    T: TryFrom<JSONValue, Error = JSONError>,
+{
+    type Error = JSONError;
+
+    fn try_from(value: JSONValue) -> Result<Self, Self::Error> {
+        if value.is_null() {
+            Ok(None)
+        } else {
+            T::try_from(value).map(Some)
+        }
+    }
+}
+
+impl<'a, T> TryFrom<&'a JSONValue> for Vec<T>
+where
+    T: TryFrom<&'a JSONValue, Error = JSONError>,
+{
+    type Error = JSONError;
+
+    fn try_from(value: &'a JSONValue) -> Result<Self, Self::Error> {
+        let json_array = value.as_array().ok_or_else(|| expected("array", value))?;
+        json_array
+            .iter()
+            .enumerate()
+            .map(|(index, item)| {
+                T::try_from(item).map_err(|error| error.prefix_path(&index.to_string()))
+            })
+            .collect()
+    }
+}
+
+impl<T> TryFrom<JSONValue> for Vec<T>
+where
+    T: TryFrom<JSONValue, Error = JSONError>,
+{
+    type Error = JSONError;
+
+    fn try_from(value: JSONValue) -> Result<Self, Self::Error> {
+        if !value.is_array() {
+            return Err(expected("array", &value));
+        }
+        value
+            .into_array()
+            .unwrap()
+            .into_iter()
+            .enumerate()
+            .map(|(index, item)| {
+                T::try_from(item).map_err(|error| error.prefix_path(&index.to_string()))
+            })
+            .collect()
+    }
+}
+
+impl<'a, T, const N: usize> TryFrom<&'a JSONValue> for [T; N]
+where
+    T: TryFrom<&'a JSONValue, Error = JSONError>,
+{
+    type Error = JSONError;
+
+    fn try_from(value: &'a JSONValue) -> Result<Self, Self::Error> {
+        let vec = Vec::<T>::try_from(value)?;
+        let length = vec.len();
+        vec.try_into().map_err(|_| {
+            JSONError::with_path(
+                &format!("Expected array of length <{}>, got <{}>", N, length),
+                "",
+            )
+        })
+    }
+}
+
+impl<T, const N: usize> TryFrom<JSONValue> for [T; N]
+where
+    T: TryFrom<JSONValue, Error = JSONError>,
+{
+    type Error = JSONError;
+
+    fn try_from(value: JSONValue) -> Result<Self, Self::Error> {
+        let vec = Vec::<T>::try_from(value)?;
+        let length = vec.len();
+        vec.try_into().map_err(|_| {
+            JSONError::with_path(
+                &format!("Expected array of length <{}>, got <{}>", N, length),
+                "",
+            )
+        })
+    }
+}
+
+/// Convert every value of a JSON Object, prefixing errors with the key they happened at
+fn try_collect_object<'a, T, C>(value: &'a JSONValue) -> Result<C, JSONError>
+where
+    T: TryFrom<&'a JSONValue, Error = JSONError>,
+    C: FromIterator<(String, T)>,
+{
+    let json_object = value.as_object().ok_or_else(|| expected("object", value))?;
+    json_object
+        .iter()
+        .map(|(key, item)| match T::try_from(item) {
+            Ok(item) => Ok((key.clone(), item)),
+            Err(error) => Err(error.prefix_path(key)),
+        })
+        .collect()
+}
+
+fn try_collect_owned_object<T, C>(value: JSONValue) -> Result<C, JSONError>
+where
+    T: TryFrom<JSONValue, Error = JSONError>,
+    C: FromIterator<(String, T)>,
+{
+    if !value.is_object() {
+        return Err(expected("object", &value));
+    }
+    value
+        .into_object()
+        .unwrap()
+        .into_iter()
+        .map(|(key, item)| match T::try_from(item) {
+            Ok(item) => Ok((key, item)),
+            Err(error) => Err(error.prefix_path(&key)),
+        })
+        .collect()
+}
+
+impl<'a, T, S> TryFrom<&'a JSONValue> for HashMap<String, T, S>
+where
+    T: TryFrom<&'a JSONValue, Error = JSONError>,
+    S: BuildHasher + Default,
+{
+    type Error = JSONError;
+
+    fn try_from(value: &'a JSONValue) -> Result<Self, Self::Error> {
+        try_collect_object(value)
+    }
+}
+
+impl<T, S> TryFrom<JSONValue> for HashMap<String, T, S>
+where
+    T: TryFrom<JSONValue, Error = JSONError>,
+    S: BuildHasher + Default,
+{
+    type Error = JSONError;
+
+    fn try_from(value: JSONValue) -> Result<Self, Self::Error> {
+        try_collect_owned_object(value)
+    }
+}
+
+impl<'a, T> TryFrom<&'a JSONValue> for BTreeMap<String, T>
+where
+    T: TryFrom<&'a JSONValue, Error = JSONError>,
+{
+    type Error = JSONError;
+
+    fn try_from(value: &'a JSONValue) -> Result<Self, Self::Error> {
+        try_collect_object(value)
+    }
+}
+
+impl<T> TryFrom<JSONValue> for BTreeMap<String, T>
+where
+    T: TryFrom<JSONValue, Error = JSONError>,
+{
+    type Error = JSONError;
+
+    fn try_from(value: JSONValue) -> Result<Self, Self::Error> {
+        try_collect_owned_object(value)
+    }
+}
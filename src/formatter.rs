@@ -0,0 +1,470 @@
+use std::fmt;
+
+/// Controls the characters written around each piece of a JSON Value when it is serialized.
+///
+/// Every method has a default that matches [`CompactFormatter`],
+/// so a custom style only needs to override the hooks it cares about.
+///
+/// # Example
+///
+/// ```
+/// use std::fmt;
+/// use parson::{Formatter, JSONValue};
+///
+/// struct SpacedColonFormatter;
+///
+/// impl Formatter for SpacedColonFormatter {
+///     fn begin_object_value<W: ?Sized + fmt::Write>(&mut self, writer: &mut W) -> fmt::Result {
+///         writer.write_str(" : ")
+///     }
+/// }
+///
+/// let json_value = r#"{ "key": "value" }"#.parse::<JSONValue>().unwrap();
+/// assert_eq!(
+///     json_value.to_string_with_formatter(&mut SpacedColonFormatter),
+///     r#"{ "key" : "value" }"#
+/// );
+/// ```
+pub trait Formatter {
+    /// Write a JSON Null
+    fn write_null<W: ?Sized + fmt::Write>(&mut self, writer: &mut W) -> fmt::Result {
+        writer.write_str("null")
+    }
+
+    /// Write a JSON Boolean
+    fn write_boolean<W: ?Sized + fmt::Write>(
+        &mut self,
+        writer: &mut W,
+        value: bool,
+    ) -> fmt::Result {
+        writer.write_str(if value { "true" } else { "false" })
+    }
+
+    /// Write a JSON Number
+    fn write_number<W: ?Sized + fmt::Write>(&mut self, writer: &mut W, value: f64) -> fmt::Result {
+        write!(writer, "{}", value)
+    }
+
+    /// Write a JSON String, or the key of a JSON Object, including its quotes
+    fn write_string<W: ?Sized + fmt::Write>(&mut self, writer: &mut W, value: &str) -> fmt::Result {
+        write_escaped_string(writer, value)
+    }
+
+    /// Called before the first element of a JSON Array
+    fn begin_array<W: ?Sized + fmt::Write>(&mut self, writer: &mut W) -> fmt::Result {
+        writer.write_str("[ ")
+    }
+
+    /// Called after the last element of a JSON Array
+    fn end_array<W: ?Sized + fmt::Write>(&mut self, writer: &mut W) -> fmt::Result {
+        writer.write_str(" ]")
+    }
+
+    /// Called before every element of a JSON Array
+    fn begin_array_value<W: ?Sized + fmt::Write>(
+        &mut self,
+        writer: &mut W,
+        first: bool,
+    ) -> fmt::Result {
+        if first {
+            Ok(())
+        } else {
+            writer.write_str(", ")
+        }
+    }
+
+    /// Called after every element of a JSON Array
+    fn end_array_value<W: ?Sized + fmt::Write>(&mut self, _writer: &mut W) -> fmt::Result {
+        Ok(())
+    }
+
+    /// Called before the first key of a JSON Object
+    fn begin_object<W: ?Sized + fmt::Write>(&mut self, writer: &mut W) -> fmt::Result {
+        writer.write_str("{ ")
+    }
+
+    /// Called after the last value of a JSON Object
+    fn end_object<W: ?Sized + fmt::Write>(&mut self, writer: &mut W) -> fmt::Result {
+        writer.write_str(" }")
+    }
+
+    /// Called before every key of a JSON Object
+    fn begin_object_key<W: ?Sized + fmt::Write>(
+        &mut self,
+        writer: &mut W,
+        first: bool,
+    ) -> fmt::Result {
+        if first {
+            Ok(())
+        } else {
+            writer.write_str(", ")
+        }
+    }
+
+    /// Called after every key of a JSON Object
+    fn end_object_key<W: ?Sized + fmt::Write>(&mut self, _writer: &mut W) -> fmt::Result {
+        Ok(())
+    }
+
+    /// Called between a key of a JSON Object and its value
+    fn begin_object_value<W: ?Sized + fmt::Write>(&mut self, writer: &mut W) -> fmt::Result {
+        writer.write_str(": ")
+    }
+
+    /// Called after every value of a JSON Object
+    fn end_object_value<W: ?Sized + fmt::Write>(&mut self, _writer: &mut W) -> fmt::Result {
+        Ok(())
+    }
+}
+
+/// Write a string in quotes, escaping only the characters JSON does not allow as they are:
+/// quotation marks, backslashes and control characters
+pub(crate) fn write_escaped_string<W: ?Sized + fmt::Write>(
+    writer: &mut W,
+    value: &str,
+) -> fmt::Result {
+    writer.write_char('"')?;
+
+    let mut start = 0;
+    for (index, char) in value.char_indices() {
+        let escaped = match char {
+            '"' => "\\\"",
+            '\\' => "\\\\",
+            '\u{8}' => "\\b",
+            '\u{c}' => "\\f",
+            '\n' => "\\n",
+            '\r' => "\\r",
+            '\t' => "\\t",
+            '\u{0}'..='\u{1f}' => "",
+            _ => continue,
+        };
+
+        writer.write_str(&value[start..index])?;
+        if escaped.is_empty() {
+            write_unicode_escape(writer, char)?;
+        } else {
+            writer.write_str(escaped)?;
+        }
+        start = index + char.len_utf8();
+    }
+
+    writer.write_str(&value[start..])?;
+    writer.write_char('"')
+}
+
+/// Write a character as one `\uXXXX` escape, or a surrogate pair of them
+fn write_unicode_escape<W: ?Sized + fmt::Write>(writer: &mut W, char: char) -> fmt::Result {
+    for unit in char.encode_utf16(&mut [0; 2]) {
+        write!(writer, "\\u{:04x}", unit)?;
+    }
+    Ok(())
+}
+
+/// Writes a JSON Value on a single line, the same way `to_string()` does
+#[derive(Debug, Clone, Default)]
+pub struct CompactFormatter;
+
+impl Formatter for CompactFormatter {}
+
+/// Writes every element of a JSON Array and every pair of a JSON Object on its own line
+///
+/// # Example
+///
+/// ```
+/// use parson::{JSONValue, PrettyFormatter};
+///
+/// let json_value = r#"{ "key": [1, {}] }"#.parse::<JSONValue>().unwrap();
+/// assert_eq!(
+///     json_value.to_string_with_formatter(&mut PrettyFormatter::with_indent("\t")),
+///     "{\n\t\"key\": [\n\t\t1,\n\t\t{}\n\t]\n}"
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct PrettyFormatter {
+    indent: String,
+    level: usize,
+    has_value: bool,
+}
+
+impl Default for PrettyFormatter {
+    fn default() -> Self {
+        PrettyFormatter::with_spaces(2)
+    }
+}
+
+impl PrettyFormatter {
+    /// Create a new Pretty Formatter that indents with 2 spaces
+    pub fn new() -> Self {
+        PrettyFormatter::default()
+    }
+
+    /// Create a new Pretty Formatter that indents with `spaces` spaces
+    pub fn with_spaces(spaces: usize) -> Self {
+        PrettyFormatter::with_indent(&" ".repeat(spaces))
+    }
+
+    /// Create a new Pretty Formatter that writes `indent` once per level of nesting
+    pub fn with_indent(indent: &str) -> Self {
+        PrettyFormatter {
+            indent: indent.to_string(),
+            level: 0,
+            has_value: false,
+        }
+    }
+
+    /// Start the output `level` levels of nesting deep
+    pub(crate) fn at_level(mut self, level: usize) -> Self {
+        self.level = level;
+        self
+    }
+
+    fn write_newline<W: ?Sized + fmt::Write>(&self, writer: &mut W) -> fmt::Result {
+        writer.write_char('\n')?;
+        for _ in 0..self.level {
+            writer.write_str(&self.indent)?;
+        }
+        Ok(())
+    }
+
+    fn begin<W: ?Sized + fmt::Write>(&mut self, writer: &mut W, bracket: char) -> fmt::Result {
+        self.level += 1;
+        self.has_value = false;
+        writer.write_char(bracket)
+    }
+
+    fn end<W: ?Sized + fmt::Write>(&mut self, writer: &mut W, bracket: char) -> fmt::Result {
+        self.level -= 1;
+        if self.has_value {
+            self.write_newline(writer)?;
+        }
+        writer.write_char(bracket)
+    }
+
+    fn begin_item<W: ?Sized + fmt::Write>(&mut self, writer: &mut W, first: bool) -> fmt::Result {
+        if !first {
+            writer.write_char(',')?;
+        }
+        self.write_newline(writer)
+    }
+}
+
+impl Formatter for PrettyFormatter {
+    fn begin_array<W: ?Sized + fmt::Write>(&mut self, writer: &mut W) -> fmt::Result {
+        self.begin(writer, '[')
+    }
+
+    fn end_array<W: ?Sized + fmt::Write>(&mut self, writer: &mut W) -> fmt::Result {
+        self.end(writer, ']')
+    }
+
+    fn begin_array_value<W: ?Sized + fmt::Write>(
+        &mut self,
+        writer: &mut W,
+        first: bool,
+    ) -> fmt::Result {
+        self.begin_item(writer, first)
+    }
+
+    fn end_array_value<W: ?Sized + fmt::Write>(&mut self, _writer: &mut W) -> fmt::Result {
+        self.has_value = true;
+        Ok(())
+    }
+
+    fn begin_object<W: ?Sized + fmt::Write>(&mut self, writer: &mut W) -> fmt::Result {
+        self.begin(writer, '{')
+    }
+
+    fn end_object<W: ?Sized + fmt::Write>(&mut self, writer: &mut W) -> fmt::Result {
+        self.end(writer, '}')
+    }
+
+    fn begin_object_key<W: ?Sized + fmt::Write>(
+        &mut self,
+        writer: &mut W,
+        first: bool,
+    ) -> fmt::Result {
+        self.begin_item(writer, first)
+    }
+
+    fn end_object_value<W: ?Sized + fmt::Write>(&mut self, _writer: &mut W) -> fmt::Result {
+        self.has_value = true;
+        Ok(())
+    }
+}
+
+/// Wraps another [`Formatter`], escaping more characters in every JSON String and key it writes
+///
+/// Strings are still written by the wrapped formatter,
+/// and the extra escaping is applied to what it writes
+///
+/// # Example
+///
+/// ```
+/// use parson::{CompactFormatter, EscapeFormatter, JSONValue};
+///
+/// let json_value = r#"{ "café": "</script> 😀" }"#.parse::<JSONValue>().unwrap();
+/// let mut formatter = EscapeFormatter::new(CompactFormatter)
+///     .ascii_only(true)
+///     .html_safe(true);
+/// assert_eq!(
+///     json_value.to_string_with_formatter(&mut formatter),
+///     r#"{ "caf\u00e9": "\u003c/script\u003e \ud83d\ude00" }"#
+/// );
+/// ```
+///
+/// A wrapped formatter that writes strings its own way keeps doing so:
+///
+/// ```
+/// use std::fmt;
+/// use parson::{EscapeFormatter, Formatter, JSONValue};
+///
+/// struct UppercaseFormatter;
+///
+/// impl Formatter for UppercaseFormatter {
+///     fn write_string<W: ?Sized + fmt::Write>(&mut self, writer: &mut W, value: &str) -> fmt::Result {
+///         write!(writer, "\"{}\"", value.to_uppercase())
+///     }
+/// }
+///
+/// let json_value = r#"["café"]"#.parse::<JSONValue>().unwrap();
+/// let mut formatter = EscapeFormatter::new(UppercaseFormatter).ascii_only(true);
+/// assert_eq!(json_value.to_string_with_formatter(&mut formatter), r#"[ "CAF\u00c9" ]"#);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct EscapeFormatter<F: Formatter> {
+    formatter: F,
+    ascii_only: bool,
+    html_safe: bool,
+}
+
+impl<F: Formatter> EscapeFormatter<F> {
+    /// Create a new Escape Formatter around `formatter`, with no extra escaping turned on
+    pub fn new(formatter: F) -> Self {
+        EscapeFormatter {
+            formatter,
+            ascii_only: false,
+            html_safe: false,
+        }
+    }
+
+    /// Set whether every non-ASCII character is escaped as `\uXXXX`,
+    /// using a surrogate pair for characters outside the Basic Multilingual Plane
+    pub fn ascii_only(mut self, ascii_only: bool) -> Self {
+        self.ascii_only = ascii_only;
+        self
+    }
+
+    /// Set whether `<`, `>`, `&`, U+2028 and U+2029 are escaped,
+    /// so the output can be embedded inside HTML and `<script>` tags
+    pub fn html_safe(mut self, html_safe: bool) -> Self {
+        self.html_safe = html_safe;
+        self
+    }
+
+    /// Unwrap the formatter inside the Escape Formatter
+    pub fn into_inner(self) -> F {
+        self.formatter
+    }
+}
+
+/// Escapes the extra characters an [`EscapeFormatter`] asks for
+/// in whatever the wrapped formatter writes for a JSON String
+struct EscapeWriter<'a, W: ?Sized + fmt::Write> {
+    writer: &'a mut W,
+    ascii_only: bool,
+    html_safe: bool,
+}
+
+impl<'a, W: ?Sized + fmt::Write> fmt::Write for EscapeWriter<'a, W> {
+    fn write_str(&mut self, string: &str) -> fmt::Result {
+        let mut start = 0;
+        for (index, char) in string.char_indices() {
+            match char {
+                '<' | '>' | '&' | '\u{2028}' | '\u{2029}' if self.html_safe => {}
+                '\u{80}'.. if self.ascii_only => {}
+                _ => continue,
+            }
+
+            self.writer.write_str(&string[start..index])?;
+            write_unicode_escape(self.writer, char)?;
+            start = index + char.len_utf8();
+        }
+
+        self.writer.write_str(&string[start..])
+    }
+}
+
+impl<F: Formatter> Formatter for EscapeFormatter<F> {
+    fn write_null<W: ?Sized + fmt::Write>(&mut self, writer: &mut W) -> fmt::Result {
+        self.formatter.write_null(writer)
+    }
+
+    fn write_boolean<W: ?Sized + fmt::Write>(
+        &mut self,
+        writer: &mut W,
+        value: bool,
+    ) -> fmt::Result {
+        self.formatter.write_boolean(writer, value)
+    }
+
+    fn write_number<W: ?Sized + fmt::Write>(&mut self, writer: &mut W, value: f64) -> fmt::Result {
+        self.formatter.write_number(writer, value)
+    }
+
+    fn write_string<W: ?Sized + fmt::Write>(&mut self, writer: &mut W, value: &str) -> fmt::Result {
+        let mut writer = EscapeWriter {
+            writer,
+            ascii_only: self.ascii_only,
+            html_safe: self.html_safe,
+        };
+        self.formatter.write_string(&mut writer, value)
+    }
+
+    fn begin_array<W: ?Sized + fmt::Write>(&mut self, writer: &mut W) -> fmt::Result {
+        self.formatter.begin_array(writer)
+    }
+
+    fn end_array<W: ?Sized + fmt::Write>(&mut self, writer: &mut W) -> fmt::Result {
+        self.formatter.end_array(writer)
+    }
+
+    fn begin_array_value<W: ?Sized + fmt::Write>(
+        &mut self,
+        writer: &mut W,
+        first: bool,
+    ) -> fmt::Result {
+        self.formatter.begin_array_value(writer, first)
+    }
+
+    fn end_array_value<W: ?Sized + fmt::Write>(&mut self, writer: &mut W) -> fmt::Result {
+        self.formatter.end_array_value(writer)
+    }
+
+    fn begin_object<W: ?Sized + fmt::Write>(&mut self, writer: &mut W) -> fmt::Result {
+        self.formatter.begin_object(writer)
+    }
+
+    fn end_object<W: ?Sized + fmt::Write>(&mut self, writer: &mut W) -> fmt::Result {
+        self.formatter.end_object(writer)
+    }
+
+    fn begin_object_key<W: ?Sized + fmt::Write>(
+        &mut self,
+        writer: &mut W,
+        first: bool,
+    ) -> fmt::Result {
+        self.formatter.begin_object_key(writer, first)
+    }
+
+    fn end_object_key<W: ?Sized + fmt::Write>(&mut self, writer: &mut W) -> fmt::Result {
+        self.formatter.end_object_key(writer)
+    }
+
+    fn begin_object_value<W: ?Sized + fmt::Write>(&mut self, writer: &mut W) -> fmt::Result {
+        self.formatter.begin_object_value(writer)
+    }
+
+    fn end_object_value<W: ?Sized + fmt::Write>(&mut self, writer: &mut W) -> fmt::Result {
+        self.formatter.end_object_value(writer)
+    }
+}
@@ -1,76 +1,85 @@
-use std::fmt::Display;
+use std::{
+    cmp::Ordering,
+    fmt::{self, Display},
+};
 
-use crate::JSONValue;
+use crate::{
+    serializer::Serializer, shared::Shared, CompactFormatter, Formatter, JSONValue, PrettyFormatter,
+};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct JSONArray {
-    data: Vec<JSONValue>,
+    data: Shared<Vec<JSONValue>>,
 }
 
+/// Convert JSON Array to a Rust owned string
+///
+/// # Example
+///
+/// ```
+/// use parson::{JSONArray, JSONObject, JSONValue};
+///
+/// let mut json_array = JSONArray::new();
+/// assert_eq!(json_array.to_string(), "[  ]");
+/// json_array.push(JSONValue::from_object(JSONObject::new()));
+/// assert_eq!(json_array.to_string(), "[ {  } ]");
+/// json_array.push(JSONValue::from_array(JSONArray::new()));
+/// assert_eq!(json_array.to_string(), "[ {  }, [  ] ]");
+/// ```
 impl Display for JSONArray {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.to_string())
+        self.serialize(&mut Serializer::new(f, &mut CompactFormatter))
     }
 }
 
 impl JSONArray {
     /// Create a new empty JSON Array
     pub fn new() -> Self {
-        JSONArray { data: vec![] }
+        JSONArray {
+            data: Shared::new(vec![]),
+        }
     }
 
-    /// Convert JSON Array to a Rust owned string
+    /// Format JSON Array to a Rust owned string
     ///
     /// # Example
     ///
     /// ```
-    /// use parson::{JSONArray, JSONObject, JSONValue};
+    /// use parson::{JSONArray, JSONNumber, JSONValue};
     ///
     /// let mut json_array = JSONArray::new();
-    /// assert_eq!(json_array.to_string(), "[  ]");
-    /// json_array.push(JSONValue::from_object(JSONObject::new()));
-    /// assert_eq!(json_array.to_string(), "[ {  } ]");
-    /// json_array.push(JSONValue::from_array(JSONArray::new()));
-    /// assert_eq!(json_array.to_string(), "[ {  }, [  ] ]");
+    /// assert_eq!(json_array.format_string(0, 2), "[]");
+    /// json_array.push(JSONValue::from_number(JSONNumber::new(1.0)));
+    /// json_array.push(JSONValue::from_number(JSONNumber::new(2.0)));
+    /// assert_eq!(json_array.format_string(0, 2), "[\n  1,\n  2\n]");
     /// ```
-    pub fn to_string(&self) -> String {
-        let mut result = "[ ".to_string();
-
-        for value in self.data.iter() {
-            result.push_str(&format!("{}, ", value.to_string()));
-        }
-
-        if self.data.len() > 0 {
-            result.pop();
-            result.pop();
-        }
-
-        result.push_str(" ]");
+    pub fn format_string(&self, indents: i32, spaces: i32) -> String {
+        let mut result = String::new();
+        let mut formatter =
+            PrettyFormatter::with_spaces(spaces as usize).at_level(indents as usize);
+        self.serialize(&mut Serializer::new(&mut result, &mut formatter))
+            .expect("writing to a String cannot fail");
         result
     }
 
-    pub fn format_string(&self, indents: i32, spaces: i32) -> String {
-        if self.data.len() == 0 {
-            return String::from("[]");
-        }
-
-        let mut result = "[".to_string();
-
-        for value in self.data.iter() {
-            result.push_str(&format!(
-                "\n{}{},",
-                " ".repeat(((indents + 1) * spaces) as usize),
-                value.format_string(indents + 1, spaces)
-            ));
+    pub(crate) fn serialize<W: fmt::Write, F: Formatter>(
+        &self,
+        serializer: &mut Serializer<W, F>,
+    ) -> fmt::Result {
+        serializer.formatter.begin_array(serializer.writer)?;
+        for (index, value) in self.data.iter().enumerate() {
+            serializer
+                .formatter
+                .begin_array_value(serializer.writer, index == 0)?;
+            value.serialize(serializer)?;
+            serializer.formatter.end_array_value(serializer.writer)?;
         }
-
-        result.push_str(&format!("\n{}]", " ".repeat((indents * spaces) as usize)));
-        result
+        serializer.formatter.end_array(serializer.writer)
     }
 
     /// Convert JSON Array to a Rust Vector
     pub fn to_vec(&self) -> Vec<JSONValue> {
-        self.data.clone()
+        self.data.to_vec()
     }
 
     /// Get the number of elements in the JSON Array
@@ -78,6 +87,11 @@ impl JSONArray {
         self.data.len()
     }
 
+    /// If the JSON Array has no elements
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
     /// Add push JSON Value to back of the JSON Array
     pub fn push(&mut self, value: JSONValue) {
         self.data.push(value);
@@ -87,4 +101,182 @@ impl JSONArray {
     pub fn get(&self, index: usize) -> Option<&JSONValue> {
         self.data.get(index)
     }
+
+    /// Get a mutable reference to the value at a specific index in the JSON Array
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use parson::{JSONArray, JSONNumber, JSONValue};
+    ///
+    /// let mut json_array = JSONArray::new();
+    /// json_array.push(JSONValue::from_number(JSONNumber::new(1.0)));
+    /// *json_array.get_mut(0).unwrap() = JSONValue::from_number(JSONNumber::new(2.0));
+    /// assert_eq!(json_array.to_string(), "[ 2 ]");
+    /// ```
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut JSONValue> {
+        self.data.get_mut(index)
+    }
+
+    /// Insert a JSON Value at a specific index in the JSON Array,
+    /// shifting every element after it to the right
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the length of the JSON Array
+    pub fn insert(&mut self, index: usize, value: JSONValue) {
+        self.data.insert(index, value);
+    }
+
+    /// Remove and return the value at a specific index in the JSON Array,
+    /// shifting every element after it to the left
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use parson::JSONValue;
+    ///
+    /// let mut json_value = "[1, 2, 3]".parse::<JSONValue>().unwrap();
+    /// let json_array = json_value.as_array_mut().unwrap();
+    /// assert_eq!(json_array.remove(1).get_number().unwrap(), 2.0);
+    /// json_array.insert(0, json_array.get(1).unwrap().clone());
+    /// assert_eq!(json_array.to_string(), "[ 3, 1, 3 ]");
+    /// ```
+    pub fn remove(&mut self, index: usize) -> JSONValue {
+        self.data.remove(index)
+    }
+
+    /// Remove and return the last value of the JSON Array, if there is one
+    pub fn pop(&mut self) -> Option<JSONValue> {
+        self.data.pop()
+    }
+
+    /// Keep only the values for which `keep` returns true
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use parson::JSONValue;
+    ///
+    /// let mut json_value = "[1, null, 2, null]".parse::<JSONValue>().unwrap();
+    /// json_value.as_array_mut().unwrap().retain(|value| !value.is_null());
+    /// assert_eq!(json_value.to_string(), "[ 1, 2 ]");
+    /// ```
+    pub fn retain<F: FnMut(&JSONValue) -> bool>(&mut self, keep: F) {
+        self.data.retain(keep);
+    }
+
+    /// Remove every value from the JSON Array
+    pub fn clear(&mut self) {
+        self.data.clear();
+    }
+
+    /// Sort the JSON Array in place with a comparator function, keeping equal values in order
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use parson::JSONValue;
+    ///
+    /// let mut json_value = "[3, 1, 2]".parse::<JSONValue>().unwrap();
+    /// json_value.as_array_mut().unwrap().sort_by(|a, b| {
+    ///     a.get_number().unwrap().total_cmp(&b.get_number().unwrap())
+    /// });
+    /// assert_eq!(json_value.to_string(), "[ 1, 2, 3 ]");
+    /// ```
+    pub fn sort_by<F: FnMut(&JSONValue, &JSONValue) -> Ordering>(&mut self, compare: F) {
+        self.data.sort_by(compare);
+    }
+
+    /// Iterate over references to the values in the JSON Array
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use parson::JSONValue;
+    ///
+    /// let json_value = "[1, 2, 3]".parse::<JSONValue>().unwrap();
+    /// let json_array = json_value.as_array().unwrap();
+    /// let sum = json_array
+    ///     .iter()
+    ///     .filter_map(|value| value.as_f64())
+    ///     .sum::<f64>();
+    /// assert_eq!(sum, 6.0);
+    /// ```
+    pub fn iter(&self) -> std::slice::Iter<'_, JSONValue> {
+        self.data.iter()
+    }
+
+    /// Iterate over mutable references to the values in the JSON Array
+    pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, JSONValue> {
+        self.data.iter_mut()
+    }
+
+    /// Get the values in the JSON Array as a slice
+    pub fn as_slice(&self) -> &[JSONValue] {
+        &self.data
+    }
+
+    /// Get the values in the JSON Array as a mutable slice
+    pub fn as_mut_slice(&mut self) -> &mut [JSONValue] {
+        &mut self.data
+    }
+}
+
+impl IntoIterator for JSONArray {
+    type Item = JSONValue;
+    type IntoIter = std::vec::IntoIter<JSONValue>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.data.into_inner().into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a JSONArray {
+    type Item = &'a JSONValue;
+    type IntoIter = std::slice::Iter<'a, JSONValue>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut JSONArray {
+    type Item = &'a mut JSONValue;
+    type IntoIter = std::slice::IterMut<'a, JSONValue>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// Collect JSON Values into a JSON Array
+///
+/// # Example
+///
+/// ```
+/// use parson::{JSONArray, JSONNumber, JSONValue};
+///
+/// let mut json_array = (1..=3)
+///     .map(|number| JSONValue::from_number(JSONNumber::new(number as f64)))
+///     .collect::<JSONArray>();
+/// json_array.extend(vec![JSONValue::from_number(JSONNumber::new(4.0))]);
+/// assert_eq!(json_array.to_string(), "[ 1, 2, 3, 4 ]");
+/// ```
+impl FromIterator<JSONValue> for JSONArray {
+    fn from_iter<I: IntoIterator<Item = JSONValue>>(iter: I) -> Self {
+        JSONArray {
+            data: Shared::new(iter.into_iter().collect()),
+        }
+    }
+}
+
+impl Extend<JSONValue> for JSONArray {
+    fn extend<I: IntoIterator<Item = JSONValue>>(&mut self, iter: I) {
+        self.data.extend(iter);
+    }
 }
@@ -1,13 +1,25 @@
-use std::fmt::Display;
+use std::fmt::{self, Display};
 
-#[derive(Debug, Clone)]
+use crate::{serializer::Serializer, CompactFormatter, Formatter};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct JSONBoolean {
     data: bool,
 }
 
+/// Convert JSON Boolean to a Rust owned string
+///
+/// # Example
+///
+/// ```
+/// use parson::JSONBoolean;
+///
+/// assert_eq!(JSONBoolean::new(true).to_string(), "true");
+/// assert_eq!(JSONBoolean::new(false).to_string(), "false");
+/// ```
 impl Display for JSONBoolean {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.to_string())
+        self.serialize(&mut Serializer::new(f, &mut CompactFormatter))
     }
 }
 
@@ -17,18 +29,13 @@ impl JSONBoolean {
         JSONBoolean { data }
     }
 
-    /// Convert JSON Boolean to a Rust owned string
-    ///
-    /// # Example
-    ///
-    /// ```
-    /// use parson::JSONBoolean;
-    ///
-    /// assert_eq!(JSONBoolean::new(true).to_string(), "true");
-    /// assert_eq!(JSONBoolean::new(false).to_string(), "false");
-    /// ```
-    pub fn to_string(&self) -> String {
-        self.data.to_string()
+    pub(crate) fn serialize<W: fmt::Write, F: Formatter>(
+        &self,
+        serializer: &mut Serializer<W, F>,
+    ) -> fmt::Result {
+        serializer
+            .formatter
+            .write_boolean(serializer.writer, self.data)
     }
 
     /// Get a Rust bool from the JSON Boolean
@@ -0,0 +1,876 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    str::FromStr,
+};
+
+use crate::{
+    json_pointer::{cannot_index, parse_index},
+    JSONError, JSONObject, JSONPointer, JSONValue,
+};
+
+/// A JSON document that replicas edit independently and merge in any order, always converging
+///
+/// The document is a conflict-free replicated data type modelled on Automerge.
+/// Every edit is a [`JSONCrdtOperation`] with an id made of a Lamport counter and the replica name,
+/// which orders all operations the same way on every replica:
+///
+/// - A key of a JSON Object or an item of a JSON Array is a last-writer-wins register,
+///   so of two values set concurrently the one with the greater id is kept.
+/// - Keys are add-wins, so a key removed on one replica while set on another is kept.
+///   With the `preserve_order` feature, the keys of a JSON Object are in the order they were first set.
+/// - JSON Arrays are a sequence where each item is inserted after another,
+///   so items inserted concurrently are all kept and never interleave.
+///
+/// Operations can arrive in any order and more than once.
+/// One whose dependencies have not arrived yet waits until they do.
+/// Each replica needs a unique name.
+/// Counters go up to `u64::MAX - 1`: an operation with a greater one does not parse,
+/// and a local edit which would need one fails.
+///
+/// # Example
+///
+/// ```
+/// use parson::{json, JSONCrdt};
+///
+/// let mut alice = JSONCrdt::new("alice");
+/// alice.set("/theme", json!("light")).unwrap();
+/// alice.set("/plugins", json!(["git"])).unwrap();
+///
+/// let mut bob = JSONCrdt::new("bob");
+/// bob.merge(&alice).unwrap();
+///
+/// // Both edit offline
+/// alice.set("/theme", json!("dark")).unwrap();
+/// alice.insert("/plugins/-", json!("lint")).unwrap();
+/// bob.set("/font", json!({ "size": 14 })).unwrap();
+/// bob.insert("/plugins/0", json!("vim")).unwrap();
+///
+/// // Then sync, sending the operations as JSON in any order
+/// let sent: Vec<String> = bob.operations().iter().map(|operation| operation.to_string()).collect();
+/// for operation in sent.iter().rev() {
+///     alice.apply(operation.parse().unwrap()).unwrap();
+/// }
+/// bob.merge(&alice).unwrap();
+///
+/// assert_eq!(alice.value(), bob.value());
+/// assert_eq!(
+///     alice.value(),
+///     json!({ "theme": "dark", "plugins": ["vim", "git", "lint"], "font": { "size": 14 } })
+/// );
+/// ```
+///
+/// Concurrent writes to the same key keep the greater id, and a concurrent set beats a removal
+///
+/// ```
+/// use parson::{json, JSONCrdt};
+///
+/// let mut a = JSONCrdt::new("a");
+/// a.set("/port", json!(80)).unwrap();
+/// a.set("/host", json!("localhost")).unwrap();
+/// let mut b = a.fork("b");
+///
+/// a.set("/port", json!(8080)).unwrap();
+/// b.set("/port", json!(9090)).unwrap();
+/// a.remove("/host").unwrap();
+/// b.set("/host", json!("db.internal")).unwrap();
+///
+/// a.merge(&b).unwrap();
+/// b.merge(&a).unwrap();
+/// assert_eq!(a.value(), json!({ "port": 9090, "host": "db.internal" }));
+/// assert_eq!(b.value(), a.value());
+/// ```
+#[derive(Debug, Clone)]
+pub struct JSONCrdt {
+    replica: String,
+    clock: u64,
+    containers: HashMap<OperationId, Container>,
+    applied: HashSet<OperationId>,
+    operations: Vec<JSONCrdtOperation>,
+    pending: Vec<JSONCrdtOperation>,
+}
+
+/// One edit of a [`JSONCrdt`], which can be sent to other replicas as JSON
+///
+/// # Example
+///
+/// ```
+/// use parson::{json, JSONCrdt, JSONCrdtOperation, JSONValue};
+///
+/// let mut doc = JSONCrdt::new("alice");
+/// doc.set("/name", json!("api")).unwrap();
+/// doc.set("/name", json!("gateway")).unwrap();
+///
+/// let operation = &doc.operations()[1];
+/// assert_eq!(
+///     JSONValue::from(operation),
+///     json!({ "id": "2@alice", "object": "_root", "action": "set", "key": "name", "value": "gateway", "pred": ["1@alice"] })
+/// );
+/// assert_eq!(&operation.to_string().parse::<JSONCrdtOperation>().unwrap(), operation);
+///
+/// let error = r#"{ "id": "3@bob", "object": "_root", "action": "move", "pred": [] }"#
+///     .parse::<JSONCrdtOperation>()
+///     .unwrap_err();
+/// assert_eq!(error.get_message(), "Unknown action <move> (at /action)");
+///
+/// let error = r#"{ "id": "18446744073709551615@bob", "object": "_root", "action": "set", "key": "a", "value": 1, "pred": [] }"#
+///     .parse::<JSONCrdtOperation>()
+///     .unwrap_err();
+/// assert_eq!(error.get_message(), "Invalid operation id <18446744073709551615@bob> (at /id)");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct JSONCrdtOperation {
+    id: OperationId,
+    object: OperationId,
+    action: Action,
+    pred: Vec<OperationId>,
+}
+
+/// A Lamport counter and the replica which made the operation.
+/// The root JSON Object has the counter 0
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct OperationId {
+    counter: u64,
+    replica: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Action {
+    /// Set a key or item, replacing the values in `pred`
+    Set { key: Key, content: Content },
+    /// Insert an item after another, or at the start of the JSON Array
+    Insert {
+        after: Option<OperationId>,
+        content: Content,
+    },
+    /// Remove the values in `pred` from a key or item
+    Delete { key: Key },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Key {
+    Name(String),
+    Item(OperationId),
+}
+
+/// A value an operation writes, where a JSON Object or JSON Array
+/// is a new container with the id of the operation
+#[derive(Debug, Clone, PartialEq)]
+enum Content {
+    Scalar(JSONValue),
+    Object,
+    Array,
+}
+
+#[derive(Debug, Clone)]
+enum Container {
+    Object(HashMap<String, Register>),
+    Array(Vec<Register>),
+}
+
+/// The values of a key or item which no operation has replaced,
+/// where the one with the greatest id wins. A key or item without values is removed
+#[derive(Debug, Clone)]
+struct Register {
+    /// For a key the first operation that set it, and for an item the operation that inserted it
+    first: OperationId,
+    values: Vec<(OperationId, Content)>,
+}
+
+/// Where a local edit writes
+enum Place {
+    Key(Key),
+    After(Option<OperationId>),
+}
+
+/// A key or item a JSON Pointer refers to
+struct Slot {
+    /// `None` past the end of a JSON Array
+    key: Option<Key>,
+    /// The ids of the current values
+    pred: Vec<OperationId>,
+    /// The winning value
+    current: Option<(OperationId, Content)>,
+    /// The item before, for inserting into a JSON Array
+    after: Option<OperationId>,
+}
+
+impl JSONCrdt {
+    /// Create an empty document, a JSON Object, edited by the replica `replica`
+    pub fn new(replica: &str) -> Self {
+        let root = OperationId::root();
+        JSONCrdt {
+            replica: replica.to_string(),
+            clock: 0,
+            containers: HashMap::from([(root.clone(), Container::Object(HashMap::new()))]),
+            applied: HashSet::from([root]),
+            operations: Vec::new(),
+            pending: Vec::new(),
+        }
+    }
+
+    /// Copy the document for another replica named `replica`
+    pub fn fork(&self, replica: &str) -> Self {
+        JSONCrdt {
+            replica: replica.to_string(),
+            ..self.clone()
+        }
+    }
+
+    /// Get the name of the replica
+    pub fn replica(&self) -> &str {
+        &self.replica
+    }
+
+    /// Get the JSON Value of the document
+    pub fn value(&self) -> JSONValue {
+        self.materialize(&OperationId::root(), &Content::Object)
+    }
+
+    /// Get every operation applied to the document, in an order another replica can apply them in
+    pub fn operations(&self) -> &[JSONCrdtOperation] {
+        &self.operations
+    }
+
+    /// Get the number of operations received whose dependencies have not arrived yet
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Set the value `pointer` refers to, adding a key to a JSON Object or an item
+    /// to the end of a JSON Array with `-`
+    pub fn set(&mut self, pointer: &str, value: JSONValue) -> Result<(), JSONError> {
+        let pointer = JSONPointer::parse(pointer)?;
+        let (object, slot) = self.locate(&pointer, true)?;
+        self.reserve(count_operations(&value))?;
+        let place = match slot.key {
+            Some(key) => Place::Key(key),
+            None => Place::After(slot.after),
+        };
+        self.write(object, place, slot.pred, &value);
+        Ok(())
+    }
+
+    /// Insert a value where `pointer` refers to, so an index into a JSON Array
+    /// shifts the items from that index up
+    pub fn insert(&mut self, pointer: &str, value: JSONValue) -> Result<(), JSONError> {
+        let pointer = JSONPointer::parse(pointer)?;
+        let (object, slot) = self.locate(&pointer, true)?;
+        self.reserve(count_operations(&value))?;
+        let (place, pred) = match slot.key {
+            Some(Key::Name(name)) => (Place::Key(Key::Name(name)), slot.pred),
+            _ => (Place::After(slot.after), Vec::new()),
+        };
+        self.write(object, place, pred, &value);
+        Ok(())
+    }
+
+    /// Remove the value `pointer` refers to
+    pub fn remove(&mut self, pointer: &str) -> Result<(), JSONError> {
+        let pointer = JSONPointer::parse(pointer)?;
+        let (object, slot) = self.locate(&pointer, false)?;
+        let Some(key) = slot.key else {
+            unreachable!("a removed item is inside the JSON Array");
+        };
+        if slot.current.is_none() {
+            let last = pointer.last().unwrap_or_default();
+            return Err(pointer.error(
+                pointer.tokens().len() - 1,
+                &format!("Key <{}> not found", last),
+            ));
+        }
+        self.reserve(1)?;
+        self.local(object, Action::Delete { key }, slot.pred);
+        Ok(())
+    }
+
+    /// Apply an operation from another replica.
+    ///
+    /// An operation already applied is ignored, and one whose dependencies have not arrived yet
+    /// is held until they do. An operation which does not fit the document is dropped with an error
+    pub fn apply(&mut self, operation: JSONCrdtOperation) -> Result<(), JSONError> {
+        if self.applied.contains(&operation.id)
+            || self
+                .pending
+                .iter()
+                .any(|pending| pending.id == operation.id)
+        {
+            return Ok(());
+        }
+        self.pending.push(operation);
+
+        let mut result = Ok(());
+        while let Some(index) = self
+            .pending
+            .iter()
+            .position(|operation| self.is_ready(operation))
+        {
+            let operation = self.pending.remove(index);
+            match self.check(&operation) {
+                Ok(()) => self.integrate(operation),
+                Err(error) => {
+                    if result.is_ok() {
+                        result = Err(error);
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    /// Apply every operation of another replica
+    pub fn merge(&mut self, other: &JSONCrdt) -> Result<(), JSONError> {
+        for operation in &other.operations {
+            self.apply(operation.clone())?;
+        }
+        Ok(())
+    }
+
+    /// Find the container a JSON Pointer points into, and the key or item in it
+    fn locate(
+        &self,
+        pointer: &JSONPointer,
+        allow_end: bool,
+    ) -> Result<(OperationId, Slot), JSONError> {
+        let tokens = pointer.tokens();
+        if tokens.is_empty() {
+            return Err(JSONError::with_path(
+                "Cannot replace or remove the root of a JSON CRDT document",
+                "",
+            ));
+        }
+
+        let mut object = OperationId::root();
+        for (depth, token) in tokens.iter().enumerate() {
+            let last = depth + 1 == tokens.len();
+            let slot = self
+                .slot(&object, token, last && allow_end)
+                .map_err(|message| pointer.error(depth, &message))?;
+            if last {
+                return Ok((object, slot));
+            }
+            object = match slot.current {
+                Some((id, Content::Object | Content::Array)) => id,
+                Some((_, Content::Scalar(value))) => {
+                    return Err(pointer.error(depth + 1, &cannot_index(&value, &tokens[depth + 1])))
+                }
+                None => return Err(pointer.error(depth, &format!("Key <{}> not found", token))),
+            };
+        }
+        unreachable!("the JSON Pointer has tokens");
+    }
+
+    fn slot(&self, object: &OperationId, token: &str, allow_end: bool) -> Result<Slot, String> {
+        match &self.containers[object] {
+            Container::Object(keys) => {
+                let register = keys.get(token);
+                Ok(Slot {
+                    key: Some(Key::Name(token.to_string())),
+                    pred: register.map(Register::ids).unwrap_or_default(),
+                    current: register.and_then(Register::winner).cloned(),
+                    after: None,
+                })
+            }
+            Container::Array(items) => {
+                let visible: Vec<&Register> = items.iter().filter(|item| item.is_set()).collect();
+                let index = parse_index(token, visible.len(), allow_end)?;
+                let after = index
+                    .checked_sub(1)
+                    .map(|before| visible[before].first.clone());
+                Ok(match visible.get(index) {
+                    Some(item) => Slot {
+                        key: Some(Key::Item(item.first.clone())),
+                        pred: item.ids(),
+                        current: item.winner().cloned(),
+                        after,
+                    },
+                    None => Slot {
+                        key: None,
+                        pred: Vec::new(),
+                        current: None,
+                        after,
+                    },
+                })
+            }
+        }
+    }
+
+    /// Make the operations which write `value`, and the members or items inside it
+    fn write(
+        &mut self,
+        object: OperationId,
+        place: Place,
+        pred: Vec<OperationId>,
+        value: &JSONValue,
+    ) -> OperationId {
+        let content = if value.is_object() {
+            Content::Object
+        } else if value.is_array() {
+            Content::Array
+        } else {
+            Content::Scalar(value.clone())
+        };
+        let action = match place {
+            Place::Key(key) => Action::Set { key, content },
+            Place::After(after) => Action::Insert { after, content },
+        };
+        let id = self.local(object, action, pred);
+
+        if let Some(json_object) = value.as_object() {
+            for (key, member) in json_object.iter() {
+                let place = Place::Key(Key::Name(key.clone()));
+                self.write(id.clone(), place, Vec::new(), member);
+            }
+        } else if let Some(json_array) = value.as_array() {
+            let mut after = None;
+            for item in json_array.iter() {
+                after = Some(self.write(id.clone(), Place::After(after), Vec::new(), item));
+            }
+        }
+        id
+    }
+
+    /// Fail unless `count` more local operations fit below [`MAX_COUNTER`]
+    fn reserve(&self, count: u64) -> Result<(), JSONError> {
+        if MAX_COUNTER - self.clock < count {
+            return Err(JSONError::with_path(
+                "Operation counter is exhausted, no more local edits can be made",
+                "",
+            ));
+        }
+        Ok(())
+    }
+
+    fn local(
+        &mut self,
+        object: OperationId,
+        action: Action,
+        pred: Vec<OperationId>,
+    ) -> OperationId {
+        let id = OperationId {
+            counter: self.clock + 1,
+            replica: self.replica.clone(),
+        };
+        self.integrate(JSONCrdtOperation {
+            id: id.clone(),
+            object,
+            action,
+            pred,
+        });
+        id
+    }
+
+    /// If everything the operation refers to has been applied
+    fn is_ready(&self, operation: &JSONCrdtOperation) -> bool {
+        let reference = match &operation.action {
+            Action::Set {
+                key: Key::Item(item),
+                ..
+            }
+            | Action::Delete {
+                key: Key::Item(item),
+            }
+            | Action::Insert {
+                after: Some(item), ..
+            } => Some(item),
+            _ => None,
+        };
+        self.applied.contains(&operation.object)
+            && operation.pred.iter().all(|id| self.applied.contains(id))
+            && reference.is_none_or(|id| self.applied.contains(id))
+    }
+
+    /// Check a ready operation edits a container the way its type allows
+    fn check(&self, operation: &JSONCrdtOperation) -> Result<(), JSONError> {
+        let error = |message: &str| {
+            Err(JSONError::with_path(
+                &format!("Operation <{}> {}", operation.id, message),
+                "",
+            ))
+        };
+        let Some(container) = self.containers.get(&operation.object) else {
+            return error(&format!(
+                "edits <{}>, which is not a JSON Object or JSON Array",
+                operation.object
+            ));
+        };
+
+        match (container, &operation.action) {
+            (
+                Container::Object(_),
+                Action::Set {
+                    key: Key::Name(_), ..
+                }
+                | Action::Delete { key: Key::Name(_) },
+            ) => Ok(()),
+            (Container::Array(_), Action::Insert { after: None, .. }) => Ok(()),
+            (
+                Container::Array(items),
+                Action::Set {
+                    key: Key::Item(item),
+                    ..
+                }
+                | Action::Delete {
+                    key: Key::Item(item),
+                }
+                | Action::Insert {
+                    after: Some(item), ..
+                },
+            ) => {
+                if items.iter().any(|register| &register.first == item) {
+                    Ok(())
+                } else {
+                    error(&format!(
+                        "refers to <{}>, which is not an item of <{}>",
+                        item, operation.object
+                    ))
+                }
+            }
+            (Container::Object(_), _) => error("edits a JSON Object as a JSON Array"),
+            (Container::Array(_), _) => error("edits a JSON Array as a JSON Object"),
+        }
+    }
+
+    /// Apply an operation which has been checked
+    fn integrate(&mut self, operation: JSONCrdtOperation) {
+        let id = &operation.id;
+        self.clock = self.clock.max(id.counter);
+
+        let container = self.containers.get_mut(&operation.object).unwrap();
+        let created = match (&operation.action, container) {
+            (Action::Set { key, content }, container) => {
+                let register = match (key, container) {
+                    (Key::Name(name), Container::Object(keys)) => {
+                        let register = keys.entry(name.clone()).or_insert_with(|| Register {
+                            first: id.clone(),
+                            values: Vec::new(),
+                        });
+                        register.first = register.first.clone().min(id.clone());
+                        register
+                    }
+                    (Key::Item(item), Container::Array(items)) => find_item(items, item),
+                    _ => unreachable!("the operation was checked"),
+                };
+                register.update(&operation.pred, Some((id.clone(), content.clone())));
+                Some(content)
+            }
+            (Action::Delete { key }, container) => {
+                let register = match (key, container) {
+                    (Key::Name(name), Container::Object(keys)) => keys.get_mut(name),
+                    (Key::Item(item), Container::Array(items)) => Some(find_item(items, item)),
+                    _ => unreachable!("the operation was checked"),
+                };
+                if let Some(register) = register {
+                    register.update(&operation.pred, None);
+                }
+                None
+            }
+            (Action::Insert { after, content }, Container::Array(items)) => {
+                // Concurrent inserts after the same item are ordered by descending id,
+                // skipping past each one with a greater id and the items inserted after it
+                let mut index = match after {
+                    Some(after) => items.iter().position(|item| &item.first == after).unwrap() + 1,
+                    None => 0,
+                };
+                while index < items.len() && &items[index].first > id {
+                    index += 1;
+                }
+                items.insert(
+                    index,
+                    Register {
+                        first: id.clone(),
+                        values: vec![(id.clone(), content.clone())],
+                    },
+                );
+                Some(content)
+            }
+            (Action::Insert { .. }, Container::Object(_)) => {
+                unreachable!("the operation was checked")
+            }
+        };
+
+        match created {
+            Some(Content::Object) => {
+                self.containers
+                    .insert(id.clone(), Container::Object(HashMap::new()));
+            }
+            Some(Content::Array) => {
+                self.containers
+                    .insert(id.clone(), Container::Array(Vec::new()));
+            }
+            _ => {}
+        }
+        self.applied.insert(id.clone());
+        self.operations.push(operation);
+    }
+
+    fn materialize(&self, id: &OperationId, content: &Content) -> JSONValue {
+        match (content, self.containers.get(id)) {
+            (Content::Object, Some(Container::Object(keys))) => {
+                let mut keys: Vec<(&String, &Register)> = keys.iter().collect();
+                keys.sort_by(|(_, a), (_, b)| a.first.cmp(&b.first));
+                let mut json_object = JSONObject::new();
+                for (key, register) in keys {
+                    if let Some((id, content)) = register.winner() {
+                        json_object.set(key.clone(), self.materialize(id, content));
+                    }
+                }
+                JSONValue::from(json_object)
+            }
+            (Content::Array, Some(Container::Array(items))) => JSONValue::from(
+                items
+                    .iter()
+                    .filter_map(Register::winner)
+                    .map(|(id, content)| self.materialize(id, content))
+                    .collect::<Vec<_>>(),
+            ),
+            (Content::Scalar(value), _) => value.clone(),
+            _ => unreachable!("every JSON Object and JSON Array has a container"),
+        }
+    }
+}
+
+impl Register {
+    fn is_set(&self) -> bool {
+        !self.values.is_empty()
+    }
+
+    fn ids(&self) -> Vec<OperationId> {
+        self.values.iter().map(|(id, _)| id.clone()).collect()
+    }
+
+    fn winner(&self) -> Option<&(OperationId, Content)> {
+        self.values.iter().max_by(|(a, _), (b, _)| a.cmp(b))
+    }
+
+    /// Drop the values `pred` replaces, then add the new value
+    fn update(&mut self, pred: &[OperationId], value: Option<(OperationId, Content)>) {
+        self.values.retain(|(id, _)| !pred.contains(id));
+        self.values.extend(value);
+    }
+}
+
+fn find_item<'a>(items: &'a mut [Register], item: &OperationId) -> &'a mut Register {
+    items
+        .iter_mut()
+        .find(|register| &register.first == item)
+        .unwrap()
+}
+
+/// Largest counter an operation may have. Local edits which would need a greater one fail,
+/// so a replica never makes an operation the others cannot parse
+const MAX_COUNTER: u64 = u64::MAX - 1;
+
+/// How many operations writing `value` makes, one for it and one for every member or item inside it
+fn count_operations(value: &JSONValue) -> u64 {
+    let children = if let Some(json_object) = value.as_object() {
+        json_object
+            .iter()
+            .map(|(_, member)| count_operations(member))
+            .sum()
+    } else if let Some(json_array) = value.as_array() {
+        json_array.iter().map(count_operations).sum()
+    } else {
+        0
+    };
+    1 + children
+}
+
+impl OperationId {
+    fn root() -> Self {
+        OperationId {
+            counter: 0,
+            replica: String::new(),
+        }
+    }
+}
+
+impl Display for OperationId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.counter == 0 {
+            write!(f, "_root")
+        } else {
+            write!(f, "{}@{}", self.counter, self.replica)
+        }
+    }
+}
+
+impl FromStr for OperationId {
+    type Err = JSONError;
+
+    fn from_str(id: &str) -> Result<Self, Self::Err> {
+        if id == "_root" {
+            return Ok(OperationId::root());
+        }
+        let parsed = id.split_once('@').and_then(|(counter, replica)| {
+            let counter = counter
+                .parse::<u64>()
+                .ok()
+                .filter(|counter| (1..=MAX_COUNTER).contains(counter))?;
+            Some(OperationId {
+                counter,
+                replica: replica.to_string(),
+            })
+        });
+        parsed.ok_or_else(|| JSONError::with_path(&format!("Invalid operation id <{}>", id), ""))
+    }
+}
+
+impl Display for JSONCrdtOperation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", JSONValue::from(self))
+    }
+}
+
+impl FromStr for JSONCrdtOperation {
+    type Err = JSONError;
+
+    fn from_str(operation: &str) -> Result<Self, Self::Err> {
+        JSONCrdtOperation::try_from(&operation.parse::<JSONValue>()?)
+    }
+}
+
+impl From<&JSONCrdtOperation> for JSONValue {
+    fn from(operation: &JSONCrdtOperation) -> Self {
+        let id = |id: &OperationId| JSONValue::from(id.to_string());
+        let mut json_object = JSONObject::new();
+        json_object.set("id".to_string(), id(&operation.id));
+        json_object.set("object".to_string(), id(&operation.object));
+
+        let (action, content) = match &operation.action {
+            Action::Set { content, .. } => ("set", Some(content)),
+            Action::Insert { content, .. } => ("insert", Some(content)),
+            Action::Delete { .. } => ("delete", None),
+        };
+        json_object.set("action".to_string(), JSONValue::from(action));
+        match &operation.action {
+            Action::Set { key, .. } | Action::Delete { key } => match key {
+                Key::Name(name) => {
+                    json_object.set("key".to_string(), JSONValue::from(name.as_str()))
+                }
+                Key::Item(item) => json_object.set("item".to_string(), id(item)),
+            },
+            Action::Insert { after, .. } => {
+                let after = match after {
+                    Some(after) => id(after),
+                    None => JSONValue::from("_head"),
+                };
+                json_object.set("after".to_string(), after);
+            }
+        }
+        match content {
+            Some(Content::Scalar(value)) => json_object.set("value".to_string(), value.clone()),
+            Some(Content::Object) => json_object.set("make".to_string(), JSONValue::from("object")),
+            Some(Content::Array) => json_object.set("make".to_string(), JSONValue::from("array")),
+            None => {}
+        }
+
+        let pred: Vec<JSONValue> = operation.pred.iter().map(id).collect();
+        json_object.set("pred".to_string(), JSONValue::from(pred));
+        JSONValue::from(json_object)
+    }
+}
+
+impl From<JSONCrdtOperation> for JSONValue {
+    fn from(operation: JSONCrdtOperation) -> Self {
+        JSONValue::from(&operation)
+    }
+}
+
+impl TryFrom<&JSONValue> for JSONCrdtOperation {
+    type Error = JSONError;
+
+    fn try_from(operation: &JSONValue) -> Result<Self, Self::Error> {
+        let Some(json_object) = operation.as_object() else {
+            return Err(JSONError::with_path(
+                "A JSON CRDT operation must be a JSON Object",
+                "",
+            ));
+        };
+
+        let member = |name: &str| {
+            json_object.get(name).ok_or_else(|| {
+                JSONError::with_path(&format!("Operation is missing <{}>", name), "")
+            })
+        };
+        let string =
+            |name: &str| <&str>::try_from(member(name)?).map_err(|error| error.prefix_path(name));
+        let id = |name: &str| {
+            string(name)?
+                .parse::<OperationId>()
+                .map_err(|error| error.prefix_path(name))
+        };
+        let key = || match json_object.get("item") {
+            Some(_) => Ok(Key::Item(id("item")?)),
+            None => Ok(Key::Name(string("key")?.to_string())),
+        };
+        let content = || match json_object.get("make") {
+            Some(_) => match string("make")? {
+                "object" => Ok(Content::Object),
+                "array" => Ok(Content::Array),
+                make => Err(JSONError::with_path(
+                    &format!("Unknown container <{}>", make),
+                    "/make",
+                )),
+            },
+            None => {
+                let value = member("value")?;
+                if value.is_object() || value.is_array() {
+                    return Err(JSONError::with_path(
+                        "A JSON Object or JSON Array is made with <make>",
+                        "/value",
+                    ));
+                }
+                Ok(Content::Scalar(value.clone()))
+            }
+        };
+
+        let action = match string("action")? {
+            "set" => Action::Set {
+                key: key()?,
+                content: content()?,
+            },
+            "insert" => Action::Insert {
+                after: match string("after")? {
+                    "_head" => None,
+                    _ => Some(id("after")?),
+                },
+                content: content()?,
+            },
+            "delete" => Action::Delete { key: key()? },
+            action => {
+                return Err(JSONError::with_path(
+                    &format!("Unknown action <{}>", action),
+                    "/action",
+                ))
+            }
+        };
+
+        let Some(pred) = member("pred")?.as_array() else {
+            return Err(JSONError::with_path(
+                "Operation <pred> must be a JSON Array",
+                "/pred",
+            ));
+        };
+        let pred = pred
+            .iter()
+            .enumerate()
+            .map(|(index, id)| {
+                <&str>::try_from(id)
+                    .and_then(str::parse::<OperationId>)
+                    .map_err(|error| error.prefix_path(&index.to_string()).prefix_path("pred"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let operation = JSONCrdtOperation {
+            id: id("id")?,
+            object: id("object")?,
+            action,
+            pred,
+        };
+        if operation.id.counter == 0 {
+            return Err(JSONError::with_path("Invalid operation id <_root>", "/id"));
+        }
+        Ok(operation)
+    }
+}
@@ -0,0 +1,428 @@
+use std::{collections::BTreeMap, fmt::Display};
+
+use crate::{
+    lexer::Lexer,
+    parser::{Parser, Positions},
+    JSONArray, JSONError, JSONObject, JSONPointer, JSONValue,
+};
+
+/// How [`JSONDeepMerge`] combines the value at a path with the value a layer has there.
+/// A strategy for JSON Arrays replaces the value if either side is not a JSON Array
+#[derive(Debug, Clone, PartialEq)]
+pub enum JSONMergeStrategy {
+    /// The layer's value replaces the value
+    Replace,
+    /// JSON Objects are merged key by key, recursively. Anything else is replaced
+    Merge,
+    /// The layer's JSON Array items are added to the end of the JSON Array
+    Append,
+    /// The layer's JSON Array items are merged into the item with the same value for the key,
+    /// or added to the end if there is none. Items which are not JSON Objects with the key
+    /// are added unless an equal item is already there
+    UnionByKey(String),
+    /// The layer's JSON Array items are merged into the items at the same index,
+    /// and added to the end past the last one
+    MergeByIndex,
+}
+
+/// Merges layers of JSON Values into one, such as defaults overridden by user settings
+///
+/// JSON Objects are merged recursively and everything else is replaced,
+/// unless a different [`JSONMergeStrategy`] is set for the path
+///
+/// # Example
+///
+/// ```
+/// use parson::{json, JSONDeepMerge, JSONMergeStrategy};
+///
+/// let deep_merge = JSONDeepMerge::new()
+///     .strategy("/plugins".parse().unwrap(), JSONMergeStrategy::Append)
+///     .strategy("/servers".parse().unwrap(), JSONMergeStrategy::UnionByKey("name".to_string()))
+///     .strategy("/db".parse().unwrap(), JSONMergeStrategy::Replace);
+///
+/// let mut config = json!({
+///     "plugins": ["auth"],
+///     "servers": [{ "name": "a", "port": 80 }, { "name": "b", "port": 81 }],
+///     "db": { "host": "localhost", "pool": 5 },
+///     "log": { "level": "info", "format": "text" }
+/// });
+/// deep_merge.merge(&mut config, &json!({
+///     "plugins": ["metrics"],
+///     "servers": [{ "name": "b", "port": 8081 }, { "name": "c", "port": 82 }],
+///     "db": { "host": "db.internal" },
+///     "log": { "level": "debug" }
+/// }));
+/// assert_eq!(config, json!({
+///     "plugins": ["auth", "metrics"],
+///     "servers": [{ "name": "a", "port": 80 }, { "name": "b", "port": 8081 }, { "name": "c", "port": 82 }],
+///     "db": { "host": "db.internal" },
+///     "log": { "level": "debug", "format": "text" }
+/// }));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct JSONDeepMerge {
+    strategies: Vec<(JSONPointer, JSONMergeStrategy)>,
+}
+
+/// Merges named layers in order, recording which layer each value came from
+///
+/// # Example
+///
+/// ```
+/// use parson::{json, JSONDeepMerge, JSONLayers};
+///
+/// let mut layers = JSONLayers::new(JSONDeepMerge::new());
+/// layers
+///     .add_str("defaults.json", r#"{ "db": { "host": "localhost", "port": 5432 } }"#)
+///     .unwrap();
+/// layers.add("environment", &json!({ "db": { "port": 6432 } }));
+/// layers
+///     .add_str("user.json", "{\n  \"db\": {\n    \"host\": \"db.internal\"\n  }\n}")
+///     .unwrap();
+///
+/// assert_eq!(layers.value(), &json!({ "db": { "host": "db.internal", "port": 6432 } }));
+/// assert_eq!(layers.explain("/db/host").unwrap().to_string(), "user.json at line 3");
+/// assert_eq!(layers.explain("/db/port").unwrap().to_string(), "environment");
+/// assert_eq!(layers.explain("/db").unwrap().layer(), "defaults.json");
+/// assert_eq!(
+///     layers.explain("/db/user").unwrap_err().get_message(),
+///     "No layer set a value (at /db/user)"
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct JSONLayers {
+    deep_merge: JSONDeepMerge,
+    value: JSONValue,
+    origins: BTreeMap<JSONPointer, JSONOrigin>,
+}
+
+/// Where a value of merged [`JSONLayers`] came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JSONOrigin {
+    layer: String,
+    position: Option<(usize, usize)>,
+}
+
+/// The layer being merged, and where to record the origin of the values it sets
+struct Provenance<'a> {
+    layer: &'a str,
+    positions: Option<&'a Positions>,
+    origins: &'a mut BTreeMap<JSONPointer, JSONOrigin>,
+}
+
+impl Display for JSONOrigin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.position {
+            Some((line, _)) => write!(f, "{} at line {}", self.layer, line),
+            None => write!(f, "{}", self.layer),
+        }
+    }
+}
+
+impl JSONOrigin {
+    /// Get the name of the layer the value came from
+    pub fn layer(&self) -> &str {
+        &self.layer
+    }
+
+    /// Get the line the value is on in the layer, if the layer was parsed from a string
+    pub fn line(&self) -> Option<usize> {
+        self.position.map(|(line, _)| line)
+    }
+
+    /// Get the column the value starts at in the layer, if the layer was parsed from a string.
+    /// For a value in a JSON Object, this is where its key starts
+    pub fn column(&self) -> Option<usize> {
+        self.position.map(|(_, column)| column)
+    }
+}
+
+impl Provenance<'_> {
+    /// Record that the value at `path` is now `value`, found at `source` in the layer
+    fn record(&mut self, path: &JSONPointer, source: &JSONPointer, value: &JSONValue) {
+        let replaced = self
+            .origins
+            .range(path.clone()..)
+            .take_while(|(pointer, _)| pointer.starts_with(path))
+            .map(|(pointer, _)| pointer.clone())
+            .collect::<Vec<_>>();
+        for pointer in replaced {
+            self.origins.remove(&pointer);
+        }
+        self.record_value(path, source, value);
+    }
+
+    fn record_value(&mut self, path: &JSONPointer, source: &JSONPointer, value: &JSONValue) {
+        let origin = JSONOrigin {
+            layer: self.layer.to_string(),
+            position: self
+                .positions
+                .and_then(|positions| positions.get(source).copied()),
+        };
+        self.origins.insert(path.clone(), origin);
+
+        if let Some(json_object) = value.as_object() {
+            for (key, value) in json_object.iter() {
+                self.record_value(&path.join(key.as_str()), &source.join(key.as_str()), value);
+            }
+        } else if let Some(json_array) = value.as_array() {
+            for (index, value) in json_array.iter().enumerate() {
+                let index = index.to_string();
+                self.record_value(&path.join(index.as_str()), &source.join(index), value);
+            }
+        }
+    }
+}
+
+impl JSONDeepMerge {
+    /// Create a new JSON Deep Merge which merges JSON Objects and replaces everything else
+    pub fn new() -> Self {
+        JSONDeepMerge::default()
+    }
+
+    /// Set how the value at `pointer` is merged. A strategy set later for the same path wins
+    pub fn strategy(mut self, pointer: JSONPointer, strategy: JSONMergeStrategy) -> Self {
+        self.strategies.push((pointer, strategy));
+        self
+    }
+
+    /// Merge `layer` into `value`
+    pub fn merge(&self, value: &mut JSONValue, layer: &JSONValue) {
+        let root = JSONPointer::root();
+        self.merge_value(value, layer, &root, &root, &mut None);
+    }
+
+    /// Merge `layer` into `json_object`, key by key
+    pub fn merge_object(&self, json_object: &mut JSONObject, layer: &JSONObject) {
+        let root = JSONPointer::root();
+        self.merge_objects(json_object, layer, &root, &root, &mut None);
+    }
+
+    /// `path` is where the value is in the merged value, `source` is where it is in the layer
+    fn merge_value(
+        &self,
+        value: &mut JSONValue,
+        layer: &JSONValue,
+        path: &JSONPointer,
+        source: &JSONPointer,
+        provenance: &mut Option<Provenance>,
+    ) {
+        let strategy = self
+            .strategies
+            .iter()
+            .rev()
+            .find(|(pointer, _)| pointer == path)
+            .map(|(_, strategy)| strategy);
+
+        if let (Some(json_object), Some(layer_object)) = (value.as_object_mut(), layer.as_object())
+        {
+            if matches!(strategy, None | Some(JSONMergeStrategy::Merge)) {
+                self.merge_objects(json_object, layer_object, path, source, provenance);
+                return;
+            }
+        }
+        if let (Some(json_array), Some(layer_array)) = (value.as_array_mut(), layer.as_array()) {
+            match strategy {
+                Some(JSONMergeStrategy::Append) => {
+                    for (index, item) in layer_array.iter().enumerate() {
+                        self.push(
+                            json_array,
+                            item,
+                            path,
+                            &source.join(index.to_string()),
+                            provenance,
+                        );
+                    }
+                    return;
+                }
+                Some(JSONMergeStrategy::UnionByKey(key)) => {
+                    self.union_by_key(json_array, layer_array, key, path, source, provenance);
+                    return;
+                }
+                Some(JSONMergeStrategy::MergeByIndex) => {
+                    for (index, item) in layer_array.iter().enumerate() {
+                        let item_source = source.join(index.to_string());
+                        match json_array.get_mut(index) {
+                            Some(existing) => self.merge_value(
+                                existing,
+                                item,
+                                &path.join(index.to_string()),
+                                &item_source,
+                                provenance,
+                            ),
+                            None => self.push(json_array, item, path, &item_source, provenance),
+                        }
+                    }
+                    return;
+                }
+                _ => {}
+            }
+        }
+
+        *value = layer.clone();
+        if let Some(provenance) = provenance {
+            provenance.record(path, source, layer);
+        }
+    }
+
+    fn merge_objects(
+        &self,
+        json_object: &mut JSONObject,
+        layer: &JSONObject,
+        path: &JSONPointer,
+        source: &JSONPointer,
+        provenance: &mut Option<Provenance>,
+    ) {
+        for (key, layer_value) in layer.iter() {
+            let key_path = path.join(key.as_str());
+            let key_source = source.join(key.as_str());
+            match json_object.get_mut(key) {
+                Some(value) => {
+                    self.merge_value(value, layer_value, &key_path, &key_source, provenance)
+                }
+                None => {
+                    json_object.set(key.clone(), layer_value.clone());
+                    if let Some(provenance) = provenance {
+                        provenance.record(&key_path, &key_source, layer_value);
+                    }
+                }
+            }
+        }
+    }
+
+    fn union_by_key(
+        &self,
+        json_array: &mut JSONArray,
+        layer: &JSONArray,
+        key: &str,
+        path: &JSONPointer,
+        source: &JSONPointer,
+        provenance: &mut Option<Provenance>,
+    ) {
+        for (index, item) in layer.iter().enumerate() {
+            let item_source = source.join(index.to_string());
+            let item_key = item
+                .as_object()
+                .and_then(|json_object| json_object.get(key));
+            let existing = match item_key {
+                Some(item_key) => json_array.iter().position(|existing| {
+                    existing
+                        .as_object()
+                        .and_then(|json_object| json_object.get(key))
+                        == Some(item_key)
+                }),
+                None => json_array.iter().position(|existing| existing == item),
+            };
+            match existing {
+                Some(index) => {
+                    if let (Some(existing), Some(_)) = (json_array.get_mut(index), item_key) {
+                        let item_path = path.join(index.to_string());
+                        self.merge_value(existing, item, &item_path, &item_source, provenance);
+                    }
+                }
+                None => self.push(json_array, item, path, &item_source, provenance),
+            }
+        }
+    }
+
+    fn push(
+        &self,
+        json_array: &mut JSONArray,
+        item: &JSONValue,
+        path: &JSONPointer,
+        source: &JSONPointer,
+        provenance: &mut Option<Provenance>,
+    ) {
+        if let Some(provenance) = provenance {
+            provenance.record(&path.join(json_array.len().to_string()), source, item);
+        }
+        json_array.push(item.clone());
+    }
+}
+
+impl Default for JSONLayers {
+    fn default() -> Self {
+        JSONLayers::new(JSONDeepMerge::new())
+    }
+}
+
+impl JSONLayers {
+    /// Create an empty set of layers, merged with `deep_merge`
+    pub fn new(deep_merge: JSONDeepMerge) -> Self {
+        JSONLayers {
+            deep_merge,
+            value: JSONValue::from(JSONObject::new()),
+            origins: BTreeMap::new(),
+        }
+    }
+
+    /// Merge a layer over the layers added before it
+    pub fn add(&mut self, name: &str, layer: &JSONValue) {
+        self.merge(name, layer, None);
+    }
+
+    /// Parse a layer from a string and merge it over the layers added before it,
+    /// recording the line each value is on
+    pub fn add_str(&mut self, name: &str, json: &str) -> Result<(), JSONError> {
+        let parser = Parser::new(Lexer::new(json.to_string()).get_tokens()?);
+        let layer = parser.parse()?;
+        self.merge(name, &layer, Some(&parser.positions()));
+        Ok(())
+    }
+
+    fn merge(&mut self, name: &str, layer: &JSONValue, positions: Option<&Positions>) {
+        let root = JSONPointer::root();
+        let mut provenance = Some(Provenance {
+            layer: name,
+            positions,
+            origins: &mut self.origins,
+        });
+        self.deep_merge
+            .merge_value(&mut self.value, layer, &root, &root, &mut provenance);
+    }
+
+    /// Get the merged value of every layer
+    pub fn value(&self) -> &JSONValue {
+        &self.value
+    }
+
+    /// Get the merged value of every layer, consuming the layers
+    pub fn into_value(self) -> JSONValue {
+        self.value
+    }
+
+    /// Get which layer set the value at `pointer`.
+    /// A JSON Object merged from several layers is from the layer which first added it
+    pub fn explain(&self, pointer: &str) -> Result<&JSONOrigin, JSONError> {
+        let pointer = JSONPointer::parse(pointer)?;
+        self.origins
+            .get(&pointer)
+            .ok_or_else(|| JSONError::with_path("No layer set a value", &pointer.to_string()))
+    }
+}
+
+impl JSONValue {
+    /// Merge `layer` into the JSON Value, merging JSON Objects recursively and replacing
+    /// everything else. Use [`JSONDeepMerge`] to merge paths differently
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use parson::json;
+    ///
+    /// let mut json_value = json!({ "db": { "host": "localhost", "port": 5432 }, "tags": ["a"] });
+    /// json_value.deep_merge(&json!({ "db": { "host": "db.internal" }, "tags": ["b"] }));
+    /// assert_eq!(json_value, json!({ "db": { "host": "db.internal", "port": 5432 }, "tags": ["b"] }));
+    /// ```
+    pub fn deep_merge(&mut self, layer: &JSONValue) {
+        JSONDeepMerge::new().merge(self, layer);
+    }
+}
+
+impl JSONObject {
+    /// Merge `layer` into the JSON Object, merging JSON Objects recursively and replacing
+    /// everything else. Use [`JSONDeepMerge`] to merge paths differently
+    pub fn deep_merge(&mut self, layer: &JSONObject) {
+        JSONDeepMerge::new().merge_object(self, layer);
+    }
+}
@@ -0,0 +1,614 @@
+use crate::{JSONArray, JSONObject, JSONPatch, JSONPatchOperation, JSONPointer, JSONValue};
+
+/// Compares two JSON Values, giving the changes as a JSON Patch or as a readable report
+///
+/// By default JSON Arrays are compared item by item at the same index.
+/// With `lcs_arrays`, items are matched by the longest common subsequence instead,
+/// so inserting an item near the start of a JSON Array is one `add` rather than a change to every later item.
+/// With `detect_moves`, a value removed in one place and added later is written as one `move`.
+///
+/// # Example
+///
+/// ```
+/// use parson::{json, JSONDiff, JSONValue};
+///
+/// let before = json!({ "name": "api", "replicas": 2, "ports": [80, 443], "debug": true });
+/// let after = json!({ "name": "api", "replicas": 3, "ports": [8080, 80, 443], "region": "eu" });
+///
+/// let patch = JSONDiff::new().lcs_arrays(true).diff(&before, &after);
+/// assert_eq!(patch.len(), 4);
+/// #[cfg(feature = "preserve_order")]
+/// assert_eq!(
+///     JSONValue::from(&patch),
+///     json!([
+///         { "op": "replace", "path": "/replicas", "value": 3 },
+///         { "op": "add", "path": "/ports/0", "value": 8080 },
+///         { "op": "remove", "path": "/debug" },
+///         { "op": "add", "path": "/region", "value": "eu" }
+///     ])
+/// );
+///
+/// let mut patched = before.clone();
+/// patch.apply(&mut patched).unwrap();
+/// assert_eq!(patched, after);
+///
+/// #[cfg(feature = "preserve_order")]
+/// assert_eq!(
+///     JSONDiff::new().lcs_arrays(true).report(&before, &after),
+///     "- /replicas: 2\n+ /replicas: 3\n+ /ports/0: 8080\n- /debug: true\n+ /region: \"eu\"\n"
+/// );
+///
+/// let before = json!({ "steps": ["build", "test", "deploy"] });
+/// let after = json!({ "steps": ["test", "deploy", "build"] });
+/// assert_eq!(
+///     JSONValue::from(JSONDiff::new().lcs_arrays(true).detect_moves(true).diff(&before, &after)),
+///     json!([{ "op": "move", "from": "/steps/0", "path": "/steps/2" }])
+/// );
+/// ```
+///
+/// Paths can be left out of the comparison, and a report can be coloured for a terminal
+///
+/// ```
+/// use parson::{json, JSONDiff};
+///
+/// let before = json!({ "spec": { "image": "api:1" }, "metadata": { "generation": 4 } });
+/// let after = json!({ "spec": { "image": "api:2" }, "metadata": { "generation": 5 } });
+///
+/// let diff = JSONDiff::new()
+///     .ignore("/metadata/generation".parse().unwrap())
+///     .colored(true);
+/// assert_eq!(diff.diff(&before, &after).len(), 1);
+/// assert_eq!(
+///     diff.report(&before, &after),
+///     "\x1b[31m- /spec/image: \"api:1\"\x1b[0m\n\x1b[32m+ /spec/image: \"api:2\"\x1b[0m\n"
+/// );
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct JSONDiff {
+    detect_moves: bool,
+    lcs_arrays: bool,
+    colored: bool,
+    ignore: Vec<JSONPointer>,
+}
+
+/// One change found by a diff, before it becomes a JSON Patch operation.
+/// `in_array` records if the value is an item of a JSON Array, so later paths can be shifted
+#[derive(Debug, Clone)]
+enum Edit {
+    Add {
+        path: JSONPointer,
+        in_array: bool,
+        value: JSONValue,
+    },
+    Remove {
+        path: JSONPointer,
+        in_array: bool,
+        value: JSONValue,
+    },
+    Replace {
+        path: JSONPointer,
+        old: JSONValue,
+        new: JSONValue,
+    },
+    Move {
+        from: JSONPointer,
+        from_in_array: bool,
+        path: JSONPointer,
+        path_in_array: bool,
+        value: JSONValue,
+    },
+}
+
+/// Items of `a` and `b` between two matches of their longest common subsequence.
+/// `a_start` is the index of the first removed item in `a`, `position` is where it is when edited
+struct RunOfItems<'a> {
+    removed: &'a [JSONValue],
+    added: &'a [JSONValue],
+    a_start: usize,
+    position: usize,
+}
+
+impl JSONDiff {
+    /// Create a new JSON Diff which compares JSON Arrays by index and does not detect moves
+    pub fn new() -> Self {
+        JSONDiff::default()
+    }
+
+    /// Set whether a value removed in one place and added later is written as a `move`
+    pub fn detect_moves(mut self, detect_moves: bool) -> Self {
+        self.detect_moves = detect_moves;
+        self
+    }
+
+    /// Set whether JSON Array items are matched by the longest common subsequence.
+    /// JSON Arrays that differ in too many items to compare every pair, about 4 million pairs,
+    /// are compared by index between their common start and end
+    pub fn lcs_arrays(mut self, lcs_arrays: bool) -> Self {
+        self.lcs_arrays = lcs_arrays;
+        self
+    }
+
+    /// Set whether the lines of a report are coloured with ANSI escape codes
+    pub fn colored(mut self, colored: bool) -> Self {
+        self.colored = colored;
+        self
+    }
+
+    /// Leave the value at `pointer` and everything inside it out of the comparison.
+    /// The pointer is matched against where the value is in the first JSON Value
+    pub fn ignore(mut self, pointer: JSONPointer) -> Self {
+        self.ignore.push(pointer);
+        self
+    }
+
+    /// Get a JSON Patch which turns `a` into `b`
+    pub fn diff(&self, a: &JSONValue, b: &JSONValue) -> JSONPatch {
+        self.edits(a, b)
+            .into_iter()
+            .map(|edit| match edit {
+                Edit::Add { path, value, .. } => JSONPatchOperation::Add { path, value },
+                Edit::Remove { path, .. } => JSONPatchOperation::Remove { path },
+                Edit::Replace { path, new, .. } => JSONPatchOperation::Replace { path, value: new },
+                Edit::Move { from, path, .. } => JSONPatchOperation::Move { from, path },
+            })
+            .collect()
+    }
+
+    /// Get a report of the changes from `a` to `b`, one line each,
+    /// with `-` before removed values and `+` before added values.
+    /// A changed value is shown as its old value removed and its new value added
+    pub fn report(&self, a: &JSONValue, b: &JSONValue) -> String {
+        let mut report = String::new();
+        for edit in self.edits(a, b) {
+            match edit {
+                Edit::Add { path, value, .. } => self.report_line(&mut report, '+', &path, &value),
+                Edit::Remove { path, value, .. } => {
+                    self.report_line(&mut report, '-', &path, &value)
+                }
+                Edit::Replace { path, old, new } => {
+                    self.report_line(&mut report, '-', &path, &old);
+                    self.report_line(&mut report, '+', &path, &new);
+                }
+                Edit::Move {
+                    from, path, value, ..
+                } => {
+                    self.report_line(&mut report, '-', &from, &value);
+                    self.report_line(&mut report, '+', &path, &value);
+                }
+            }
+        }
+        report
+    }
+
+    fn report_line(&self, report: &mut String, sign: char, path: &JSONPointer, value: &JSONValue) {
+        let path = if path.is_root() {
+            "(root)".to_string()
+        } else {
+            path.to_string()
+        };
+        let line = format!("{} {}: {}", sign, path, value);
+        if self.colored {
+            let color = if sign == '-' { "31" } else { "32" };
+            report.push_str(&format!("\x1b[{}m{}\x1b[0m\n", color, line));
+        } else {
+            report.push_str(&line);
+            report.push('\n');
+        }
+    }
+
+    fn edits(&self, a: &JSONValue, b: &JSONValue) -> Vec<Edit> {
+        let mut edits = Vec::new();
+        self.diff_values(a, b, &JSONPointer::root(), &JSONPointer::root(), &mut edits);
+        if self.detect_moves {
+            detect_moves(&mut edits);
+        }
+        edits
+    }
+
+    /// `path` is where the value is when its edits are applied, `source` is where it is in `a`
+    fn diff_values(
+        &self,
+        a: &JSONValue,
+        b: &JSONValue,
+        path: &JSONPointer,
+        source: &JSONPointer,
+        edits: &mut Vec<Edit>,
+    ) {
+        if self.equal(a, b, source) {
+            return;
+        }
+        if let (Some(a_object), Some(b_object)) = (a.as_object(), b.as_object()) {
+            self.diff_objects(a_object, b_object, path, source, edits);
+        } else if let (Some(a_array), Some(b_array)) = (a.as_array(), b.as_array()) {
+            if self.lcs_arrays {
+                self.diff_arrays_lcs(a_array, b_array, path, source, edits);
+            } else {
+                self.diff_arrays_by_index(a_array, b_array, path, source, edits);
+            }
+        } else {
+            edits.push(Edit::Replace {
+                path: path.clone(),
+                old: a.clone(),
+                new: b.clone(),
+            });
+        }
+    }
+
+    fn diff_objects(
+        &self,
+        a: &JSONObject,
+        b: &JSONObject,
+        path: &JSONPointer,
+        source: &JSONPointer,
+        edits: &mut Vec<Edit>,
+    ) {
+        for (key, a_value) in a.iter() {
+            let key_source = source.join(key.as_str());
+            if self.ignored(&key_source) {
+                continue;
+            }
+            match b.get(key) {
+                Some(b_value) => self.diff_values(
+                    a_value,
+                    b_value,
+                    &path.join(key.as_str()),
+                    &key_source,
+                    edits,
+                ),
+                None => edits.push(Edit::Remove {
+                    path: path.join(key.as_str()),
+                    in_array: false,
+                    value: a_value.clone(),
+                }),
+            }
+        }
+        for (key, b_value) in b.iter() {
+            if !a.contains_key(key) && !self.ignored(&source.join(key.as_str())) {
+                edits.push(Edit::Add {
+                    path: path.join(key.as_str()),
+                    in_array: false,
+                    value: b_value.clone(),
+                });
+            }
+        }
+    }
+
+    fn diff_arrays_by_index(
+        &self,
+        a: &JSONArray,
+        b: &JSONArray,
+        path: &JSONPointer,
+        source: &JSONPointer,
+        edits: &mut Vec<Edit>,
+    ) {
+        let (a, b) = (a.as_slice(), b.as_slice());
+        for (index, (a_item, b_item)) in a.iter().zip(b).enumerate() {
+            self.diff_values(
+                a_item,
+                b_item,
+                &path.join(index.to_string()),
+                &source.join(index.to_string()),
+                edits,
+            );
+        }
+        for index in (b.len()..a.len()).rev() {
+            edits.push(Edit::Remove {
+                path: path.join(index.to_string()),
+                in_array: true,
+                value: a[index].clone(),
+            });
+        }
+        for (index, b_item) in b.iter().enumerate().skip(a.len()) {
+            edits.push(Edit::Add {
+                path: path.join(index.to_string()),
+                in_array: true,
+                value: b_item.clone(),
+            });
+        }
+    }
+
+    fn diff_arrays_lcs(
+        &self,
+        a: &JSONArray,
+        b: &JSONArray,
+        path: &JSONPointer,
+        source: &JSONPointer,
+        edits: &mut Vec<Edit>,
+    ) {
+        let (a, b) = (a.as_slice(), b.as_slice());
+        let matches = longest_common_subsequence(a.len(), b.len(), |i, j| {
+            self.equal(&a[i], &b[j], &source.join(i.to_string()))
+        });
+
+        // Turn the runs of unmatched items between matches into edits
+        let (mut position, mut a_start, mut b_start) = (0, 0, 0);
+        for (a_index, b_index) in matches.into_iter().chain([(a.len(), b.len())]) {
+            let run = RunOfItems {
+                removed: &a[a_start..a_index],
+                added: &b[b_start..b_index],
+                a_start,
+                position,
+            };
+            self.diff_run(run, path, source, edits);
+            position += b_index - b_start + 1;
+            a_start = a_index + 1;
+            b_start = b_index + 1;
+        }
+    }
+
+    /// Turn a run of removed items and added items into edits,
+    /// pairing them up as changes before removing or adding the rest
+    fn diff_run(
+        &self,
+        run: RunOfItems,
+        path: &JSONPointer,
+        source: &JSONPointer,
+        edits: &mut Vec<Edit>,
+    ) {
+        let paired = run.removed.len().min(run.added.len());
+        for offset in 0..paired {
+            self.diff_values(
+                &run.removed[offset],
+                &run.added[offset],
+                &path.join((run.position + offset).to_string()),
+                &source.join((run.a_start + offset).to_string()),
+                edits,
+            );
+        }
+        for offset in (paired..run.removed.len()).rev() {
+            edits.push(Edit::Remove {
+                path: path.join((run.position + offset).to_string()),
+                in_array: true,
+                value: run.removed[offset].clone(),
+            });
+        }
+        for (offset, value) in run.added.iter().enumerate().skip(paired) {
+            edits.push(Edit::Add {
+                path: path.join((run.position + offset).to_string()),
+                in_array: true,
+                value: value.clone(),
+            });
+        }
+    }
+
+    fn ignored(&self, source: &JSONPointer) -> bool {
+        self.ignore
+            .iter()
+            .any(|pointer| source.starts_with(pointer))
+    }
+
+    /// Compare two values, skipping any ignored paths inside them
+    fn equal(&self, a: &JSONValue, b: &JSONValue, source: &JSONPointer) -> bool {
+        if self.ignored(source) {
+            return true;
+        }
+        if !self
+            .ignore
+            .iter()
+            .any(|pointer| pointer.starts_with(source))
+        {
+            return a == b;
+        }
+        if let (Some(a_object), Some(b_object)) = (a.as_object(), b.as_object()) {
+            a_object.iter().all(|(key, a_value)| {
+                let key_source = source.join(key.as_str());
+                self.ignored(&key_source)
+                    || b_object
+                        .get(key)
+                        .is_some_and(|b_value| self.equal(a_value, b_value, &key_source))
+            }) && b_object
+                .keys()
+                .all(|key| a_object.contains_key(key) || self.ignored(&source.join(key.as_str())))
+        } else if let (Some(a_array), Some(b_array)) = (a.as_array(), b.as_array()) {
+            a_array.len() == b_array.len()
+                && a_array.iter().zip(b_array.iter()).enumerate().all(
+                    |(index, (a_item, b_item))| {
+                        self.equal(a_item, b_item, &source.join(index.to_string()))
+                    },
+                )
+        } else {
+            a == b
+        }
+    }
+}
+
+/// Get a JSON Patch which turns `a` into `b`, comparing JSON Arrays by index
+///
+/// # Example
+///
+/// ```
+/// use parson::{diff, json};
+///
+/// let before = json!({ "tags": ["a", "b", "c"], "owner": { "name": "ops" } });
+/// let after = json!({ "tags": ["a", "x"], "owner": { "name": "platform" } });
+///
+/// let patch = diff(&before, &after);
+/// assert_eq!(patch.len(), 3);
+/// #[cfg(feature = "preserve_order")]
+/// assert_eq!(
+///     patch.to_string(),
+///     r#"[ { "op": "replace", "path": "/tags/1", "value": "x" }, { "op": "remove", "path": "/tags/2" }, { "op": "replace", "path": "/owner/name", "value": "platform" } ]"#
+/// );
+///
+/// let mut patched = before.clone();
+/// patch.apply(&mut patched).unwrap();
+/// assert_eq!(patched, after);
+/// assert!(diff(&after, &after).is_empty());
+/// ```
+pub fn diff(a: &JSONValue, b: &JSONValue) -> JSONPatch {
+    JSONDiff::new().diff(a, b)
+}
+
+/// The most pairs of items [`longest_common_subsequence`] compares, about 40 MB of tables
+const MAX_LCS_PAIRS: usize = 1 << 22;
+
+/// Find the longest common subsequence of two lists with `a_len` and `b_len` items,
+/// as the indexes of each pair of matched items in order.
+///
+/// Only the common prefix and suffix are matched if the items between them
+/// would need more than [`MAX_LCS_PAIRS`] comparisons
+pub(crate) fn longest_common_subsequence<F: Fn(usize, usize) -> bool>(
+    a_len: usize,
+    b_len: usize,
+    equal: F,
+) -> Vec<(usize, usize)> {
+    let mut prefix = 0;
+    while prefix < a_len && prefix < b_len && equal(prefix, prefix) {
+        prefix += 1;
+    }
+    let mut suffix = 0;
+    while suffix < a_len - prefix
+        && suffix < b_len - prefix
+        && equal(a_len - 1 - suffix, b_len - 1 - suffix)
+    {
+        suffix += 1;
+    }
+    let (n, m) = (a_len - prefix - suffix, b_len - prefix - suffix);
+    // Leave the items between the prefix and suffix unmatched if comparing them all costs too much
+    let (n, m) = if n.checked_mul(m).is_some_and(|pairs| pairs <= MAX_LCS_PAIRS) {
+        (n, m)
+    } else {
+        (0, 0)
+    };
+
+    // matches[i * m + j] is if the items are equal, lengths[i * (m + 1) + j] is the length of
+    // the longest common subsequence of the items from i and j onwards
+    let matches = (0..n * m)
+        .map(|k| equal(prefix + k / m, prefix + k % m))
+        .collect::<Vec<_>>();
+    let mut lengths = vec![0; (n + 1) * (m + 1)];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lengths[i * (m + 1) + j] = if matches[i * m + j] {
+                lengths[(i + 1) * (m + 1) + j + 1] + 1
+            } else {
+                lengths[(i + 1) * (m + 1) + j].max(lengths[i * (m + 1) + j + 1])
+            };
+        }
+    }
+
+    let mut pairs = (0..prefix).map(|k| (k, k)).collect::<Vec<_>>();
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if matches[i * m + j] {
+            pairs.push((prefix + i, prefix + j));
+            i += 1;
+            j += 1;
+        } else if lengths[i * (m + 1) + j + 1] >= lengths[(i + 1) * (m + 1) + j] {
+            j += 1;
+        } else {
+            i += 1;
+        }
+    }
+    pairs.extend((0..suffix).map(|k| (a_len - suffix + k, b_len - suffix + k)));
+    pairs
+}
+
+/// Replace each removal followed later by an addition of an equal value with one move.
+///
+/// The removed value stays in place until the move, so the paths of the edits in between
+/// are shifted past it, and its own path is shifted by those edits
+fn detect_moves(edits: &mut Vec<Edit>) {
+    let mut index = 0;
+    while index < edits.len() {
+        let Edit::Add { value, .. } = &edits[index] else {
+            index += 1;
+            continue;
+        };
+        let Some(removal) = edits[..index].iter().position(
+            |edit| matches!(edit, Edit::Remove { value: removed, .. } if removed == value),
+        ) else {
+            index += 1;
+            continue;
+        };
+
+        let Edit::Remove {
+            path: mut from,
+            in_array: from_in_array,
+            ..
+        } = edits.remove(removal)
+        else {
+            unreachable!("the edit was matched as a removal");
+        };
+        index -= 1;
+        for edit in &mut edits[removal..index] {
+            shift_past(edit, &mut from, from_in_array);
+        }
+        if let Edit::Add {
+            path,
+            in_array,
+            value,
+        } = edits.remove(index)
+        {
+            edits.insert(
+                index,
+                Edit::Move {
+                    from,
+                    from_in_array,
+                    path,
+                    path_in_array: in_array,
+                    value,
+                },
+            );
+        }
+        index += 1;
+    }
+}
+
+/// Shift the paths of `edit` past the value at `kept`, which has not been removed yet,
+/// then shift `kept` by the items `edit` adds or removes
+fn shift_past(edit: &mut Edit, kept: &mut JSONPointer, kept_in_array: bool) {
+    match edit {
+        Edit::Add { path, in_array, .. } => {
+            shift_index(path, kept, kept_in_array, true);
+            shift_index(kept, path, *in_array, true);
+        }
+        Edit::Remove { path, in_array, .. } => {
+            shift_index(path, kept, kept_in_array, true);
+            shift_index(kept, path, *in_array, false);
+        }
+        Edit::Replace { path, .. } => shift_index(path, kept, kept_in_array, true),
+        Edit::Move {
+            from,
+            from_in_array,
+            path,
+            path_in_array,
+            ..
+        } => {
+            shift_index(from, kept, kept_in_array, true);
+            shift_index(kept, from, *from_in_array, false);
+            shift_index(path, kept, kept_in_array, true);
+            shift_index(kept, path, *path_in_array, true);
+        }
+    }
+}
+
+/// Shift `pointer` for an item inserted or removed at `at` in a JSON Array,
+/// if `pointer` refers to a later item of that JSON Array or a value inside it
+fn shift_index(pointer: &mut JSONPointer, at: &JSONPointer, at_in_array: bool, inserted: bool) {
+    let (Some(at_index), true) = (
+        at.last().and_then(|token| token.parse::<usize>().ok()),
+        at_in_array,
+    ) else {
+        return;
+    };
+    let depth = at.tokens().len() - 1;
+    let tokens = pointer.tokens();
+    if tokens.len() <= depth || tokens[..depth] != at.tokens()[..depth] {
+        return;
+    }
+    let Ok(index) = tokens[depth].parse::<usize>() else {
+        return;
+    };
+    let shifted = if inserted && index >= at_index {
+        index + 1
+    } else if !inserted && index > at_index {
+        index - 1
+    } else {
+        return;
+    };
+
+    let mut tokens = tokens.to_vec();
+    tokens[depth] = shifted.to_string();
+    *pointer = JSONPointer::from_tokens(tokens);
+}
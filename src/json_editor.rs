@@ -0,0 +1,300 @@
+use crate::{JSONError, JSONPatch, JSONPatchOperation, JSONPointer, JSONValue};
+
+/// Edits a JSON Value, recording each change so it can be undone, redone,
+/// grouped into transactions, or exported as a JSON Patch
+///
+/// Each edit outside a transaction is one step of the undo history,
+/// and a committed transaction is one step however many edits it made.
+/// Undoing a removal from a JSON Object adds the key back at the end of the JSON Object.
+///
+/// # Example
+///
+/// ```
+/// use parson::{json, JSONEditor};
+///
+/// let mut editor = JSONEditor::new(json!({ "name": "api", "ports": [80] }));
+/// editor.set("/name", json!("gateway")).unwrap();
+/// editor.insert("/ports/-", json!(443)).unwrap();
+/// editor.remove("/ports/0").unwrap();
+/// assert_eq!(editor.value(), &json!({ "name": "gateway", "ports": [443] }));
+///
+/// editor.undo().unwrap();
+/// editor.undo().unwrap();
+/// assert_eq!(editor.value(), &json!({ "name": "gateway", "ports": [80] }));
+/// editor.redo().unwrap();
+/// assert_eq!(editor.value(), &json!({ "name": "gateway", "ports": [80, 443] }));
+///
+/// #[cfg(feature = "preserve_order")]
+/// assert_eq!(
+///     editor.patch().to_string(),
+///     r#"[ { "op": "replace", "path": "/name", "value": "gateway" }, { "op": "add", "path": "/ports/1", "value": 443 } ]"#
+/// );
+/// ```
+///
+/// A transaction can be rolled back, or committed as one step of the undo history
+///
+/// ```
+/// use parson::{json, JSONEditor};
+///
+/// let mut editor = JSONEditor::new(json!({ "db": { "host": "localhost" } }));
+///
+/// let error = editor
+///     .transaction(|editor| {
+///         editor.set("/db/host", json!("db.internal"))?;
+///         editor.set("/db/port/number", json!(5432))?;
+///         Ok(())
+///     })
+///     .unwrap_err();
+/// assert_eq!(error.get_message(), "Key <port> not found (at /db/port)");
+/// assert_eq!(editor.value(), &json!({ "db": { "host": "localhost" } }));
+///
+/// editor.begin().unwrap();
+/// editor.set("/db/host", json!("db.internal")).unwrap();
+/// editor.set("/db/port", json!(5432)).unwrap();
+/// editor.commit().unwrap();
+/// assert_eq!(editor.value(), &json!({ "db": { "host": "db.internal", "port": 5432 } }));
+///
+/// editor.undo().unwrap();
+/// assert_eq!(editor.value(), &json!({ "db": { "host": "localhost" } }));
+/// assert!(!editor.can_undo());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct JSONEditor {
+    value: JSONValue,
+    undo: Vec<Vec<Step>>,
+    redo: Vec<Vec<Step>>,
+    transaction: Option<Vec<Step>>,
+}
+
+/// An edit, and the operation which undoes it
+#[derive(Debug, Clone, PartialEq)]
+struct Step {
+    operation: JSONPatchOperation,
+    inverse: JSONPatchOperation,
+}
+
+impl JSONEditor {
+    /// Start editing a JSON Value with an empty history
+    pub fn new(value: JSONValue) -> Self {
+        JSONEditor {
+            value,
+            undo: Vec::new(),
+            redo: Vec::new(),
+            transaction: None,
+        }
+    }
+
+    /// Get the JSON Value with every edit so far
+    pub fn value(&self) -> &JSONValue {
+        &self.value
+    }
+
+    /// Get the JSON Value with every edit so far, consuming the editor
+    pub fn into_value(self) -> JSONValue {
+        self.value
+    }
+
+    /// Set the value `pointer` refers to, adding a key to a JSON Object or an item
+    /// to the end of a JSON Array with `-`, and returning the value it replaced
+    pub fn set(&mut self, pointer: &str, value: JSONValue) -> Result<Option<JSONValue>, JSONError> {
+        let pointer = self.end_of_array(JSONPointer::parse(pointer)?);
+        let old = pointer.set(&mut self.value, value.clone(), false)?;
+        let (operation, inverse) = match &old {
+            Some(old) => (
+                JSONPatchOperation::Replace {
+                    path: pointer.clone(),
+                    value,
+                },
+                JSONPatchOperation::Replace {
+                    path: pointer,
+                    value: old.clone(),
+                },
+            ),
+            None => (
+                JSONPatchOperation::Add {
+                    path: pointer.clone(),
+                    value,
+                },
+                JSONPatchOperation::Remove { path: pointer },
+            ),
+        };
+        self.record(Step { operation, inverse });
+        Ok(old)
+    }
+
+    /// Insert a value where `pointer` refers to, the way a JSON Patch `add` does,
+    /// so an index into a JSON Array shifts the items from that index up
+    pub fn insert(
+        &mut self,
+        pointer: &str,
+        value: JSONValue,
+    ) -> Result<Option<JSONValue>, JSONError> {
+        let pointer = self.end_of_array(JSONPointer::parse(pointer)?);
+        let old = pointer.insert(&mut self.value, value.clone())?;
+        let inverse = match &old {
+            Some(old) => JSONPatchOperation::Replace {
+                path: pointer.clone(),
+                value: old.clone(),
+            },
+            None => JSONPatchOperation::Remove {
+                path: pointer.clone(),
+            },
+        };
+        self.record(Step {
+            operation: JSONPatchOperation::Add {
+                path: pointer,
+                value,
+            },
+            inverse,
+        });
+        Ok(old)
+    }
+
+    /// Remove the value `pointer` refers to and return it
+    pub fn remove(&mut self, pointer: &str) -> Result<JSONValue, JSONError> {
+        let pointer = JSONPointer::parse(pointer)?;
+        let old = pointer.remove(&mut self.value)?;
+        self.record(Step {
+            operation: JSONPatchOperation::Remove {
+                path: pointer.clone(),
+            },
+            inverse: JSONPatchOperation::Add {
+                path: pointer,
+                value: old.clone(),
+            },
+        });
+        Ok(old)
+    }
+
+    /// Start a transaction, so the edits until [`commit`](JSONEditor::commit)
+    /// are one step of the undo history, or can all be undone with [`rollback`](JSONEditor::rollback)
+    pub fn begin(&mut self) -> Result<(), JSONError> {
+        if self.transaction.is_some() {
+            return Err(JSONError::with_path("A transaction is already open", ""));
+        }
+        self.transaction = Some(Vec::new());
+        Ok(())
+    }
+
+    /// Keep the edits of the open transaction as one step of the undo history
+    pub fn commit(&mut self) -> Result<(), JSONError> {
+        let steps = self.take_transaction()?;
+        if !steps.is_empty() {
+            self.undo.push(steps);
+        }
+        Ok(())
+    }
+
+    /// Undo every edit of the open transaction
+    pub fn rollback(&mut self) -> Result<(), JSONError> {
+        let steps = self.take_transaction()?;
+        self.apply_inverses(&steps)
+    }
+
+    /// Run `edit` in a transaction, committing it if `edit` succeeds and rolling it back if it fails
+    pub fn transaction<T, F: FnOnce(&mut JSONEditor) -> Result<T, JSONError>>(
+        &mut self,
+        edit: F,
+    ) -> Result<T, JSONError> {
+        self.begin()?;
+        match edit(self) {
+            Ok(result) => {
+                self.commit()?;
+                Ok(result)
+            }
+            Err(error) => {
+                self.rollback()?;
+                Err(error)
+            }
+        }
+    }
+
+    /// Undo the last step of the undo history, returning `false` if there is none
+    pub fn undo(&mut self) -> Result<bool, JSONError> {
+        self.no_transaction("undo")?;
+        let Some(steps) = self.undo.pop() else {
+            return Ok(false);
+        };
+        self.apply_inverses(&steps)?;
+        self.redo.push(steps);
+        Ok(true)
+    }
+
+    /// Redo the last undone step, returning `false` if there is none
+    pub fn redo(&mut self) -> Result<bool, JSONError> {
+        self.no_transaction("redo")?;
+        let Some(steps) = self.redo.pop() else {
+            return Ok(false);
+        };
+        for step in &steps {
+            step.operation.apply(&mut self.value)?;
+        }
+        self.undo.push(steps);
+        Ok(true)
+    }
+
+    /// If there is a step to undo
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    /// If there is an undone step to redo
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Get the edits which have not been undone, including those of an open transaction,
+    /// as a JSON Patch which turns the original JSON Value into the current one
+    pub fn patch(&self) -> JSONPatch {
+        self.undo
+            .iter()
+            .flatten()
+            .chain(self.transaction.iter().flatten())
+            .map(|step| step.operation.clone())
+            .collect()
+    }
+
+    /// Replace a last token of `-` in a pointer to a JSON Array with the index it adds at,
+    /// so the recorded operations can be undone
+    fn end_of_array(&self, pointer: JSONPointer) -> JSONPointer {
+        if pointer.last() != Some("-") {
+            return pointer;
+        }
+        let parent = pointer.parent().unwrap_or_default();
+        match parent.resolve(&self.value).map(JSONValue::as_array) {
+            Ok(Some(json_array)) => parent.join(json_array.len().to_string()),
+            _ => pointer,
+        }
+    }
+
+    fn record(&mut self, step: Step) {
+        self.redo.clear();
+        match &mut self.transaction {
+            Some(steps) => steps.push(step),
+            None => self.undo.push(vec![step]),
+        }
+    }
+
+    fn take_transaction(&mut self) -> Result<Vec<Step>, JSONError> {
+        self.transaction
+            .take()
+            .ok_or_else(|| JSONError::with_path("No transaction is open", ""))
+    }
+
+    fn no_transaction(&self, action: &str) -> Result<(), JSONError> {
+        match self.transaction {
+            Some(_) => Err(JSONError::with_path(
+                &format!("Cannot {} while a transaction is open", action),
+                "",
+            )),
+            None => Ok(()),
+        }
+    }
+
+    fn apply_inverses(&mut self, steps: &[Step]) -> Result<(), JSONError> {
+        for step in steps.iter().rev() {
+            step.inverse.apply(&mut self.value)?;
+        }
+        Ok(())
+    }
+}
@@ -4,6 +4,7 @@ pub struct JSONError {
     message: String,
     line: usize,
     column: usize,
+    path: Option<String>,
 }
 
 impl Debug for JSONError {
@@ -24,14 +25,49 @@ impl JSONError {
             message: message.to_string(),
             line,
             column,
+            path: None,
+        }
+    }
+
+    /// Create a new JSON Error about the value at a JSON Pointer path,
+    /// rather than about a position in the input
+    pub fn with_path(message: &str, path: &str) -> Self {
+        JSONError {
+            message: message.to_string(),
+            line: 0,
+            column: 0,
+            path: Some(path.to_string()),
         }
     }
 
     pub fn get_message(&self) -> String {
-        format!(
-            "{} (line {} column {})",
-            self.message, self.line, self.column
-        )
+        match &self.path {
+            Some(path) if path.is_empty() => self.message.clone(),
+            Some(path) => format!("{} (at {})", self.message, path),
+            None => format!(
+                "{} (line {} column {})",
+                self.message, self.line, self.column
+            ),
+        }
+    }
+
+    /// Get the JSON Pointer path of the value the error is about, if it is about a value
+    pub fn get_path(&self) -> Option<&str> {
+        self.path.as_deref()
+    }
+
+    /// Prepend context such as which operation failed to the message of the error
+    pub(crate) fn prefix_message(mut self, prefix: &str) -> Self {
+        self.message = format!("{}{}", prefix, self.message);
+        self
+    }
+
+    /// Prepend a key or index to the path of the error, as it bubbles up out of a container
+    pub(crate) fn prefix_path(mut self, token: &str) -> Self {
+        let path = self.path.take().unwrap_or_default();
+        let token = token.replace('~', "~0").replace('/', "~1");
+        self.path = Some(format!("/{}{}", token, path));
+        self
     }
 }
 
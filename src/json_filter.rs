@@ -0,0 +1,2420 @@
+use std::{cell::Cell, cmp::Ordering, fmt, fmt::Display, rc::Rc, str::FromStr};
+
+use crate::{
+    json_err, json_pointer::type_name, Formatter, JSONArray, JSONError, JSONObject, JSONRef,
+    JSONValue,
+};
+
+/// A filter in a subset of the jq language, compiled once so it can be run against many JSON Values
+///
+/// A filter takes one input and produces a stream of zero or more outputs. Supported are
+/// `.`, `..`, `.foo`, `."foo"`, `.[index]`, `.[start:end]`, `.[]`, the `?` suffix, pipes, commas,
+/// literals and string interpolation, array and object construction, arithmetic, comparisons,
+/// `and`/`or`/`//`, `if-then-elif-else-end`, `try-catch`, variables bound with `as`, `reduce`,
+/// and builtins such as `select`, `map`, `keys`, `length`, `to_entries` and `from_entries`.
+///
+/// As in jq, a number that is not finite is output as `null` if it is NaN,
+/// and as the largest finite number of the same sign if it is infinite.
+/// To keep untrusted filters from exhausting the stack or memory, a filter may nest at most
+/// 128 levels deep, and multiplying a string by a number may build at most 256 MiB.
+///
+/// # Example
+///
+/// ```
+/// use parson::{json, JSONFilter, JSONValue};
+///
+/// let json_value = r#"{
+///     "services": [
+///         { "name": "api", "port": 8080, "replicas": 3 },
+///         { "name": "worker", "port": null, "replicas": 2 },
+///         { "name": "web", "port": 80, "replicas": 1 }
+///     ]
+/// }"#
+/// .parse::<JSONValue>()
+/// .unwrap();
+///
+/// let run = |filter: &str| {
+///     JSONFilter::parse(filter)
+///         .unwrap()
+///         .run(&json_value)
+///         .unwrap()
+///         .iter()
+///         .map(|output| output.to_string())
+///         .collect::<Vec<_>>()
+/// };
+/// assert_eq!(run(".services[] | select(.port) | .name"), [r#""api""#, r#""web""#]);
+/// assert_eq!(run("[.services[].replicas] | add"), ["6"]);
+/// assert_eq!(
+///     json_value.filter(".services | map({ (.name): .replicas * 2 }) | add").unwrap(),
+///     [json!({ "api": 6, "worker": 4, "web": 2 })]
+/// );
+/// assert_eq!(
+///     run(r#".services[0] | to_entries | map("\(.key)=\(.value)") | sort | join(",")"#),
+///     [r#""name=api,port=8080,replicas=3""#]
+/// );
+/// assert_eq!(
+///     json_value.filter("reduce .services[] as $service ({}; . + { ($service.name): $service.port })").unwrap(),
+///     [json!({ "api": 8080, "worker": null, "web": 80 })]
+/// );
+/// assert_eq!(
+///     run(".services[] | if .replicas > 2 then \"scale\" elif .port == null then \"internal\" else empty end"),
+///     [r#""scale""#, r#""internal""#]
+/// );
+/// assert_eq!(run(".services | length as $count | [range($count)] | map(. * 10)"), ["[ 0, 10, 20 ]"]);
+/// assert_eq!(run("[.services[] | .port // 0] | sort | first, last"), ["0", "8080"]);
+/// assert_eq!(run(r#"try error("failed") catch "caught: \(.)""#), [r#""caught: failed""#]);
+/// assert_eq!(run(".services[1].name[1:4], (.services[0] | keys)"), [r#""ork""#, r#"[ "name", "port", "replicas" ]"#]);
+/// assert_eq!(run(r#""ab" * 2, "ab" * 0, [-1 | sqrt], (1e1000 | . > 1e308)"#), [r#""abab""#, "null", "[ null ]", "true"]);
+/// assert_eq!(run("-1e1000"), [JSONValue::from(-f64::MAX).to_string()]);
+///
+/// let error = JSONFilter::parse(".services[] | select(.port").unwrap_err();
+/// assert_eq!(error.get_message(), "Expected <)>, got end of filter (line 1 column 27)");
+///
+/// let error = JSONFilter::parse(".services | map(.name) | $names").unwrap_err();
+/// assert_eq!(error.get_message(), "Variable <$names> is not defined (line 1 column 26)");
+///
+/// let error = json_value.filter(".services[] | .name + .port").unwrap_err();
+/// assert_eq!(
+///     error.get_message(),
+///     r#"JSON String ("api") and JSON Number (8080) cannot be added (line 1 column 21)"#
+/// );
+///
+/// let error = json_value.filter(r#""x" * 1e18"#).unwrap_err();
+/// assert_eq!(
+///     error.get_message(),
+///     r#"JSON String ("x") and JSON Number (1000000000000000000) cannot be multiplied because the result is too long (line 1 column 5)"#
+/// );
+///
+/// let error = JSONFilter::parse(&format!("{}.{}", "(".repeat(200), ")".repeat(200))).unwrap_err();
+/// assert_eq!(error.get_message(), "Filter nested too deeply (line 1 column 129)");
+/// ```
+#[derive(Debug, Clone)]
+pub struct JSONFilter {
+    source: String,
+    expression: Expression,
+}
+
+impl Display for JSONFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+impl FromStr for JSONFilter {
+    type Err = JSONError;
+
+    fn from_str(filter: &str) -> Result<Self, Self::Err> {
+        JSONFilter::parse(filter)
+    }
+}
+
+impl JSONFilter {
+    /// Compile a filter, failing with the position of the first syntax error,
+    /// undefined variable or unknown function
+    pub fn parse(filter: &str) -> Result<Self, JSONError> {
+        let mut lexer = FilterLexer {
+            chars: filter.chars().collect(),
+            position: 0,
+            line: 1,
+            column: 1,
+            interpolation_depth: 0,
+        };
+        let mut parser = FilterParser {
+            tokens: lexer.lex(false)?,
+            position: 0,
+            scope: vec![],
+            depth: 0,
+        };
+        let expression = parser.parse_pipe(true)?;
+        parser.expect_end()?;
+
+        Ok(JSONFilter {
+            source: filter.to_string(),
+            expression,
+        })
+    }
+
+    /// Run the filter against an input, collecting every output.
+    /// Fails with the first error the filter raises that is not caught with `try` or `?`
+    pub fn run(&self, input: &JSONValue) -> Result<Vec<JSONValue>, JSONError> {
+        let mut outputs = vec![];
+        self.run_each(input, |output| outputs.push(output))?;
+        Ok(outputs)
+    }
+
+    /// Run the filter against an input, passing each output to `output` as soon as it is produced.
+    /// Outputs produced before an error are still passed on
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use parson::{JSONFilter, JSONValue};
+    ///
+    /// let filter = ".[] | 10 / .".parse::<JSONFilter>().unwrap();
+    /// let mut outputs = vec![];
+    /// let result = filter.run_each(&"[1, 2, 0, 5]".parse().unwrap(), |output| {
+    ///     outputs.push(output.as_f64().unwrap())
+    /// });
+    /// assert_eq!(outputs, [10.0, 5.0]);
+    /// assert_eq!(
+    ///     result.unwrap_err().get_message(),
+    ///     "JSON Number (10) and JSON Number (0) cannot be divided because the divisor is zero (line 1 column 10)"
+    /// );
+    /// ```
+    pub fn run_each<F: FnMut(JSONValue)>(
+        &self,
+        input: &JSONValue,
+        mut output: F,
+    ) -> Result<(), JSONError> {
+        let evaluator = Evaluator {
+            labels: Cell::new(0),
+        };
+        let result = evaluator.eval(
+            &self.expression,
+            input,
+            &Variables::default(),
+            &mut |mut value| {
+                make_finite(&mut value);
+                output(value);
+                Ok(())
+            },
+        );
+
+        match result {
+            Err(Halt::Error(value, line, column)) => {
+                let message = match value.as_str() {
+                    Some(message) => message.to_string(),
+                    None => format!("{} (not a string)", to_json(&value)),
+                };
+                Err(JSONError::new(&message, line, column))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl JSONValue {
+    /// Compile a jq filter and run it against the JSON Value, collecting every output
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use parson::JSONValue;
+    ///
+    /// let json_value = r#"{ "a": [1, 2, 3] }"#.parse::<JSONValue>().unwrap();
+    /// let outputs = json_value.filter(".a[] | select(. >= 2) | . * 100").unwrap();
+    /// assert_eq!(outputs, [JSONValue::from(200), JSONValue::from(300)]);
+    /// ```
+    pub fn filter(&self, filter: &str) -> Result<Vec<JSONValue>, JSONError> {
+        JSONFilter::parse(filter)?.run(self)
+    }
+}
+
+// Lexing
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Dot,
+    DotDot,
+    Field(String),
+    Variable(String),
+    Identifier(String),
+    Number(f64),
+    String(Vec<StringPart>),
+    Symbol(&'static str),
+    End,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum StringPart {
+    Literal(String),
+    /// The tokens inside `\( ... )`, ending with an `End` token for the closing parenthesis
+    Interpolation(Vec<Spanned>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Spanned {
+    token: Token,
+    /// The source text of the token, for error messages
+    text: String,
+    line: usize,
+    column: usize,
+}
+
+impl Spanned {
+    fn describe(&self) -> String {
+        if self.text.is_empty() {
+            "end of filter".to_string()
+        } else {
+            format!("<{}>", self.text)
+        }
+    }
+}
+
+/// Two character symbols come first, so they are matched before their first character is
+const SYMBOLS: [&str; 23] = [
+    "==", "!=", "<=", ">=", "//", "[", "]", "{", "}", "(", ")", "|", ",", ":", ";", "?", "+", "-",
+    "*", "/", "%", "<", ">",
+];
+
+struct FilterLexer {
+    chars: Vec<char>,
+    position: usize,
+    line: usize,
+    column: usize,
+    /// How many string interpolations deep the lexer is
+    interpolation_depth: usize,
+}
+
+impl FilterLexer {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.position + offset).copied()
+    }
+
+    fn advance(&mut self) -> Option<char> {
+        let char = self.peek()?;
+        self.position += 1;
+        if char == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(char)
+    }
+
+    /// Lex the whole filter, or only up to the parenthesis closing an interpolation
+    fn lex(&mut self, interpolation: bool) -> Result<Vec<Spanned>, JSONError> {
+        let (start_line, start_column) = (self.line, self.column);
+        let mut tokens = vec![];
+        let mut depth = 0;
+
+        loop {
+            while let Some(char) = self.peek() {
+                if char == '#' {
+                    while !matches!(self.peek(), None | Some('\n')) {
+                        self.advance();
+                    }
+                } else if char.is_whitespace() {
+                    self.advance();
+                } else {
+                    break;
+                }
+            }
+
+            let (line, column, start) = (self.line, self.column, self.position);
+            let Some(char) = self.peek() else {
+                if interpolation {
+                    json_err!("Unterminated string interpolation"; start_line, start_column);
+                }
+                tokens.push(Spanned {
+                    token: Token::End,
+                    text: String::new(),
+                    line,
+                    column,
+                });
+                return Ok(tokens);
+            };
+
+            if interpolation && char == ')' && depth == 0 {
+                self.advance();
+                tokens.push(Spanned {
+                    token: Token::End,
+                    text: ")".to_string(),
+                    line,
+                    column,
+                });
+                return Ok(tokens);
+            }
+
+            let token = match char {
+                '.' if self.peek_at(1) == Some('.') => {
+                    self.advance();
+                    self.advance();
+                    Token::DotDot
+                }
+                '.' => {
+                    self.advance();
+                    match self.peek() {
+                        Some(char) if is_identifier_first(char) => {
+                            Token::Field(self.lex_identifier())
+                        }
+                        _ => Token::Dot,
+                    }
+                }
+                '$' => {
+                    self.advance();
+                    match self.peek() {
+                        Some(char) if is_identifier_first(char) => {
+                            Token::Variable(self.lex_identifier())
+                        }
+                        _ => json_err!("Expected a variable name after <$>"; line, column),
+                    }
+                }
+                '"' => Token::String(self.lex_string()?),
+                char if is_identifier_first(char) => Token::Identifier(self.lex_identifier()),
+                '0'..='9' => self.lex_number(line, column)?,
+                _ => {
+                    let symbol = SYMBOLS.iter().find(|symbol| {
+                        symbol
+                            .chars()
+                            .enumerate()
+                            .all(|(offset, char)| self.peek_at(offset) == Some(char))
+                    });
+                    match symbol {
+                        Some(symbol) => {
+                            for _ in 0..symbol.len() {
+                                self.advance();
+                            }
+                            match *symbol {
+                                "(" => depth += 1,
+                                ")" if depth > 0 => depth -= 1,
+                                _ => {}
+                            }
+                            Token::Symbol(symbol)
+                        }
+                        None if char == '=' => {
+                            json_err!("Unexpected character <=>, assignment is not supported"; line, column)
+                        }
+                        None => json_err!("Unexpected character <{}>", char; line, column),
+                    }
+                }
+            };
+
+            tokens.push(Spanned {
+                token,
+                text: self.chars[start..self.position].iter().collect(),
+                line,
+                column,
+            });
+        }
+    }
+
+    fn lex_identifier(&mut self) -> String {
+        let mut identifier = String::new();
+        while let Some(char) = self.peek() {
+            if !is_identifier_first(char) && !char.is_ascii_digit() {
+                break;
+            }
+            identifier.push(char);
+            self.advance();
+        }
+        identifier
+    }
+
+    fn lex_number(&mut self, line: usize, column: usize) -> Result<Token, JSONError> {
+        let mut lexeme = String::new();
+        let digits = |lexer: &mut FilterLexer, lexeme: &mut String| {
+            while let Some(char @ '0'..='9') = lexer.peek() {
+                lexeme.push(char);
+                lexer.advance();
+            }
+        };
+
+        digits(self, &mut lexeme);
+        if self.peek() == Some('.') && matches!(self.peek_at(1), Some('0'..='9')) {
+            lexeme.push('.');
+            self.advance();
+            digits(self, &mut lexeme);
+        }
+        if matches!(self.peek(), Some('e' | 'E')) {
+            let sign = matches!(self.peek_at(1), Some('+' | '-'));
+            let offset = if sign { 2 } else { 1 };
+            if matches!(self.peek_at(offset), Some('0'..='9')) {
+                for _ in 0..offset {
+                    lexeme.push(self.advance().unwrap_or_default());
+                }
+                digits(self, &mut lexeme);
+            }
+        }
+
+        match lexeme.parse::<f64>() {
+            Ok(number) => Ok(Token::Number(number)),
+            Err(_) => json_err!("Invalid number <{}>", lexeme; line, column),
+        }
+    }
+
+    fn lex_string(&mut self) -> Result<Vec<StringPart>, JSONError> {
+        let (line, column) = (self.line, self.column);
+        self.advance();
+
+        let mut parts = vec![];
+        let mut literal = String::new();
+        loop {
+            let (escape_line, escape_column) = (self.line, self.column);
+            match self.advance() {
+                None => json_err!("Unterminated string"; line, column),
+                Some('"') => break,
+                Some('\\') => match self.advance() {
+                    Some('"') => literal.push('"'),
+                    Some('\\') => literal.push('\\'),
+                    Some('/') => literal.push('/'),
+                    Some('b') => literal.push('\u{8}'),
+                    Some('f') => literal.push('\u{c}'),
+                    Some('n') => literal.push('\n'),
+                    Some('r') => literal.push('\r'),
+                    Some('t') => literal.push('\t'),
+                    Some('u') => literal.push(self.lex_unicode_escape(escape_line, escape_column)?),
+                    Some('(') => {
+                        if !literal.is_empty() {
+                            parts.push(StringPart::Literal(std::mem::take(&mut literal)));
+                        }
+                        if self.interpolation_depth == MAX_DEPTH {
+                            json_err!("Filter nested too deeply"; escape_line, escape_column);
+                        }
+                        self.interpolation_depth += 1;
+                        parts.push(StringPart::Interpolation(self.lex(true)?));
+                        self.interpolation_depth -= 1;
+                    }
+                    _ => json_err!("Invalid escape sequence"; escape_line, escape_column),
+                },
+                Some(char) => literal.push(char),
+            }
+        }
+
+        if !literal.is_empty() || parts.is_empty() {
+            parts.push(StringPart::Literal(literal));
+        }
+        Ok(parts)
+    }
+
+    fn lex_unicode_escape(&mut self, line: usize, column: usize) -> Result<char, JSONError> {
+        let hex = |lexer: &mut FilterLexer| {
+            let mut code = 0;
+            for _ in 0..4 {
+                match lexer.advance().and_then(|char| char.to_digit(16)) {
+                    Some(digit) => code = code * 16 + digit,
+                    None => json_err!("Expected 4 hex digits in unicode escape"; line, column),
+                }
+            }
+            Ok(code)
+        };
+
+        let high = hex(self)?;
+        let code = if (0xD800..0xDC00).contains(&high)
+            && self.peek() == Some('\\')
+            && self.peek_at(1) == Some('u')
+        {
+            self.advance();
+            self.advance();
+            let low = hex(self)?;
+            if !(0xDC00..0xE000).contains(&low) {
+                json_err!("Unpaired surrogate in unicode escape"; line, column);
+            }
+            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+        } else {
+            high
+        };
+
+        match char::from_u32(code) {
+            Some(char) => Ok(char),
+            None => json_err!("Unpaired surrogate in unicode escape"; line, column),
+        }
+    }
+}
+
+fn is_identifier_first(char: char) -> bool {
+    char.is_ascii_alphabetic() || char == '_'
+}
+
+// Parsing
+
+#[derive(Debug, Clone)]
+struct Expression {
+    kind: Kind,
+    line: usize,
+    column: usize,
+}
+
+#[derive(Debug, Clone)]
+enum Kind {
+    Identity,
+    Recurse,
+    Literal(JSONValue),
+    Interpolation(Vec<Segment>),
+    Index(Box<Expression>, Box<Expression>),
+    Slice(
+        Box<Expression>,
+        Option<Box<Expression>>,
+        Option<Box<Expression>>,
+    ),
+    Iterate(Box<Expression>),
+    Try(Box<Expression>, Option<Box<Expression>>),
+    Pipe(Box<Expression>, Box<Expression>),
+    Comma(Box<Expression>, Box<Expression>),
+    Negate(Box<Expression>),
+    Arithmetic(Box<Expression>, Operator, Box<Expression>),
+    Comparison(Box<Expression>, Ordering, bool, Box<Expression>),
+    And(Box<Expression>, Box<Expression>),
+    Or(Box<Expression>, Box<Expression>),
+    Alternative(Box<Expression>, Box<Expression>),
+    Array(Option<Box<Expression>>),
+    Object(Vec<(Expression, Expression)>),
+    If(Box<Expression>, Box<Expression>, Option<Box<Expression>>),
+    Bind(Box<Expression>, String, Box<Expression>),
+    Reduce(Box<Expression>, String, Box<Expression>, Box<Expression>),
+    Variable(String),
+    Call(String, Vec<Expression>),
+}
+
+#[derive(Debug, Clone)]
+enum Segment {
+    Literal(String),
+    Expression(Expression),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Modulo,
+}
+
+const KEYWORDS: [&str; 17] = [
+    "if", "then", "elif", "else", "end", "as", "reduce", "foreach", "try", "catch", "label",
+    "import", "include", "def", "and", "or", "__loc__",
+];
+
+/// The builtin functions and how many arguments each takes
+const FUNCTIONS: [(&str, usize); 63] = [
+    ("empty", 0),
+    ("not", 0),
+    ("length", 0),
+    ("keys", 0),
+    ("keys_unsorted", 0),
+    ("to_entries", 0),
+    ("from_entries", 0),
+    ("add", 0),
+    ("type", 0),
+    ("sort", 0),
+    ("unique", 0),
+    ("reverse", 0),
+    ("min", 0),
+    ("max", 0),
+    ("flatten", 0),
+    ("floor", 0),
+    ("sqrt", 0),
+    ("tostring", 0),
+    ("tonumber", 0),
+    ("tojson", 0),
+    ("fromjson", 0),
+    ("ascii_downcase", 0),
+    ("ascii_upcase", 0),
+    ("first", 0),
+    ("last", 0),
+    ("any", 0),
+    ("all", 0),
+    ("recurse", 0),
+    ("error", 0),
+    ("map", 1),
+    ("map_values", 1),
+    ("select", 1),
+    ("with_entries", 1),
+    ("sort_by", 1),
+    ("group_by", 1),
+    ("has", 1),
+    ("first", 1),
+    ("last", 1),
+    ("any", 1),
+    ("all", 1),
+    ("range", 1),
+    ("error", 1),
+    ("join", 1),
+    ("split", 1),
+    ("startswith", 1),
+    ("endswith", 1),
+    ("ltrimstr", 1),
+    ("rtrimstr", 1),
+    ("limit", 2),
+    ("range", 2),
+    ("min_by", 1),
+    ("max_by", 1),
+    ("unique_by", 1),
+    ("values", 0),
+    ("isempty", 1),
+    ("nulls", 0),
+    ("booleans", 0),
+    ("numbers", 0),
+    ("strings", 0),
+    ("arrays", 0),
+    ("objects", 0),
+    ("iterables", 0),
+    ("scalars", 0),
+];
+
+/// Deepest that a filter may nest, so parsing and evaluating it cannot overflow the stack.
+/// Every operator in a chain such as `a, b, c` or `.a.b.c` counts as a level,
+/// as each one wraps the expression before it
+const MAX_DEPTH: usize = 128;
+
+struct FilterParser {
+    tokens: Vec<Spanned>,
+    position: usize,
+    /// Variables bound around the expression being parsed
+    scope: Vec<String>,
+    /// How many levels deep the expression being parsed is nested
+    depth: usize,
+}
+
+impl FilterParser {
+    fn peek(&self) -> &Spanned {
+        &self.tokens[self.position.min(self.tokens.len() - 1)]
+    }
+
+    fn advance(&mut self) -> Spanned {
+        let spanned = self.peek().clone();
+        if self.position < self.tokens.len() - 1 {
+            self.position += 1;
+        }
+        spanned
+    }
+
+    fn error<T>(&self, message: &str, at: &Spanned) -> Result<T, JSONError> {
+        json_err!(message; at.line, at.column)
+    }
+
+    /// Go one level deeper, failing at the next token past [`MAX_DEPTH`].
+    /// Callers restore `depth` once the nested expression is parsed
+    fn nest(&mut self) -> Result<(), JSONError> {
+        if self.depth == MAX_DEPTH {
+            return self.error("Filter nested too deeply", self.peek());
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    fn is_symbol(&self, symbol: &str) -> bool {
+        matches!(self.peek().token, Token::Symbol(next) if next == symbol)
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(&self.peek().token, Token::Identifier(identifier) if identifier == keyword)
+    }
+
+    fn expect_symbol(&mut self, symbol: &str) -> Result<Spanned, JSONError> {
+        if self.is_symbol(symbol) {
+            Ok(self.advance())
+        } else {
+            let next = self.peek();
+            self.error(
+                &format!("Expected <{}>, got {}", symbol, next.describe()),
+                next,
+            )
+        }
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<Spanned, JSONError> {
+        if self.is_keyword(keyword) {
+            Ok(self.advance())
+        } else {
+            let next = self.peek();
+            self.error(
+                &format!("Expected <{}>, got {}", keyword, next.describe()),
+                next,
+            )
+        }
+    }
+
+    fn expect_variable(&mut self) -> Result<String, JSONError> {
+        match &self.peek().token {
+            Token::Variable(name) => {
+                let name = name.clone();
+                self.advance();
+                Ok(name)
+            }
+            _ => {
+                let next = self.peek();
+                self.error(
+                    &format!("Expected a variable, got {}", next.describe()),
+                    next,
+                )
+            }
+        }
+    }
+
+    fn expect_end(&mut self) -> Result<(), JSONError> {
+        match self.peek().token {
+            Token::End => Ok(()),
+            _ => {
+                let next = self.peek();
+                self.error(&format!("Unexpected {}", next.describe()), next)
+            }
+        }
+    }
+
+    /// Parse `a | b`. Object values are parsed without `comma`, so the comma ends the value
+    fn parse_pipe(&mut self, comma: bool) -> Result<Expression, JSONError> {
+        let depth = self.depth;
+        self.nest()?;
+        let mut left = self.parse_comma(comma)?;
+        if self.is_symbol("|") {
+            let at = self.advance();
+            let right = self.parse_pipe(comma)?;
+            left = expression(Kind::Pipe(Box::new(left), Box::new(right)), &at);
+        }
+        self.depth = depth;
+        Ok(left)
+    }
+
+    fn parse_comma(&mut self, comma: bool) -> Result<Expression, JSONError> {
+        let depth = self.depth;
+        let mut left = self.parse_alternative()?;
+        while comma && self.is_symbol(",") {
+            let at = self.advance();
+            self.nest()?;
+            let right = self.parse_alternative()?;
+            left = expression(Kind::Comma(Box::new(left), Box::new(right)), &at);
+        }
+        self.depth = depth;
+        Ok(left)
+    }
+
+    fn parse_alternative(&mut self) -> Result<Expression, JSONError> {
+        let depth = self.depth;
+        let mut left = self.parse_or()?;
+        if self.is_symbol("//") {
+            let at = self.advance();
+            self.nest()?;
+            let right = self.parse_alternative()?;
+            left = expression(Kind::Alternative(Box::new(left), Box::new(right)), &at);
+        }
+        self.depth = depth;
+        Ok(left)
+    }
+
+    fn parse_or(&mut self) -> Result<Expression, JSONError> {
+        let depth = self.depth;
+        let mut left = self.parse_and()?;
+        while self.is_keyword("or") {
+            let at = self.advance();
+            self.nest()?;
+            let right = self.parse_and()?;
+            left = expression(Kind::Or(Box::new(left), Box::new(right)), &at);
+        }
+        self.depth = depth;
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> Result<Expression, JSONError> {
+        let depth = self.depth;
+        let mut left = self.parse_comparison()?;
+        while self.is_keyword("and") {
+            let at = self.advance();
+            self.nest()?;
+            let right = self.parse_comparison()?;
+            left = expression(Kind::And(Box::new(left), Box::new(right)), &at);
+        }
+        self.depth = depth;
+        Ok(left)
+    }
+
+    fn comparison(&self) -> Option<(Ordering, bool)> {
+        match self.peek().token {
+            Token::Symbol("==") => Some((Ordering::Equal, true)),
+            Token::Symbol("!=") => Some((Ordering::Equal, false)),
+            Token::Symbol("<") => Some((Ordering::Less, true)),
+            Token::Symbol(">=") => Some((Ordering::Less, false)),
+            Token::Symbol(">") => Some((Ordering::Greater, true)),
+            Token::Symbol("<=") => Some((Ordering::Greater, false)),
+            _ => None,
+        }
+    }
+
+    fn parse_comparison(&mut self) -> Result<Expression, JSONError> {
+        let left = self.parse_additive()?;
+        let Some((ordering, matches)) = self.comparison() else {
+            return Ok(left);
+        };
+
+        let at = self.advance();
+        let right = self.parse_additive()?;
+        if self.comparison().is_some() {
+            let next = self.peek();
+            return self.error(
+                &format!("Comparisons cannot be chained, got {}", next.describe()),
+                next,
+            );
+        }
+        Ok(expression(
+            Kind::Comparison(Box::new(left), ordering, matches, Box::new(right)),
+            &at,
+        ))
+    }
+
+    fn parse_additive(&mut self) -> Result<Expression, JSONError> {
+        let depth = self.depth;
+        let mut left = self.parse_multiplicative()?;
+        loop {
+            let operator = match self.peek().token {
+                Token::Symbol("+") => Operator::Add,
+                Token::Symbol("-") => Operator::Subtract,
+                _ => {
+                    self.depth = depth;
+                    return Ok(left);
+                }
+            };
+            let at = self.advance();
+            self.nest()?;
+            let right = self.parse_multiplicative()?;
+            left = expression(
+                Kind::Arithmetic(Box::new(left), operator, Box::new(right)),
+                &at,
+            );
+        }
+    }
+
+    fn parse_multiplicative(&mut self) -> Result<Expression, JSONError> {
+        let depth = self.depth;
+        let mut left = self.parse_unary()?;
+        loop {
+            let operator = match self.peek().token {
+                Token::Symbol("*") => Operator::Multiply,
+                Token::Symbol("/") => Operator::Divide,
+                Token::Symbol("%") => Operator::Modulo,
+                _ => {
+                    self.depth = depth;
+                    return Ok(left);
+                }
+            };
+            let at = self.advance();
+            self.nest()?;
+            let right = self.parse_unary()?;
+            left = expression(
+                Kind::Arithmetic(Box::new(left), operator, Box::new(right)),
+                &at,
+            );
+        }
+    }
+
+    /// Parse a negation, or a term optionally bound to a variable for the rest of the pipe
+    fn parse_unary(&mut self) -> Result<Expression, JSONError> {
+        if self.is_symbol("-") {
+            let at = self.advance();
+            self.nest()?;
+            let operand = self.parse_unary()?;
+            self.depth -= 1;
+            return Ok(match operand.kind {
+                Kind::Literal(literal) if literal.is_number() => expression(
+                    Kind::Literal(JSONValue::from(-literal.as_f64().unwrap_or_default())),
+                    &at,
+                ),
+                kind => expression(
+                    Kind::Negate(Box::new(Expression {
+                        kind,
+                        line: operand.line,
+                        column: operand.column,
+                    })),
+                    &at,
+                ),
+            });
+        }
+
+        let term = self.parse_postfix()?;
+        if !self.is_keyword("as") {
+            return Ok(term);
+        }
+
+        let at = self.advance();
+        let name = self.expect_variable()?;
+        self.expect_symbol("|")?;
+        self.scope.push(name.clone());
+        let body = self.parse_pipe(true);
+        self.scope.pop();
+        Ok(expression(
+            Kind::Bind(Box::new(term), name, Box::new(body?)),
+            &at,
+        ))
+    }
+
+    fn parse_postfix(&mut self) -> Result<Expression, JSONError> {
+        let depth = self.depth;
+        let mut term = self.parse_primary()?;
+        loop {
+            let at = self.peek().clone();
+            match &at.token {
+                Token::Field(name) => {
+                    self.advance();
+                    self.nest()?;
+                    let key = expression(Kind::Literal(JSONValue::from(name)), &at);
+                    term = expression(Kind::Index(Box::new(term), Box::new(key)), &at);
+                }
+                Token::Dot if matches!(self.tokens[self.position + 1].token, Token::String(_)) => {
+                    self.advance();
+                    self.nest()?;
+                    let key = self.parse_primary()?;
+                    term = expression(Kind::Index(Box::new(term), Box::new(key)), &at);
+                }
+                Token::Dot if self.tokens[self.position + 1].token == Token::Symbol("[") => {
+                    self.advance();
+                }
+                Token::Symbol("[") => {
+                    self.advance();
+                    self.nest()?;
+                    term = self.parse_brackets(term, &at)?;
+                }
+                Token::Symbol("?") => {
+                    self.advance();
+                    self.nest()?;
+                    term = expression(Kind::Try(Box::new(term), None), &at);
+                }
+                _ => {
+                    self.depth = depth;
+                    return Ok(term);
+                }
+            }
+        }
+    }
+
+    /// Parse the rest of `[]`, `[index]` or `[start:end]` after the opening bracket
+    fn parse_brackets(&mut self, term: Expression, at: &Spanned) -> Result<Expression, JSONError> {
+        let term = Box::new(term);
+        if self.is_symbol("]") {
+            self.advance();
+            return Ok(expression(Kind::Iterate(term), at));
+        }
+
+        let start = if self.is_symbol(":") {
+            None
+        } else {
+            Some(Box::new(self.parse_pipe(true)?))
+        };
+        if !self.is_symbol(":") {
+            self.expect_symbol("]")?;
+            let index = start.unwrap_or_else(|| Box::new(expression(Kind::Identity, at)));
+            return Ok(expression(Kind::Index(term, index), at));
+        }
+
+        self.advance();
+        let end = if self.is_symbol("]") {
+            None
+        } else {
+            Some(Box::new(self.parse_pipe(true)?))
+        };
+        self.expect_symbol("]")?;
+        if start.is_none() && end.is_none() {
+            return self.error("A slice needs a start or an end", at);
+        }
+        Ok(expression(Kind::Slice(term, start, end), at))
+    }
+
+    fn parse_primary(&mut self) -> Result<Expression, JSONError> {
+        let at = self.advance();
+        match &at.token {
+            Token::Dot if matches!(self.peek().token, Token::String(_)) => {
+                let identity = expression(Kind::Identity, &at);
+                let key = self.parse_primary()?;
+                Ok(expression(
+                    Kind::Index(Box::new(identity), Box::new(key)),
+                    &at,
+                ))
+            }
+            Token::Dot => Ok(expression(Kind::Identity, &at)),
+            Token::DotDot => Ok(expression(Kind::Recurse, &at)),
+            Token::Field(name) => {
+                let identity = expression(Kind::Identity, &at);
+                let key = expression(Kind::Literal(JSONValue::from(name)), &at);
+                Ok(expression(
+                    Kind::Index(Box::new(identity), Box::new(key)),
+                    &at,
+                ))
+            }
+            Token::Number(number) => Ok(expression(Kind::Literal(JSONValue::from(*number)), &at)),
+            Token::String(parts) => self.parse_string(parts, &at),
+            Token::Variable(name) => {
+                if !self.scope.contains(name) {
+                    return self.error(&format!("Variable <${}> is not defined", name), &at);
+                }
+                Ok(expression(Kind::Variable(name.clone()), &at))
+            }
+            Token::Symbol("(") => {
+                let inner = self.parse_pipe(true)?;
+                self.expect_symbol(")")?;
+                Ok(inner)
+            }
+            Token::Symbol("[") => {
+                if self.is_symbol("]") {
+                    self.advance();
+                    return Ok(expression(Kind::Array(None), &at));
+                }
+                let inner = self.parse_pipe(true)?;
+                self.expect_symbol("]")?;
+                Ok(expression(Kind::Array(Some(Box::new(inner))), &at))
+            }
+            Token::Symbol("{") => self.parse_object(&at),
+            Token::Identifier(name) => match name.as_str() {
+                "if" => self.parse_if(&at),
+                "try" => {
+                    self.nest()?;
+                    let body = self.parse_postfix()?;
+                    let handler = if self.is_keyword("catch") {
+                        self.advance();
+                        Some(Box::new(self.parse_postfix()?))
+                    } else {
+                        None
+                    };
+                    self.depth -= 1;
+                    Ok(expression(Kind::Try(Box::new(body), handler), &at))
+                }
+                "reduce" => self.parse_reduce(&at),
+                "true" => Ok(expression(Kind::Literal(JSONValue::from(true)), &at)),
+                "false" => Ok(expression(Kind::Literal(JSONValue::from(false)), &at)),
+                "null" => Ok(expression(Kind::Literal(JSONValue::from(())), &at)),
+                name if KEYWORDS.contains(&name) => {
+                    self.error(&format!("Expected a filter, got {}", at.describe()), &at)
+                }
+                name => self.parse_call(name, &at),
+            },
+            _ => self.error(&format!("Expected a filter, got {}", at.describe()), &at),
+        }
+    }
+
+    fn parse_call(&mut self, name: &str, at: &Spanned) -> Result<Expression, JSONError> {
+        let mut arguments = vec![];
+        if self.is_symbol("(") {
+            self.advance();
+            loop {
+                arguments.push(self.parse_pipe(true)?);
+                if self.is_symbol(";") {
+                    self.advance();
+                } else {
+                    break;
+                }
+            }
+            self.expect_symbol(")")?;
+        }
+
+        if !FUNCTIONS.contains(&(name, arguments.len())) {
+            return self.error(
+                &format!("Unknown function <{}/{}>", name, arguments.len()),
+                at,
+            );
+        }
+        Ok(expression(Kind::Call(name.to_string(), arguments), at))
+    }
+
+    fn parse_string(
+        &mut self,
+        parts: &[StringPart],
+        at: &Spanned,
+    ) -> Result<Expression, JSONError> {
+        if let [StringPart::Literal(literal)] = parts {
+            return Ok(expression(Kind::Literal(JSONValue::from(literal)), at));
+        }
+
+        let mut segments = vec![];
+        for part in parts {
+            match part {
+                StringPart::Literal(literal) => segments.push(Segment::Literal(literal.clone())),
+                StringPart::Interpolation(tokens) => {
+                    let mut parser = FilterParser {
+                        tokens: tokens.clone(),
+                        position: 0,
+                        scope: self.scope.clone(),
+                        depth: self.depth,
+                    };
+                    let inner = parser.parse_pipe(true)?;
+                    parser.expect_end()?;
+                    segments.push(Segment::Expression(inner));
+                }
+            }
+        }
+        Ok(expression(Kind::Interpolation(segments), at))
+    }
+
+    fn parse_object(&mut self, at: &Spanned) -> Result<Expression, JSONError> {
+        let mut entries = vec![];
+        if self.is_symbol("}") {
+            self.advance();
+            return Ok(expression(Kind::Object(entries), at));
+        }
+
+        loop {
+            entries.push(self.parse_object_entry()?);
+            if self.is_symbol(",") {
+                self.advance();
+            } else if self.is_symbol("}") {
+                self.advance();
+                return Ok(expression(Kind::Object(entries), at));
+            } else {
+                let next = self.peek();
+                return self.error(
+                    &format!("Expected <,> or <}}>, got {}", next.describe()),
+                    next,
+                );
+            }
+        }
+    }
+
+    /// Parse `key: value`, or the shorthands `key`, `"key"` and `$key`
+    fn parse_object_entry(&mut self) -> Result<(Expression, Expression), JSONError> {
+        let at = self.advance();
+        let key = match &at.token {
+            Token::Identifier(name) => expression(Kind::Literal(JSONValue::from(name)), &at),
+            Token::String(parts) => self.parse_string(parts, &at)?,
+            Token::Variable(name) => {
+                if !self.scope.contains(name) {
+                    return self.error(&format!("Variable <${}> is not defined", name), &at);
+                }
+                let key = expression(Kind::Literal(JSONValue::from(name)), &at);
+                return Ok((key, expression(Kind::Variable(name.clone()), &at)));
+            }
+            Token::Symbol("(") => {
+                let key = self.parse_pipe(true)?;
+                self.expect_symbol(")")?;
+                self.expect_symbol(":")?;
+                return Ok((key, self.parse_pipe(false)?));
+            }
+            _ => {
+                return self.error(
+                    &format!("Expected an object key, got {}", at.describe()),
+                    &at,
+                )
+            }
+        };
+
+        if self.is_symbol(":") {
+            self.advance();
+            return Ok((key, self.parse_pipe(false)?));
+        }
+        let identity = expression(Kind::Identity, &at);
+        let value = expression(Kind::Index(Box::new(identity), Box::new(key.clone())), &at);
+        Ok((key, value))
+    }
+
+    /// Parse the rest of an `if` or `elif` after its keyword, up to and including `end`
+    fn parse_if(&mut self, at: &Spanned) -> Result<Expression, JSONError> {
+        self.nest()?;
+        let condition = self.parse_pipe(true)?;
+        self.expect_keyword("then")?;
+        let then = self.parse_pipe(true)?;
+
+        let otherwise = if self.is_keyword("elif") {
+            let elif_at = self.advance();
+            Some(Box::new(self.parse_if(&elif_at)?))
+        } else if self.is_keyword("else") {
+            self.advance();
+            let otherwise = self.parse_pipe(true)?;
+            self.expect_keyword("end")?;
+            Some(Box::new(otherwise))
+        } else if self.is_keyword("end") {
+            self.advance();
+            None
+        } else {
+            let next = self.peek();
+            return self.error(
+                &format!("Expected <elif>, <else> or <end>, got {}", next.describe()),
+                next,
+            );
+        };
+
+        self.depth -= 1;
+        Ok(expression(
+            Kind::If(Box::new(condition), Box::new(then), otherwise),
+            at,
+        ))
+    }
+
+    fn parse_reduce(&mut self, at: &Spanned) -> Result<Expression, JSONError> {
+        self.nest()?;
+        let source = self.parse_postfix()?;
+        self.expect_keyword("as")?;
+        let name = self.expect_variable()?;
+        self.expect_symbol("(")?;
+        let initial = self.parse_pipe(true)?;
+        self.expect_symbol(";")?;
+        self.scope.push(name.clone());
+        let update = self.parse_pipe(true);
+        self.scope.pop();
+        let update = update?;
+        self.expect_symbol(")")?;
+
+        self.depth -= 1;
+        Ok(expression(
+            Kind::Reduce(Box::new(source), name, Box::new(initial), Box::new(update)),
+            at,
+        ))
+    }
+}
+
+fn expression(kind: Kind, at: &Spanned) -> Expression {
+    Expression {
+        kind,
+        line: at.line,
+        column: at.column,
+    }
+}
+
+// Evaluation
+
+/// Why evaluation stopped early
+enum Halt {
+    /// A value raised with `error` or by a failed operation, and where it was raised
+    Error(JSONValue, usize, usize),
+    /// A halt raised after the outputs left a `try` or `//`, which they must not catch
+    Downstream(Box<Halt>),
+    /// Enough outputs were taken by the `limit` with this label
+    Break(usize),
+}
+
+type Emit<'e> = dyn FnMut(JSONValue) -> Result<(), Halt> + 'e;
+
+#[derive(Clone, Default)]
+struct Variables(Option<Rc<(String, JSONValue, Variables)>>);
+
+impl Variables {
+    fn bind(&self, name: &str, value: JSONValue) -> Self {
+        Variables(Some(Rc::new((name.to_string(), value, self.clone()))))
+    }
+
+    fn get(&self, name: &str) -> Option<&JSONValue> {
+        let mut variables = self;
+        while let Some(binding) = &variables.0 {
+            if binding.0 == name {
+                return Some(&binding.1);
+            }
+            variables = &binding.2;
+        }
+        None
+    }
+}
+
+struct Evaluator {
+    labels: Cell<usize>,
+}
+
+fn fail(at: &Expression, message: String) -> Halt {
+    Halt::Error(JSONValue::from(message), at.line, at.column)
+}
+
+fn truthy(value: &JSONValue) -> bool {
+    !value.is_null() && value.as_bool() != Some(false)
+}
+
+/// Run `f`, where any halt raised by the outputs it emits gets wrapped in `Downstream`,
+/// and return the error `f` raised itself, if any
+fn catch(f: impl FnOnce() -> Result<(), Halt>) -> Result<Option<(JSONValue, usize, usize)>, Halt> {
+    match f() {
+        Ok(()) => Ok(None),
+        Err(Halt::Error(value, line, column)) => Ok(Some((value, line, column))),
+        Err(Halt::Downstream(halt)) => Err(*halt),
+        Err(halt) => Err(halt),
+    }
+}
+
+fn downstream(halt: Halt) -> Halt {
+    Halt::Downstream(Box::new(halt))
+}
+
+impl Evaluator {
+    fn eval(
+        &self,
+        expression: &Expression,
+        input: &JSONValue,
+        variables: &Variables,
+        emit: &mut Emit,
+    ) -> Result<(), Halt> {
+        match &expression.kind {
+            Kind::Identity => emit(input.clone()),
+            Kind::Recurse => self.recurse(input, emit),
+            Kind::Literal(literal) => emit(literal.clone()),
+            Kind::Interpolation(segments) => {
+                self.interpolate(segments, String::new(), input, variables, emit)
+            }
+            Kind::Index(term, index) => self.eval(term, input, variables, &mut |target| {
+                self.eval(index, input, variables, &mut |index| {
+                    emit(index_value(expression, &target, &index)?)
+                })
+            }),
+            Kind::Slice(term, start, end) => self.eval(term, input, variables, &mut |target| {
+                self.eval_optional(start.as_deref(), input, variables, &mut |start| {
+                    self.eval_optional(end.as_deref(), input, variables, &mut |end| {
+                        emit(slice_value(expression, &target, &start, &end)?)
+                    })
+                })
+            }),
+            Kind::Iterate(term) => self.eval(term, input, variables, &mut |target| {
+                for item in iterate(expression, &target)? {
+                    emit(item.clone())?;
+                }
+                Ok(())
+            }),
+            Kind::Try(body, handler) => {
+                let error = catch(|| {
+                    self.eval(body, input, variables, &mut |value| {
+                        emit(value).map_err(downstream)
+                    })
+                })?;
+                match (error, handler) {
+                    (Some((value, _, _)), Some(handler)) => {
+                        self.eval(handler, &value, variables, emit)
+                    }
+                    _ => Ok(()),
+                }
+            }
+            Kind::Pipe(left, right) => self.eval(left, input, variables, &mut |value| {
+                self.eval(right, &value, variables, emit)
+            }),
+            Kind::Comma(left, right) => {
+                self.eval(left, input, variables, emit)?;
+                self.eval(right, input, variables, emit)
+            }
+            Kind::Negate(operand) => self.eval(operand, input, variables, &mut |value| match value
+                .as_f64()
+            {
+                Some(number) => emit(JSONValue::from(-number)),
+                None => Err(fail(
+                    expression,
+                    format!(
+                        "{} ({}) cannot be negated",
+                        type_name(&value),
+                        to_json(&value)
+                    ),
+                )),
+            }),
+            Kind::Arithmetic(left, operator, right) => {
+                self.eval(right, input, variables, &mut |right| {
+                    self.eval(left, input, variables, &mut |left| {
+                        emit(arithmetic(expression, &left, *operator, &right)?)
+                    })
+                })
+            }
+            Kind::Comparison(left, ordering, matches, right) => {
+                self.eval(right, input, variables, &mut |right| {
+                    self.eval(left, input, variables, &mut |left| {
+                        emit(JSONValue::from((left.cmp(&right) == *ordering) == *matches))
+                    })
+                })
+            }
+            Kind::And(left, right) => self.eval(left, input, variables, &mut |left| {
+                if !truthy(&left) {
+                    return emit(JSONValue::from(false));
+                }
+                self.eval(right, input, variables, &mut |right| {
+                    emit(JSONValue::from(truthy(&right)))
+                })
+            }),
+            Kind::Or(left, right) => self.eval(left, input, variables, &mut |left| {
+                if truthy(&left) {
+                    return emit(JSONValue::from(true));
+                }
+                self.eval(right, input, variables, &mut |right| {
+                    emit(JSONValue::from(truthy(&right)))
+                })
+            }),
+            Kind::Alternative(left, right) => {
+                let mut any = false;
+                catch(|| {
+                    self.eval(left, input, variables, &mut |value| {
+                        if !truthy(&value) {
+                            return Ok(());
+                        }
+                        any = true;
+                        emit(value).map_err(downstream)
+                    })
+                })?;
+                if any {
+                    return Ok(());
+                }
+                self.eval(right, input, variables, emit)
+            }
+            Kind::Array(inner) => {
+                let mut items = JSONArray::new();
+                if let Some(inner) = inner {
+                    self.eval(inner, input, variables, &mut |item| {
+                        items.push(item);
+                        Ok(())
+                    })?;
+                }
+                emit(JSONValue::from(items))
+            }
+            Kind::Object(entries) => {
+                self.construct_object(entries, JSONObject::new(), input, variables, emit)
+            }
+            Kind::If(condition, then, otherwise) => {
+                self.eval(condition, input, variables, &mut |condition| match (
+                    truthy(&condition),
+                    otherwise,
+                ) {
+                    (true, _) => self.eval(then, input, variables, emit),
+                    (false, Some(otherwise)) => self.eval(otherwise, input, variables, emit),
+                    (false, None) => emit(input.clone()),
+                })
+            }
+            Kind::Bind(source, name, body) => self.eval(source, input, variables, &mut |value| {
+                self.eval(body, input, &variables.bind(name, value), emit)
+            }),
+            Kind::Reduce(source, name, initial, update) => {
+                self.eval(initial, input, variables, &mut |initial| {
+                    let mut state = initial;
+                    self.eval(source, input, variables, &mut |item| {
+                        let mut last = None;
+                        self.eval(update, &state, &variables.bind(name, item), &mut |value| {
+                            last = Some(value);
+                            Ok(())
+                        })?;
+                        state = last.unwrap_or_else(|| JSONValue::from(()));
+                        Ok(())
+                    })?;
+                    emit(state)
+                })
+            }
+            Kind::Variable(name) => match variables.get(name) {
+                Some(value) => emit(value.clone()),
+                None => Err(fail(
+                    expression,
+                    format!("Variable <${}> is not defined", name),
+                )),
+            },
+            Kind::Call(name, arguments) => {
+                self.call(expression, name, arguments, input, variables, emit)
+            }
+        }
+    }
+
+    /// Evaluate an optional slice bound, where a missing bound is `null`
+    fn eval_optional(
+        &self,
+        expression: Option<&Expression>,
+        input: &JSONValue,
+        variables: &Variables,
+        emit: &mut Emit,
+    ) -> Result<(), Halt> {
+        match expression {
+            Some(expression) => self.eval(expression, input, variables, emit),
+            None => emit(JSONValue::from(())),
+        }
+    }
+
+    fn collect(
+        &self,
+        expression: &Expression,
+        input: &JSONValue,
+        variables: &Variables,
+    ) -> Result<Vec<JSONValue>, Halt> {
+        let mut values = vec![];
+        self.eval(expression, input, variables, &mut |value| {
+            values.push(value);
+            Ok(())
+        })?;
+        Ok(values)
+    }
+
+    fn recurse(&self, input: &JSONValue, emit: &mut Emit) -> Result<(), Halt> {
+        emit(input.clone())?;
+        if let Some(json_array) = input.as_array() {
+            for item in json_array {
+                self.recurse(item, emit)?;
+            }
+        } else if let Some(json_object) = input.as_object() {
+            for item in json_object.values() {
+                self.recurse(item, emit)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn interpolate(
+        &self,
+        segments: &[Segment],
+        prefix: String,
+        input: &JSONValue,
+        variables: &Variables,
+        emit: &mut Emit,
+    ) -> Result<(), Halt> {
+        match segments.split_first() {
+            None => emit(JSONValue::from(prefix)),
+            Some((Segment::Literal(literal), rest)) => {
+                self.interpolate(rest, prefix + literal, input, variables, emit)
+            }
+            Some((Segment::Expression(inner), rest)) => {
+                self.eval(inner, input, variables, &mut |value| {
+                    let text = match value.as_str() {
+                        Some(string) => string.to_string(),
+                        None => to_json(&value),
+                    };
+                    self.interpolate(rest, format!("{}{}", prefix, text), input, variables, emit)
+                })
+            }
+        }
+    }
+
+    fn construct_object(
+        &self,
+        entries: &[(Expression, Expression)],
+        json_object: JSONObject,
+        input: &JSONValue,
+        variables: &Variables,
+        emit: &mut Emit,
+    ) -> Result<(), Halt> {
+        let Some(((key, value), rest)) = entries.split_first() else {
+            return emit(JSONValue::from(json_object));
+        };
+
+        self.eval(key, input, variables, &mut |key_value| {
+            let Some(key_string) = key_value.as_str() else {
+                return Err(fail(
+                    key,
+                    format!(
+                        "Object keys must be strings, got {} ({})",
+                        type_name(&key_value),
+                        to_json(&key_value)
+                    ),
+                ));
+            };
+            self.eval(value, input, variables, &mut |value| {
+                let mut json_object = json_object.clone();
+                json_object.set(key_string.to_string(), value);
+                self.construct_object(rest, json_object, input, variables, emit)
+            })
+        })
+    }
+
+    fn call(
+        &self,
+        at: &Expression,
+        name: &str,
+        arguments: &[Expression],
+        input: &JSONValue,
+        variables: &Variables,
+        emit: &mut Emit,
+    ) -> Result<(), Halt> {
+        let error = |message: String| Err(fail(at, message));
+        let describe = || format!("{} ({})", type_name(input), to_json(input));
+
+        match (name, arguments) {
+            ("empty", []) => Ok(()),
+            ("not", []) => emit(JSONValue::from(!truthy(input))),
+            ("length", []) => match input.get_ref() {
+                JSONRef::String(json_string) => {
+                    emit(JSONValue::from(json_string.as_str().chars().count()))
+                }
+                JSONRef::Array(json_array) => emit(JSONValue::from(json_array.len())),
+                JSONRef::Object(json_object) => emit(JSONValue::from(json_object.len())),
+                JSONRef::Null(_) => emit(JSONValue::from(0)),
+                JSONRef::Number(_) => {
+                    emit(JSONValue::from(input.as_f64().unwrap_or_default().abs()))
+                }
+                JSONRef::Boolean(_) => error(format!("{} has no length", describe())),
+            },
+            ("keys" | "keys_unsorted", []) => {
+                if let Some(json_object) = input.as_object() {
+                    let mut keys = json_object.keys().map(JSONValue::from).collect::<Vec<_>>();
+                    if name == "keys" {
+                        keys.sort();
+                    }
+                    emit(JSONValue::from(keys))
+                } else if let Some(json_array) = input.as_array() {
+                    emit(JSONValue::from((0..json_array.len()).collect::<Vec<_>>()))
+                } else {
+                    error(format!("{} has no keys", describe()))
+                }
+            }
+            ("to_entries", []) => match input.as_object() {
+                Some(json_object) => emit(to_entries(json_object)),
+                None => error(format!("{} has no entries", describe())),
+            },
+            ("from_entries", []) => emit(from_entries(at, input)?),
+            ("add", []) => {
+                let mut sum = JSONValue::from(());
+                for item in iterate(at, input)? {
+                    sum = arithmetic(at, &sum, Operator::Add, item)?;
+                }
+                emit(sum)
+            }
+            ("type", []) => emit(JSONValue::from(match input.get_ref() {
+                JSONRef::String(_) => "string",
+                JSONRef::Number(_) => "number",
+                JSONRef::Boolean(_) => "boolean",
+                JSONRef::Null(_) => "null",
+                JSONRef::Array(_) => "array",
+                JSONRef::Object(_) => "object",
+            })),
+            ("sort" | "unique" | "min" | "max" | "flatten", []) => {
+                let Some(json_array) = input.as_array() else {
+                    return error(format!("{} is not a JSON Array", describe()));
+                };
+                let mut items = json_array.to_vec();
+                match name {
+                    "sort" => {
+                        items.sort();
+                        emit(JSONValue::from(items))
+                    }
+                    "unique" => {
+                        items.sort();
+                        items.dedup();
+                        emit(JSONValue::from(items))
+                    }
+                    "min" => emit(
+                        items
+                            .into_iter()
+                            .min()
+                            .unwrap_or_else(|| JSONValue::from(())),
+                    ),
+                    "max" => emit(
+                        items
+                            .into_iter()
+                            .max()
+                            .unwrap_or_else(|| JSONValue::from(())),
+                    ),
+                    _ => {
+                        let mut flattened = vec![];
+                        flatten(&items, &mut flattened);
+                        emit(JSONValue::from(flattened))
+                    }
+                }
+            }
+            ("reverse", []) => {
+                if let Some(string) = input.as_str() {
+                    emit(JSONValue::from(string.chars().rev().collect::<String>()))
+                } else if input.is_null() {
+                    emit(JSONValue::from(JSONArray::new()))
+                } else if let Some(json_array) = input.as_array() {
+                    emit(JSONValue::from(
+                        json_array.iter().rev().cloned().collect::<JSONArray>(),
+                    ))
+                } else {
+                    error(format!("{} cannot be reversed", describe()))
+                }
+            }
+            ("floor" | "sqrt", []) => match input.as_f64() {
+                Some(number) if name == "floor" => emit(JSONValue::from(number.floor())),
+                Some(number) => emit(JSONValue::from(number.sqrt())),
+                None => error(format!("{} is not a number", describe())),
+            },
+            ("tostring", []) => match input.as_str() {
+                Some(_) => emit(input.clone()),
+                None => emit(JSONValue::from(to_json(input))),
+            },
+            ("tojson", []) => emit(JSONValue::from(to_json(input))),
+            ("tonumber", []) => match (input.as_f64(), input.as_str()) {
+                (Some(_), _) => emit(input.clone()),
+                (_, Some(string)) => match string.parse::<JSONValue>() {
+                    Ok(number) if number.is_number() => emit(number),
+                    _ => error(format!("Cannot parse {} as a number", describe())),
+                },
+                _ => error(format!("{} cannot be converted to a number", describe())),
+            },
+            ("fromjson", []) => match input.as_str().map(str::parse::<JSONValue>) {
+                Some(Ok(value)) => emit(value),
+                Some(Err(parse_error)) => error(format!(
+                    "{} cannot be parsed as JSON: {}",
+                    describe(),
+                    parse_error.get_message()
+                )),
+                None => error(format!("{} cannot be parsed as JSON", describe())),
+            },
+            ("ascii_downcase" | "ascii_upcase", []) => match input.as_str() {
+                Some(string) if name == "ascii_downcase" => {
+                    emit(JSONValue::from(string.to_ascii_lowercase()))
+                }
+                Some(string) => emit(JSONValue::from(string.to_ascii_uppercase())),
+                None => error(format!("{} cannot be case converted", describe())),
+            },
+            ("first", []) => emit(index_value(at, input, &JSONValue::from(0))?),
+            ("last", []) => emit(index_value(at, input, &JSONValue::from(-1))?),
+            ("any" | "all", []) => {
+                let items = iterate(at, input)?;
+                emit(JSONValue::from(if name == "any" {
+                    items.into_iter().any(truthy)
+                } else {
+                    items.into_iter().all(truthy)
+                }))
+            }
+            ("recurse", []) => self.recurse(input, emit),
+            (
+                "values" | "nulls" | "booleans" | "numbers" | "strings" | "arrays" | "objects"
+                | "iterables" | "scalars",
+                [],
+            ) => {
+                let iterable = input.is_array() || input.is_object();
+                let selected = match name {
+                    "values" => !input.is_null(),
+                    "nulls" => input.is_null(),
+                    "booleans" => input.is_boolean(),
+                    "numbers" => input.is_number(),
+                    "strings" => input.is_string(),
+                    "arrays" => input.is_array(),
+                    "objects" => input.is_object(),
+                    "iterables" => iterable,
+                    _ => !iterable,
+                };
+                if selected {
+                    emit(input.clone())
+                } else {
+                    Ok(())
+                }
+            }
+            ("error", []) => Err(Halt::Error(input.clone(), at.line, at.column)),
+            ("error", [message]) => self.eval(message, input, variables, &mut |message| {
+                Err(Halt::Error(message, at.line, at.column))
+            }),
+            ("map", [f]) => {
+                let mut outputs = JSONArray::new();
+                for item in iterate(at, input)? {
+                    self.eval(f, item, variables, &mut |output| {
+                        outputs.push(output);
+                        Ok(())
+                    })?;
+                }
+                emit(JSONValue::from(outputs))
+            }
+            ("map_values", [f]) => {
+                let mut output = input.clone();
+                if let Some(json_array) = output.as_array_mut() {
+                    let mut items = JSONArray::new();
+                    for item in json_array.iter() {
+                        if let Some(first) = self.collect(f, item, variables)?.into_iter().next() {
+                            items.push(first);
+                        }
+                    }
+                    *json_array = items;
+                } else if let Some(json_object) = output.as_object_mut() {
+                    let mut entries = JSONObject::new();
+                    for (key, item) in json_object.iter() {
+                        if let Some(first) = self.collect(f, item, variables)?.into_iter().next() {
+                            entries.set(key.clone(), first);
+                        }
+                    }
+                    *json_object = entries;
+                } else {
+                    return error(format!("Cannot iterate over {}", describe()));
+                }
+                emit(output)
+            }
+            ("select", [condition]) => self.eval(condition, input, variables, &mut |condition| {
+                if truthy(&condition) {
+                    emit(input.clone())
+                } else {
+                    Ok(())
+                }
+            }),
+            ("with_entries", [f]) => {
+                let Some(json_object) = input.as_object() else {
+                    return error(format!("{} has no entries", describe()));
+                };
+                let mut outputs = JSONArray::new();
+                for entry in to_entries(json_object).as_array().into_iter().flatten() {
+                    self.eval(f, entry, variables, &mut |output| {
+                        outputs.push(output);
+                        Ok(())
+                    })?;
+                }
+                emit(from_entries(at, &JSONValue::from(outputs))?)
+            }
+            ("sort_by" | "group_by" | "unique_by" | "min_by" | "max_by", [f]) => {
+                let Some(json_array) = input.as_array() else {
+                    return error(format!("{} is not a JSON Array", describe()));
+                };
+                let mut keyed = vec![];
+                for item in json_array {
+                    keyed.push((
+                        JSONValue::from(self.collect(f, item, variables)?),
+                        item.clone(),
+                    ));
+                }
+                keyed.sort_by(|(left, _), (right, _)| left.cmp(right));
+
+                match name {
+                    "sort_by" => emit(JSONValue::from(
+                        keyed.into_iter().map(|(_, item)| item).collect::<Vec<_>>(),
+                    )),
+                    "min_by" => emit(
+                        keyed
+                            .into_iter()
+                            .next()
+                            .map(|(_, item)| item)
+                            .unwrap_or_else(|| JSONValue::from(())),
+                    ),
+                    "max_by" => emit(
+                        keyed
+                            .into_iter()
+                            .last()
+                            .map(|(_, item)| item)
+                            .unwrap_or_else(|| JSONValue::from(())),
+                    ),
+                    _ => {
+                        let mut groups: Vec<(JSONValue, Vec<JSONValue>)> = vec![];
+                        for (key, item) in keyed {
+                            match groups.last_mut() {
+                                Some((last, group)) if *last == key => group.push(item),
+                                _ => groups.push((key, vec![item])),
+                            }
+                        }
+                        emit(JSONValue::from(if name == "group_by" {
+                            groups
+                                .into_iter()
+                                .map(|(_, group)| JSONValue::from(group))
+                                .collect::<Vec<_>>()
+                        } else {
+                            groups
+                                .into_iter()
+                                .filter_map(|(_, group)| group.into_iter().next())
+                                .collect()
+                        }))
+                    }
+                }
+            }
+            ("has", [key]) => self.eval(key, input, variables, &mut |key| {
+                let has = match (input.get_ref(), key.get_ref()) {
+                    (JSONRef::Object(json_object), JSONRef::String(json_string)) => {
+                        json_object.contains_key(json_string.as_str())
+                    }
+                    (JSONRef::Array(json_array), JSONRef::Number(_)) => {
+                        let index = key.as_f64().unwrap_or_default();
+                        index >= 0.0 && index < json_array.len() as f64
+                    }
+                    _ => {
+                        return error(format!(
+                            "Cannot check whether {} has a key {}",
+                            describe(),
+                            to_json(&key)
+                        ))
+                    }
+                };
+                emit(JSONValue::from(has))
+            }),
+            ("first", [f]) => self.limit(1.0, f, input, variables, emit),
+            ("last", [f]) => match self.collect(f, input, variables)?.pop() {
+                Some(last) => emit(last),
+                None => Ok(()),
+            },
+            ("isempty", [f]) => {
+                let mut empty = true;
+                self.limit(1.0, f, input, variables, &mut |_| {
+                    empty = false;
+                    Ok(())
+                })?;
+                emit(JSONValue::from(empty))
+            }
+            ("limit", [count, f]) => {
+                self.eval(count, input, variables, &mut |count| match count.as_f64() {
+                    Some(count) => self.limit(count, f, input, variables, emit),
+                    None => error(format!("Invalid limit {}", to_json(&count))),
+                })
+            }
+            ("any" | "all", [condition]) => {
+                let mut result = name == "all";
+                for item in iterate(at, input)? {
+                    let mut found = false;
+                    self.eval(condition, item, variables, &mut |condition| {
+                        found |= truthy(&condition) == (name == "any");
+                        Ok(())
+                    })?;
+                    if found {
+                        result = name == "any";
+                        break;
+                    }
+                }
+                emit(JSONValue::from(result))
+            }
+            ("range", [end]) => self.eval(end, input, variables, &mut |end| {
+                self.range(at, &JSONValue::from(0), &end, emit)
+            }),
+            ("range", [start, end]) => self.eval(start, input, variables, &mut |start| {
+                self.eval(end, input, variables, &mut |end| {
+                    self.range(at, &start, &end, emit)
+                })
+            }),
+            ("join", [separator]) => self.eval(separator, input, variables, &mut |separator| {
+                let Some(separator) = separator.as_str() else {
+                    return error(format!("Cannot join with {}", to_json(&separator)));
+                };
+                let mut parts = vec![];
+                for item in iterate(at, input)? {
+                    parts.push(match item.get_ref() {
+                        JSONRef::String(json_string) => json_string.as_str().to_string(),
+                        JSONRef::Null(_) => String::new(),
+                        JSONRef::Number(_) | JSONRef::Boolean(_) => to_json(item),
+                        _ => {
+                            return error(format!(
+                                "Cannot join {} ({})",
+                                type_name(item),
+                                to_json(item)
+                            ))
+                        }
+                    });
+                }
+                emit(JSONValue::from(parts.join(separator)))
+            }),
+            ("split", [separator]) => {
+                self.eval(separator, input, variables, &mut |separator| match (
+                    input.as_str(),
+                    separator.as_str(),
+                ) {
+                    (Some(string), Some(separator)) => emit(split(string, separator)),
+                    _ => error(format!("{} cannot be split", describe())),
+                })
+            }
+            ("startswith" | "endswith" | "ltrimstr" | "rtrimstr", [affix]) => self.eval(
+                affix,
+                input,
+                variables,
+                &mut |affix| match (name, input.as_str(), affix.as_str()) {
+                    ("startswith", Some(string), Some(affix)) => {
+                        emit(JSONValue::from(string.starts_with(affix)))
+                    }
+                    ("endswith", Some(string), Some(affix)) => {
+                        emit(JSONValue::from(string.ends_with(affix)))
+                    }
+                    ("ltrimstr", Some(string), Some(affix)) => emit(
+                        string
+                            .strip_prefix(affix)
+                            .map_or_else(|| input.clone(), JSONValue::from),
+                    ),
+                    ("rtrimstr", Some(string), Some(affix)) => emit(
+                        string
+                            .strip_suffix(affix)
+                            .map_or_else(|| input.clone(), JSONValue::from),
+                    ),
+                    ("ltrimstr" | "rtrimstr", _, _) => emit(input.clone()),
+                    _ => error(format!("{}() requires JSON String inputs", name)),
+                },
+            ),
+            _ => error(format!("Unknown function <{}/{}>", name, arguments.len())),
+        }
+    }
+
+    /// Emit at most `count` outputs of `f`, then stop evaluating it
+    fn limit(
+        &self,
+        count: f64,
+        f: &Expression,
+        input: &JSONValue,
+        variables: &Variables,
+        emit: &mut Emit,
+    ) -> Result<(), Halt> {
+        if count <= 0.0 {
+            return Ok(());
+        }
+
+        let label = self.labels.get();
+        self.labels.set(label + 1);
+        let mut taken = 0.0;
+        let result = self.eval(f, input, variables, &mut |value| {
+            emit(value)?;
+            taken += 1.0;
+            if taken >= count {
+                Err(Halt::Break(label))
+            } else {
+                Ok(())
+            }
+        });
+
+        match result {
+            Err(Halt::Break(broken)) if broken == label => Ok(()),
+            result => result,
+        }
+    }
+
+    fn range(
+        &self,
+        at: &Expression,
+        start: &JSONValue,
+        end: &JSONValue,
+        emit: &mut Emit,
+    ) -> Result<(), Halt> {
+        let (Some(mut number), Some(end)) = (start.as_f64(), end.as_f64()) else {
+            return Err(fail(at, "Range bounds must be numeric".to_string()));
+        };
+        while number < end {
+            emit(JSONValue::from(number))?;
+            number += 1.0;
+        }
+        Ok(())
+    }
+}
+
+fn iterate<'a>(at: &Expression, value: &'a JSONValue) -> Result<Vec<&'a JSONValue>, Halt> {
+    if let Some(json_array) = value.as_array() {
+        Ok(json_array.iter().collect())
+    } else if let Some(json_object) = value.as_object() {
+        Ok(json_object.values().collect())
+    } else {
+        Err(fail(
+            at,
+            format!(
+                "Cannot iterate over {} ({})",
+                type_name(value),
+                to_json(value)
+            ),
+        ))
+    }
+}
+
+fn index_value(at: &Expression, target: &JSONValue, index: &JSONValue) -> Result<JSONValue, Halt> {
+    let null = JSONValue::from(());
+    match (target.get_ref(), index.get_ref()) {
+        (JSONRef::Null(_), JSONRef::String(_) | JSONRef::Number(_)) => Ok(null),
+        (JSONRef::Object(json_object), JSONRef::String(key)) => {
+            Ok(json_object.get(key.as_str()).cloned().unwrap_or(null))
+        }
+        (JSONRef::Array(json_array), JSONRef::Number(_)) => {
+            let index = index.as_f64().unwrap_or_default().floor();
+            let index = if index < 0.0 {
+                index + json_array.len() as f64
+            } else {
+                index
+            };
+            if index < 0.0 {
+                return Ok(null);
+            }
+            Ok(json_array.get(index as usize).cloned().unwrap_or(null))
+        }
+        _ => Err(fail(
+            at,
+            format!(
+                "Cannot index into a {} with <{}>",
+                type_name(target),
+                index
+                    .as_str()
+                    .map_or_else(|| to_json(index), str::to_string)
+            ),
+        )),
+    }
+}
+
+fn slice_value(
+    at: &Expression,
+    target: &JSONValue,
+    start: &JSONValue,
+    end: &JSONValue,
+) -> Result<JSONValue, Halt> {
+    let length = if let Some(string) = target.as_str() {
+        string.chars().count()
+    } else if let Some(json_array) = target.as_array() {
+        json_array.len()
+    } else if target.is_null() {
+        return Ok(JSONValue::from(()));
+    } else {
+        return Err(fail(
+            at,
+            format!("Cannot slice a {} ({})", type_name(target), to_json(target)),
+        ));
+    };
+
+    let bound = |bound: &JSONValue, default: usize| -> Result<usize, Halt> {
+        if bound.is_null() {
+            return Ok(default);
+        }
+        let Some(bound) = bound.as_f64() else {
+            return Err(fail(
+                at,
+                format!("Slice bounds must be numbers, got {}", to_json(bound)),
+            ));
+        };
+        let bound = if bound < 0.0 {
+            bound + length as f64
+        } else {
+            bound
+        };
+        Ok(bound.floor().clamp(0.0, length as f64) as usize)
+    };
+    let start = bound(start, 0)?;
+    let end = bound(end, length)?.max(start);
+
+    if let Some(string) = target.as_str() {
+        Ok(JSONValue::from(
+            string
+                .chars()
+                .skip(start)
+                .take(end - start)
+                .collect::<String>(),
+        ))
+    } else {
+        let json_array = target
+            .as_array()
+            .map(JSONArray::as_slice)
+            .unwrap_or_default();
+        Ok(JSONValue::from(&json_array[start..end]))
+    }
+}
+
+fn arithmetic(
+    at: &Expression,
+    left: &JSONValue,
+    operator: Operator,
+    right: &JSONValue,
+) -> Result<JSONValue, Halt> {
+    let numbers = left.as_f64().zip(right.as_f64());
+    let result = match (operator, left.get_ref(), right.get_ref()) {
+        (Operator::Add, JSONRef::Null(_), _) => Some(right.clone()),
+        (Operator::Add, _, JSONRef::Null(_)) => Some(left.clone()),
+        (Operator::Add, JSONRef::String(left), JSONRef::String(right)) => Some(JSONValue::from(
+            format!("{}{}", left.as_str(), right.as_str()),
+        )),
+        (Operator::Add, JSONRef::Array(left), JSONRef::Array(right)) => Some(JSONValue::from(
+            left.iter()
+                .chain(right.iter())
+                .cloned()
+                .collect::<JSONArray>(),
+        )),
+        (Operator::Add, JSONRef::Object(left), JSONRef::Object(right)) => {
+            let mut merged = left.clone();
+            merged.extend(
+                right
+                    .iter()
+                    .map(|(key, value)| (key.clone(), value.clone())),
+            );
+            Some(JSONValue::from(merged))
+        }
+        (Operator::Subtract, JSONRef::Array(left), JSONRef::Array(right)) => Some(JSONValue::from(
+            left.iter()
+                .filter(|item| !right.iter().any(|removed| removed == *item))
+                .cloned()
+                .collect::<JSONArray>(),
+        )),
+        (Operator::Multiply, JSONRef::Object(_), JSONRef::Object(_)) => {
+            Some(deep_merge(left, right))
+        }
+        (Operator::Multiply, JSONRef::String(string), JSONRef::Number(_))
+        | (Operator::Multiply, JSONRef::Number(_), JSONRef::String(string)) => {
+            let count = left.as_f64().or(right.as_f64()).unwrap_or_default();
+            return repeat(string.as_str(), count).ok_or_else(|| {
+                fail(
+                    at,
+                    format!(
+                        "{} ({}) and {} ({}) cannot be multiplied because the result is too long",
+                        type_name(left),
+                        to_json(left),
+                        type_name(right),
+                        to_json(right)
+                    ),
+                )
+            });
+        }
+        (Operator::Divide, JSONRef::String(left), JSONRef::String(right)) => {
+            Some(split(left.as_str(), right.as_str()))
+        }
+        (Operator::Divide | Operator::Modulo, _, _)
+            if numbers.map(|(_, right)| {
+                right.trunc() == 0.0 && (right == 0.0 || matches!(operator, Operator::Modulo))
+            }) == Some(true) =>
+        {
+            return Err(fail(
+                at,
+                format!(
+                    "{} ({}) and {} ({}) cannot be divided because the divisor is zero",
+                    type_name(left),
+                    to_json(left),
+                    type_name(right),
+                    to_json(right)
+                ),
+            ));
+        }
+        _ => numbers.map(|(left, right)| {
+            JSONValue::from(match operator {
+                Operator::Add => left + right,
+                Operator::Subtract => left - right,
+                Operator::Multiply => left * right,
+                Operator::Divide => left / right,
+                Operator::Modulo => (left.trunc() as i64).wrapping_rem(right.trunc() as i64) as f64,
+            })
+        }),
+    };
+
+    result.ok_or_else(|| {
+        let verb = match operator {
+            Operator::Add => "added",
+            Operator::Subtract => "subtracted",
+            Operator::Multiply => "multiplied",
+            Operator::Divide => "divided",
+            Operator::Modulo => "divided",
+        };
+        fail(
+            at,
+            format!(
+                "{} ({}) and {} ({}) cannot be {}",
+                type_name(left),
+                to_json(left),
+                type_name(right),
+                to_json(right),
+                verb
+            ),
+        )
+    })
+}
+
+/// Merge two JSON Objects, merging the values of keys in both if they are JSON Objects too
+fn deep_merge(left: &JSONValue, right: &JSONValue) -> JSONValue {
+    match (left.as_object(), right.as_object()) {
+        (Some(left), Some(right)) => {
+            let mut merged = left.clone();
+            for (key, value) in right {
+                let value = match merged.get(key) {
+                    Some(existing) => deep_merge(existing, value),
+                    None => value.clone(),
+                };
+                merged.set(key.clone(), value);
+            }
+            JSONValue::from(merged)
+        }
+        _ => right.clone(),
+    }
+}
+
+fn split(string: &str, separator: &str) -> JSONValue {
+    if string.is_empty() {
+        JSONValue::from(JSONArray::new())
+    } else if separator.is_empty() {
+        JSONValue::from(string.chars().map(String::from).collect::<Vec<_>>())
+    } else {
+        JSONValue::from(string.split(separator).collect::<Vec<_>>())
+    }
+}
+
+fn flatten(items: &[JSONValue], flattened: &mut Vec<JSONValue>) {
+    for item in items {
+        match item.as_array() {
+            Some(json_array) => flatten(json_array.as_slice(), flattened),
+            None => flattened.push(item.clone()),
+        }
+    }
+}
+
+fn to_entries(json_object: &JSONObject) -> JSONValue {
+    JSONValue::from(
+        json_object
+            .iter()
+            .map(|(key, value)| {
+                let mut entry = JSONObject::new();
+                entry.set("key".to_string(), JSONValue::from(key));
+                entry.set("value".to_string(), value.clone());
+                JSONValue::from(entry)
+            })
+            .collect::<Vec<_>>(),
+    )
+}
+
+/// Build a JSON Object from entries with a `key`, `k`, `name` or `Name`, and a `value` or `v`
+fn from_entries(at: &Expression, entries: &JSONValue) -> Result<JSONValue, Halt> {
+    let mut json_object = JSONObject::new();
+    for entry in iterate(at, entries)? {
+        let Some(fields) = entry.as_object() else {
+            return Err(fail(
+                at,
+                format!("Entries must be JSON Objects, got {}", to_json(entry)),
+            ));
+        };
+        let field = |names: &[&str]| names.iter().find_map(|name| fields.get(name));
+
+        let key = match field(&["key", "k", "name", "Name", "K", "Key"]) {
+            Some(key) if key.is_array() || key.is_object() => {
+                return Err(fail(
+                    at,
+                    format!("Entry keys must be strings, got {}", to_json(key)),
+                ))
+            }
+            Some(key) => key.as_str().map_or_else(|| to_json(key), str::to_string),
+            None => "null".to_string(),
+        };
+        let value = field(&["value", "v", "Value", "V"])
+            .cloned()
+            .unwrap_or_else(|| JSONValue::from(()));
+        json_object.set(key, value);
+    }
+    Ok(JSONValue::from(json_object))
+}
+
+/// Longest string that multiplying a string by a number may build, in bytes
+const MAX_REPEAT_LENGTH: usize = 256 << 20;
+
+/// Repeat a string the way jq multiplies a string by a number,
+/// giving `null` for a count that is not positive and `None` if the result
+/// would be longer than [`MAX_REPEAT_LENGTH`]
+fn repeat(string: &str, count: f64) -> Option<JSONValue> {
+    if count.is_nan() || count <= 0.0 {
+        return Some(JSONValue::from(()));
+    }
+    let times = ((count - 1.0) as usize).saturating_add(1);
+    string
+        .len()
+        .checked_mul(times)
+        .filter(|&length| length <= MAX_REPEAT_LENGTH)
+        .map(|_| JSONValue::from(string.repeat(times)))
+}
+
+/// Replace the numbers jq cannot output: NaN with `null`,
+/// and infinities with the largest finite number of the same sign
+fn make_finite(value: &mut JSONValue) {
+    if let Some(json_array) = value.as_array_mut() {
+        json_array.iter_mut().for_each(make_finite);
+    } else if let Some(json_object) = value.as_object_mut() {
+        json_object.values_mut().for_each(make_finite);
+    } else if let Some(number) = value.as_f64().filter(|number| !number.is_finite()) {
+        *value = finite(number);
+    }
+}
+
+fn finite(number: f64) -> JSONValue {
+    if number.is_nan() {
+        JSONValue::from(())
+    } else {
+        JSONValue::from(number.clamp(f64::MIN, f64::MAX))
+    }
+}
+
+/// Writes JSON without any whitespace, the way jq's `tojson` does
+struct MinifiedFormatter;
+
+impl Formatter for MinifiedFormatter {
+    fn write_number<W: ?Sized + fmt::Write>(&mut self, writer: &mut W, value: f64) -> fmt::Result {
+        match finite(value).as_f64() {
+            Some(value) => write!(writer, "{}", value),
+            None => writer.write_str("null"),
+        }
+    }
+
+    fn begin_array<W: ?Sized + fmt::Write>(&mut self, writer: &mut W) -> fmt::Result {
+        writer.write_str("[")
+    }
+
+    fn end_array<W: ?Sized + fmt::Write>(&mut self, writer: &mut W) -> fmt::Result {
+        writer.write_str("]")
+    }
+
+    fn begin_array_value<W: ?Sized + fmt::Write>(
+        &mut self,
+        writer: &mut W,
+        first: bool,
+    ) -> fmt::Result {
+        if first {
+            Ok(())
+        } else {
+            writer.write_str(",")
+        }
+    }
+
+    fn begin_object<W: ?Sized + fmt::Write>(&mut self, writer: &mut W) -> fmt::Result {
+        writer.write_str("{")
+    }
+
+    fn end_object<W: ?Sized + fmt::Write>(&mut self, writer: &mut W) -> fmt::Result {
+        writer.write_str("}")
+    }
+
+    fn begin_object_key<W: ?Sized + fmt::Write>(
+        &mut self,
+        writer: &mut W,
+        first: bool,
+    ) -> fmt::Result {
+        if first {
+            Ok(())
+        } else {
+            writer.write_str(",")
+        }
+    }
+
+    fn begin_object_value<W: ?Sized + fmt::Write>(&mut self, writer: &mut W) -> fmt::Result {
+        writer.write_str(":")
+    }
+}
+
+fn to_json(value: &JSONValue) -> String {
+    value.to_string_with_formatter(&mut MinifiedFormatter)
+}
@@ -0,0 +1,365 @@
+use crate::{json_diff::longest_common_subsequence, JSONObject, JSONPointer, JSONValue};
+
+type Resolver = Box<dyn Fn(&JSONConflict) -> JSONResolution>;
+
+/// Merges the changes two sides made to the same base JSON Value, like a three-way merge in git
+///
+/// A key or item changed on only one side takes that change, and a change made the same way
+/// on both sides is taken once. JSON Objects are merged key by key and JSON Arrays are merged
+/// by matching items against the base, so items added in different places are all kept.
+/// Anything else changed differently on both sides is a [`JSONConflict`],
+/// which is given to each resolver in turn until one resolves it.
+/// An unresolved conflict keeps our side in the merged JSON Value.
+///
+/// # Example
+///
+/// ```
+/// use parson::{json, JSONMerger, JSONResolution};
+///
+/// let base = json!({ "name": "api", "replicas": 2, "env": ["A=1"], "limits": { "cpu": 1 }, "debug": false });
+/// let ours = json!({ "name": "api", "replicas": 3, "env": ["A=1", "C=3"], "limits": { "cpu": 2 }, "debug": false });
+/// let theirs = json!({ "name": "api-v2", "replicas": 2, "env": ["Z=0", "A=1"], "limits": { "cpu": 4 } });
+///
+/// let merger = JSONMerger::new().resolver(|conflict| {
+///     if conflict.pointer().starts_with(&"/limits".parse().unwrap()) {
+///         JSONResolution::Theirs
+///     } else {
+///         JSONResolution::Unresolved
+///     }
+/// });
+/// let merge = merger.merge(&base, &ours, &theirs);
+/// assert!(merge.is_clean());
+/// assert_eq!(
+///     merge.value(),
+///     &json!({ "name": "api-v2", "replicas": 3, "env": ["Z=0", "A=1", "C=3"], "limits": { "cpu": 4 } })
+/// );
+/// ```
+#[derive(Default)]
+pub struct JSONMerger {
+    resolvers: Vec<Resolver>,
+}
+
+/// The result of a three-way merge
+#[derive(Debug, Clone, PartialEq)]
+pub struct JSONMerge {
+    value: JSONValue,
+    conflicts: Vec<JSONConflict>,
+}
+
+/// A value both sides of a three-way merge changed in different ways.
+/// A side is `None` where the value is not there, such as when that side removed it
+#[derive(Debug, Clone, PartialEq)]
+pub struct JSONConflict {
+    pointer: JSONPointer,
+    base: Option<JSONValue>,
+    ours: Option<JSONValue>,
+    theirs: Option<JSONValue>,
+}
+
+/// How a resolver settles a [`JSONConflict`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum JSONResolution {
+    /// Take our side
+    Ours,
+    /// Take their side
+    Theirs,
+    /// Keep the base value
+    Base,
+    /// Use a different value
+    Value(JSONValue),
+    /// Leave the value out
+    Remove,
+    /// Leave the conflict to the next resolver
+    Unresolved,
+}
+
+impl JSONMerger {
+    /// Create a new JSON Merger with no resolvers, so every conflict is reported
+    pub fn new() -> Self {
+        JSONMerger::default()
+    }
+
+    /// Add a resolver, which is tried after the ones added before it
+    pub fn resolver<F: Fn(&JSONConflict) -> JSONResolution + 'static>(
+        mut self,
+        resolver: F,
+    ) -> Self {
+        self.resolvers.push(Box::new(resolver));
+        self
+    }
+
+    /// Merge the changes `ours` and `theirs` made to `base`
+    pub fn merge(&self, base: &JSONValue, ours: &JSONValue, theirs: &JSONValue) -> JSONMerge {
+        let mut conflicts = Vec::new();
+        let value = self
+            .merge_values(
+                Some(base),
+                Some(ours),
+                Some(theirs),
+                &JSONPointer::root(),
+                &mut conflicts,
+            )
+            .unwrap_or_else(|| JSONValue::from(()));
+        JSONMerge { value, conflicts }
+    }
+
+    /// Merge one value, giving `None` if it should be left out
+    fn merge_values(
+        &self,
+        base: Option<&JSONValue>,
+        ours: Option<&JSONValue>,
+        theirs: Option<&JSONValue>,
+        pointer: &JSONPointer,
+        conflicts: &mut Vec<JSONConflict>,
+    ) -> Option<JSONValue> {
+        if ours == theirs || base == theirs {
+            return ours.cloned();
+        }
+        if base == ours {
+            return theirs.cloned();
+        }
+
+        if let (Some(ours), Some(theirs)) = (ours, theirs) {
+            // A JSON Object both sides added is merged as if the base was empty
+            let empty = JSONObject::new();
+            let base_object = match base {
+                Some(base) => base.as_object(),
+                None => Some(&empty),
+            };
+            if let (Some(base), Some(ours), Some(theirs)) =
+                (base_object, ours.as_object(), theirs.as_object())
+            {
+                return Some(self.merge_objects(base, ours, theirs, pointer, conflicts));
+            }
+
+            let base_array = base.and_then(JSONValue::as_array);
+            if let (Some(base), Some(ours), Some(theirs)) =
+                (base_array, ours.as_array(), theirs.as_array())
+            {
+                if let Some(merged) = self.merge_arrays(
+                    base.as_slice(),
+                    ours.as_slice(),
+                    theirs.as_slice(),
+                    pointer,
+                    conflicts,
+                ) {
+                    return Some(merged);
+                }
+            }
+        }
+
+        self.resolve(
+            JSONConflict {
+                pointer: pointer.clone(),
+                base: base.cloned(),
+                ours: ours.cloned(),
+                theirs: theirs.cloned(),
+            },
+            conflicts,
+        )
+    }
+
+    fn merge_objects(
+        &self,
+        base: &JSONObject,
+        ours: &JSONObject,
+        theirs: &JSONObject,
+        pointer: &JSONPointer,
+        conflicts: &mut Vec<JSONConflict>,
+    ) -> JSONValue {
+        let keys = ours
+            .keys()
+            .chain(theirs.keys().filter(|key| !ours.contains_key(key)));
+
+        let mut merged = JSONObject::new();
+        for key in keys {
+            let value = self.merge_values(
+                base.get(key),
+                ours.get(key),
+                theirs.get(key),
+                &pointer.join(key.as_str()),
+                conflicts,
+            );
+            if let Some(value) = value {
+                merged.set(key.clone(), value);
+            }
+        }
+        JSONValue::from(merged)
+    }
+
+    /// Merge JSON Arrays by splitting them into runs where all three sides match the base,
+    /// and runs between where they do not. Gives `None` if a run changed on both sides
+    /// can not be merged, so the whole JSON Array is a conflict
+    fn merge_arrays(
+        &self,
+        base: &[JSONValue],
+        ours: &[JSONValue],
+        theirs: &[JSONValue],
+        pointer: &JSONPointer,
+        conflicts: &mut Vec<JSONConflict>,
+    ) -> Option<JSONValue> {
+        let in_ours = matched_indexes(base, ours);
+        let in_theirs = matched_indexes(base, theirs);
+
+        let mut merged = Vec::new();
+        let mut item_conflicts = Vec::new();
+        let (mut base_start, mut ours_start, mut theirs_start) = (0, 0, 0);
+        loop {
+            // Copy the items which are unchanged on both sides
+            let mut stable = 0;
+            while base_start + stable < base.len()
+                && in_ours[base_start + stable] == Some(ours_start + stable)
+                && in_theirs[base_start + stable] == Some(theirs_start + stable)
+            {
+                stable += 1;
+            }
+            if stable > 0 {
+                merged.extend_from_slice(&base[base_start..base_start + stable]);
+                base_start += stable;
+                ours_start += stable;
+                theirs_start += stable;
+                continue;
+            }
+
+            // Merge the changes up to the next base item both sides kept
+            let next = (base_start..base.len())
+                .find(|&index| in_ours[index].is_some() && in_theirs[index].is_some());
+            let (base_end, ours_end, theirs_end) = match next {
+                Some(index) => (index, in_ours[index]?, in_theirs[index]?),
+                None => (base.len(), ours.len(), theirs.len()),
+            };
+            let base_run = &base[base_start..base_end];
+            let ours_run = &ours[ours_start..ours_end];
+            let theirs_run = &theirs[theirs_start..theirs_end];
+            if ours_run == base_run {
+                merged.extend_from_slice(theirs_run);
+            } else if theirs_run == base_run || ours_run == theirs_run {
+                merged.extend_from_slice(ours_run);
+            } else if ours_run.len() == base_run.len() && theirs_run.len() == base_run.len() {
+                for ((base, ours), theirs) in base_run.iter().zip(ours_run).zip(theirs_run) {
+                    let item_pointer = pointer.join(merged.len().to_string());
+                    let item = self.merge_values(
+                        Some(base),
+                        Some(ours),
+                        Some(theirs),
+                        &item_pointer,
+                        &mut item_conflicts,
+                    );
+                    merged.extend(item);
+                }
+            } else {
+                return None;
+            }
+
+            if next.is_none() {
+                break;
+            }
+            base_start = base_end;
+            ours_start = ours_end;
+            theirs_start = theirs_end;
+        }
+
+        conflicts.extend(item_conflicts);
+        Some(JSONValue::from(merged))
+    }
+
+    fn resolve(
+        &self,
+        conflict: JSONConflict,
+        conflicts: &mut Vec<JSONConflict>,
+    ) -> Option<JSONValue> {
+        for resolver in &self.resolvers {
+            match resolver(&conflict) {
+                JSONResolution::Ours => return conflict.ours,
+                JSONResolution::Theirs => return conflict.theirs,
+                JSONResolution::Base => return conflict.base,
+                JSONResolution::Value(value) => return Some(value),
+                JSONResolution::Remove => return None,
+                JSONResolution::Unresolved => {}
+            }
+        }
+        let value = conflict.ours.clone();
+        conflicts.push(conflict);
+        value
+    }
+}
+
+impl JSONMerge {
+    /// Get the merged JSON Value
+    pub fn value(&self) -> &JSONValue {
+        &self.value
+    }
+
+    /// Get the merged JSON Value, consuming the merge
+    pub fn into_value(self) -> JSONValue {
+        self.value
+    }
+
+    /// Get the conflicts no resolver settled, in the order they were found
+    pub fn conflicts(&self) -> &[JSONConflict] {
+        &self.conflicts
+    }
+
+    /// If the merge has no unresolved conflicts
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+}
+
+impl JSONConflict {
+    /// Get the JSON Pointer to the conflicting value in the merged JSON Value
+    pub fn pointer(&self) -> &JSONPointer {
+        &self.pointer
+    }
+
+    /// Get the value in the base
+    pub fn base(&self) -> Option<&JSONValue> {
+        self.base.as_ref()
+    }
+
+    /// Get the value on our side
+    pub fn ours(&self) -> Option<&JSONValue> {
+        self.ours.as_ref()
+    }
+
+    /// Get the value on their side
+    pub fn theirs(&self) -> Option<&JSONValue> {
+        self.theirs.as_ref()
+    }
+}
+
+/// For each item of `base`, the index of the item it is matched with in `other`
+fn matched_indexes(base: &[JSONValue], other: &[JSONValue]) -> Vec<Option<usize>> {
+    let mut indexes = vec![None; base.len()];
+    for (base_index, other_index) in
+        longest_common_subsequence(base.len(), other.len(), |i, j| base[i] == other[j])
+    {
+        indexes[base_index] = Some(other_index);
+    }
+    indexes
+}
+
+/// Merge the changes `ours` and `theirs` made to `base`, reporting every conflict
+///
+/// # Example
+///
+/// ```
+/// use parson::{json, merge3};
+///
+/// let base = json!({ "host": "db", "port": 5432, "pool": 10 });
+/// let ours = json!({ "host": "db.internal", "port": 5432, "pool": 20 });
+/// let theirs = json!({ "host": "db", "port": 6432 });
+///
+/// let merge = merge3(&base, &ours, &theirs);
+/// assert_eq!(merge.value(), &json!({ "host": "db.internal", "port": 6432, "pool": 20 }));
+///
+/// let conflict = &merge.conflicts()[0];
+/// assert_eq!(merge.conflicts().len(), 1);
+/// assert_eq!(conflict.pointer().to_string(), "/pool");
+/// assert_eq!(conflict.base(), Some(&json!(10)));
+/// assert_eq!(conflict.ours(), Some(&json!(20)));
+/// assert_eq!(conflict.theirs(), None);
+/// ```
+pub fn merge3(base: &JSONValue, ours: &JSONValue, theirs: &JSONValue) -> JSONMerge {
+    JSONMerger::new().merge(base, ours, theirs)
+}
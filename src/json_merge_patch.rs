@@ -0,0 +1,143 @@
+use crate::{JSONError, JSONObject, JSONValue};
+
+/// Apply a JSON Merge Patch as defined by RFC 7396 to a JSON Value.
+///
+/// A JSON Object in the patch is merged into the value key by key, recursively,
+/// and a `null` in it removes the key. Anything else in the patch replaces the value
+///
+/// # Example
+///
+/// ```
+/// use parson::{json, merge_patch};
+///
+/// let mut json_value = json!({ "title": "Goodbye!", "author": { "givenName": "John", "familyName": "Doe" }, "tags": ["example", "sample"] });
+/// merge_patch(&mut json_value, &json!({ "title": "Hello!", "author": { "familyName": null }, "tags": ["example"], "phoneNumber": "+01-123-456-7890" }));
+/// assert_eq!(
+///     json_value,
+///     json!({ "title": "Hello!", "author": { "givenName": "John" }, "tags": ["example"], "phoneNumber": "+01-123-456-7890" })
+/// );
+/// ```
+///
+/// The examples from appendix A of RFC 7396 all give the results the RFC expects,
+/// and [`create_merge_patch`] finds a patch that gives the same result
+///
+/// ```
+/// use parson::{create_merge_patch, merge_patch, JSONValue};
+///
+/// let tests = r#"[
+///     [{ "a": "b" }, { "a": "c" }, { "a": "c" }],
+///     [{ "a": "b" }, { "b": "c" }, { "a": "b", "b": "c" }],
+///     [{ "a": "b" }, { "a": null }, {}],
+///     [{ "a": "b", "b": "c" }, { "a": null }, { "b": "c" }],
+///     [{ "a": ["b"] }, { "a": "c" }, { "a": "c" }],
+///     [{ "a": "c" }, { "a": ["b"] }, { "a": ["b"] }],
+///     [{ "a": { "b": "c" } }, { "a": { "b": "d", "c": null } }, { "a": { "b": "d" } }],
+///     [{ "a": [{ "b": "c" }] }, { "a": [1] }, { "a": [1] }],
+///     [["a", "b"], ["c", "d"], ["c", "d"]],
+///     [{ "a": "b" }, ["c"], ["c"]],
+///     [{ "a": "foo" }, null, null],
+///     [{ "a": "foo" }, "bar", "bar"],
+///     [{ "e": null }, { "a": 1 }, { "e": null, "a": 1 }],
+///     [[1, 2], { "a": "b", "c": null }, { "a": "b" }],
+///     [{}, { "a": { "bb": { "ccc": null } } }, { "a": { "bb": {} } }]
+/// ]"#
+/// .parse::<JSONValue>()
+/// .unwrap();
+///
+/// for test in tests.as_array().unwrap() {
+///     let (original, patch, result) = (&test[0], &test[1], &test[2]);
+///     let mut json_value = original.clone();
+///     merge_patch(&mut json_value, patch);
+///     assert_eq!(&json_value, result);
+///
+///     let mut json_value = original.clone();
+///     merge_patch(&mut json_value, &create_merge_patch(original, result).unwrap());
+///     assert_eq!(&json_value, result);
+/// }
+/// ```
+pub fn merge_patch(value: &mut JSONValue, patch: &JSONValue) {
+    let Some(patch_object) = patch.as_object() else {
+        *value = patch.clone();
+        return;
+    };
+    if !value.is_object() {
+        *value = JSONValue::from(JSONObject::new());
+    }
+    let Some(json_object) = value.as_object_mut() else {
+        unreachable!("the value was just made a JSON Object");
+    };
+    for (key, patch_value) in patch_object.iter() {
+        if patch_value.is_null() {
+            json_object.remove(key);
+        } else {
+            merge_patch(
+                json_object
+                    .entry(key.clone())
+                    .or_insert(JSONValue::from(())),
+                patch_value,
+            );
+        }
+    }
+}
+
+/// Create the smallest JSON Merge Patch which turns `original` into `target`.
+///
+/// Only keys which changed are in the patch, and a JSON Object which is in both is diffed
+/// recursively. A merge patch cannot set a key to `null`, as `null` removes the key,
+/// so a `null` added to a JSON Object in `target` is an error
+///
+/// # Example
+///
+/// ```
+/// use parson::{create_merge_patch, json, merge_patch};
+///
+/// let original = json!({ "name": "api", "limits": { "cpu": 1, "memory": "1Gi" }, "debug": true });
+/// let target = json!({ "name": "api", "limits": { "cpu": 2, "memory": "1Gi" }, "region": "eu" });
+///
+/// let patch = create_merge_patch(&original, &target).unwrap();
+/// assert_eq!(patch, json!({ "limits": { "cpu": 2 }, "debug": null, "region": "eu" }));
+///
+/// let mut json_value = original.clone();
+/// merge_patch(&mut json_value, &patch);
+/// assert_eq!(json_value, target);
+///
+/// let error = create_merge_patch(&original, &json!({ "name": null })).unwrap_err();
+/// assert_eq!(
+///     error.get_message(),
+///     "A JSON Merge Patch cannot set a key to <null> (at /name)"
+/// );
+/// ```
+pub fn create_merge_patch(
+    original: &JSONValue,
+    target: &JSONValue,
+) -> Result<JSONValue, JSONError> {
+    let Some(target_object) = target.as_object() else {
+        return Ok(target.clone());
+    };
+    let empty = JSONObject::new();
+    let original_object = original.as_object().unwrap_or(&empty);
+
+    let mut patch = JSONObject::new();
+    for key in original_object.keys() {
+        if !target_object.contains_key(key) {
+            patch.set(key.clone(), JSONValue::from(()));
+        }
+    }
+    for (key, target_value) in target_object.iter() {
+        let original_value = original_object.get(key);
+        if original_value == Some(target_value) {
+            continue;
+        }
+        if target_value.is_null() {
+            return Err(
+                JSONError::with_path("A JSON Merge Patch cannot set a key to <null>", "")
+                    .prefix_path(key),
+            );
+        }
+        let patch_value =
+            create_merge_patch(original_value.unwrap_or(&JSONValue::from(())), target_value)
+                .map_err(|error| error.prefix_path(key))?;
+        patch.set(key.clone(), patch_value);
+    }
+    Ok(JSONValue::from(patch))
+}
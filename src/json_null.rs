@@ -1,11 +1,22 @@
-use std::fmt::Display;
+use std::fmt::{self, Display};
 
-#[derive(Debug, Clone)]
+use crate::{serializer::Serializer, CompactFormatter, Formatter};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct JSONNull;
 
+/// Convert JSON Null to a Rust owned string
+///
+/// # Example
+///
+/// ```
+/// use parson::JSONNull;
+///
+/// assert_eq!(JSONNull::new().to_string(), "null");
+/// ```
 impl Display for JSONNull {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.to_string())
+        self.serialize(&mut Serializer::new(f, &mut CompactFormatter))
     }
 }
 
@@ -15,16 +26,10 @@ impl JSONNull {
         JSONNull {}
     }
 
-    /// Convert JSON Null to a Rust owned string
-    ///
-    /// # Example
-    ///
-    /// ```
-    /// use parson::JSONNull;
-    ///
-    /// assert_eq!(JSONNull::new().to_string(), "null");
-    /// ```
-    pub fn to_string(&self) -> String {
-        "null".to_string()
+    pub(crate) fn serialize<W: fmt::Write, F: Formatter>(
+        &self,
+        serializer: &mut Serializer<W, F>,
+    ) -> fmt::Result {
+        serializer.formatter.write_null(serializer.writer)
     }
 }
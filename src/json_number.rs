@@ -1,13 +1,93 @@
-use std::fmt::Display;
+use std::{
+    cmp::Ordering,
+    fmt::{self, Display},
+    hash::{Hash, Hasher},
+};
+
+use crate::{serializer::Serializer, CompactFormatter, Formatter};
 
 #[derive(Debug, Clone)]
 pub struct JSONNumber {
     data: f64,
 }
 
+/// Convert JSON Number to a Rust owned string
+///
+/// # Example
+///
+/// ```
+/// use parson::JSONNumber;
+///
+/// assert_eq!(JSONNumber::new(0.0).to_string(), "0");
+/// assert_eq!(JSONNumber::new(1.0).to_string(), "1");
+/// assert_eq!(JSONNumber::new(-1.0).to_string(), "-1");
+/// assert_eq!(JSONNumber::new(-1e1).to_string(), "-10");
+/// assert_eq!(JSONNumber::new(-1e-1).to_string(), "-0.1");
+/// ```
 impl Display for JSONNumber {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.to_string())
+        self.serialize(&mut Serializer::new(f, &mut CompactFormatter))
+    }
+}
+
+/// JSON Numbers are compared numerically, so `-0` equals `0`.
+/// Every NaN equals every other NaN, which lets JSON Numbers be `Eq` and `Hash`.
+impl PartialEq for JSONNumber {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for JSONNumber {}
+
+impl Hash for JSONNumber {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let data = if self.data == 0.0 {
+            0.0
+        } else if self.data.is_nan() {
+            f64::NAN
+        } else {
+            self.data
+        };
+        data.to_bits().hash(state);
+    }
+}
+
+impl PartialOrd for JSONNumber {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// JSON Numbers are ordered numerically, with NaN after every other number
+///
+/// # Example
+///
+/// ```
+/// use parson::JSONNumber;
+///
+/// let mut numbers = [f64::NAN, 2.0, -0.0, f64::INFINITY, -1.5, 0.0]
+///     .map(JSONNumber::new)
+///     .to_vec();
+/// numbers.sort();
+/// assert_eq!(
+///     numbers.iter().map(JSONNumber::to_string).collect::<Vec<_>>(),
+///     ["-1.5", "-0", "0", "2", "inf", "NaN"]
+/// );
+/// assert_eq!(JSONNumber::new(-0.0), JSONNumber::new(0.0));
+/// assert_eq!(JSONNumber::new(f64::NAN), JSONNumber::new(f64::NAN));
+/// ```
+impl Ord for JSONNumber {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.data.is_nan(), other.data.is_nan()) {
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Greater,
+            (false, true) => Ordering::Less,
+            (false, false) => self
+                .data
+                .partial_cmp(&other.data)
+                .expect("numbers that are not NaN are ordered"),
+        }
     }
 }
 
@@ -17,21 +97,13 @@ impl JSONNumber {
         JSONNumber { data }
     }
 
-    /// Convert JSON Number to a Rust owned string
-    ///
-    /// # Example
-    ///
-    /// ```
-    /// use parson::JSONNumber;
-    ///
-    /// assert_eq!(JSONNumber::new(0.0).to_string(), "0");
-    /// assert_eq!(JSONNumber::new(1.0).to_string(), "1");
-    /// assert_eq!(JSONNumber::new(-1.0).to_string(), "-1");
-    /// assert_eq!(JSONNumber::new(-1e1).to_string(), "-10");
-    /// assert_eq!(JSONNumber::new(-1e-1).to_string(), "-0.1");
-    /// ```
-    pub fn to_string(&self) -> String {
-        self.data.to_string()
+    pub(crate) fn serialize<W: fmt::Write, F: Formatter>(
+        &self,
+        serializer: &mut Serializer<W, F>,
+    ) -> fmt::Result {
+        serializer
+            .formatter
+            .write_number(serializer.writer, self.data)
     }
 
     /// Get a Rust f64 from the JSON Number
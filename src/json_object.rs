@@ -1,75 +1,102 @@
-use std::{collections::HashMap, fmt::Display};
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    fmt::{self, Display},
+    hash::{Hash, Hasher},
+};
 
-use indexmap::{indexmap, IndexMap};
+#[cfg(feature = "preserve_order")]
+use indexmap::map;
+#[cfg(all(not(feature = "preserve_order"), not(feature = "hash_map")))]
+use std::collections::btree_map as map;
+#[cfg(all(not(feature = "preserve_order"), feature = "hash_map"))]
+use std::collections::hash_map as map;
 
-use crate::JSONValue;
+use crate::{
+    serializer::Serializer, shared::Shared, CompactFormatter, Formatter, JSONValue, PrettyFormatter,
+};
 
-#[derive(Debug, Clone)]
+/// The map backing a JSON Object, chosen by the `preserve_order` and `hash_map` features
+#[cfg(feature = "preserve_order")]
+type Map = indexmap::IndexMap<String, JSONValue>;
+#[cfg(all(not(feature = "preserve_order"), feature = "hash_map"))]
+type Map = HashMap<String, JSONValue>;
+#[cfg(all(not(feature = "preserve_order"), not(feature = "hash_map")))]
+type Map = std::collections::BTreeMap<String, JSONValue>;
+
+#[derive(Debug, Clone, Default)]
 pub struct JSONObject {
-    data: IndexMap<String, JSONValue>,
+    data: Shared<Map>,
 }
 
+/// Convert JSON Object to a Rust owned string
+///
+/// # Example
+///
+/// ```
+/// use parson::{JSONNumber, JSONObject, JSONString, JSONValue};
+///
+/// let mut json_object = JSONObject::new();
+/// assert_eq!(json_object.to_string(), "{  }");
+/// json_object.set("key".to_string(), JSONValue::from_string(JSONString::new("value".to_string())));
+/// assert_eq!(json_object.to_string(), "{ \"key\": \"value\" }");
+/// json_object.set("number".to_string(), JSONValue::from_number(JSONNumber::new(1.0)));
+/// #[cfg(feature = "preserve_order")]
+/// assert_eq!(json_object.to_string(), "{ \"key\": \"value\", \"number\": 1 }");
+/// ```
 impl Display for JSONObject {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.to_string())
+        self.serialize(&mut Serializer::new(f, &mut CompactFormatter))
     }
 }
 
 impl JSONObject {
     /// Create a new empty JSON Object
     pub fn new() -> Self {
-        JSONObject { data: indexmap! {} }
+        JSONObject {
+            data: Shared::new(Map::new()),
+        }
     }
 
-    /// Convert JSON Object to a Rust owned string
+    /// Format JSON Object to a Rust owned string
     ///
     /// # Example
     ///
     /// ```
-    /// use parson::{JSONNumber, JSONObject, JSONString, JSONValue};
+    /// use parson::{JSONNumber, JSONObject, JSONValue};
     ///
     /// let mut json_object = JSONObject::new();
-    /// assert_eq!(json_object.to_string(), "{  }");
-    /// json_object.set("key".to_string(), JSONValue::from_string(JSONString::new("value".to_string())));
-    /// assert_eq!(json_object.to_string(), "{ \"key\": \"value\" }");
-    /// json_object.set("number".to_string(), JSONValue::from_number(JSONNumber::new(1.0)));
-    /// assert_eq!(json_object.to_string(), "{ \"key\": \"value\", \"number\": 1 }");
+    /// assert_eq!(json_object.format_string(0, 2), "{}");
+    /// json_object.set("a".to_string(), JSONValue::from_number(JSONNumber::new(1.0)));
+    /// json_object.set("b".to_string(), JSONValue::from_number(JSONNumber::new(2.0)));
+    /// #[cfg(feature = "preserve_order")]
+    /// assert_eq!(json_object.format_string(0, 2), "{\n  \"a\": 1,\n  \"b\": 2\n}");
     /// ```
-    pub fn to_string(&self) -> String {
-        let mut result = "{ ".to_string();
-
-        for (key, value) in self.data.iter() {
-            result.push_str(&format!("\"{}\": {}, ", key, value.to_string()));
-        }
-
-        if self.data.len() > 0 {
-            result.pop();
-            result.pop();
-        }
-
-        result.push_str(" }");
+    pub fn format_string(&self, indents: i32, spaces: i32) -> String {
+        let mut result = String::new();
+        let mut formatter =
+            PrettyFormatter::with_spaces(spaces as usize).at_level(indents as usize);
+        self.serialize(&mut Serializer::new(&mut result, &mut formatter))
+            .expect("writing to a String cannot fail");
         result
     }
 
-	/// Format JSON Object to a Rust owned string
-    pub fn format_string(&self, indents: i32, spaces: i32) -> String {
-        if self.data.len() == 0 {
-            return String::from("{}");
-        }
-
-        let mut result = "{".to_string();
-
-        for (key, value) in self.data.iter() {
-            result.push_str(&format!(
-                "\n{}\"{}\": {},",
-                " ".repeat(((indents + 1) * spaces) as usize),
-                key,
-                value.format_string(indents + 1, spaces)
-            ));
+    pub(crate) fn serialize<W: fmt::Write, F: Formatter>(
+        &self,
+        serializer: &mut Serializer<W, F>,
+    ) -> fmt::Result {
+        serializer.formatter.begin_object(serializer.writer)?;
+        for (index, (key, value)) in self.data.iter().enumerate() {
+            serializer
+                .formatter
+                .begin_object_key(serializer.writer, index == 0)?;
+            serializer.formatter.write_string(serializer.writer, key)?;
+            serializer.formatter.end_object_key(serializer.writer)?;
+            serializer.formatter.begin_object_value(serializer.writer)?;
+            value.serialize(serializer)?;
+            serializer.formatter.end_object_value(serializer.writer)?;
         }
-
-        result.push_str(&format!("\n{}}}", " ".repeat((indents * spaces) as usize)));
-        result
+        serializer.formatter.end_object(serializer.writer)
     }
 
     /// Convert JSON Object to a Rust HashMap
@@ -85,13 +112,493 @@ impl JSONObject {
         self.data.len()
     }
 
+    /// If the JSON Object has no key-value pairs
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
     /// Get a value by a specific key in the JSON Object
     pub fn get(&self, key: &str) -> Option<&JSONValue> {
         self.data.get(key)
     }
 
+    /// Get a key and its value in the JSON Object, with the key borrowed from the JSON Object
+    pub fn get_key_value(&self, key: &str) -> Option<(&String, &JSONValue)> {
+        self.data.get_key_value(key)
+    }
+
+    /// Get a mutable reference to the value of a specific key in the JSON Object
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut JSONValue> {
+        self.data.get_mut(key)
+    }
+
     /// Add a key value pair to the JSON Object
     pub fn set(&mut self, key: String, value: JSONValue) {
         self.data.insert(key, value);
     }
+
+    /// Remove a key from the JSON Object and return its value,
+    /// keeping the other keys in the order they were added
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use parson::{json, JSONValue};
+    ///
+    /// let mut json_value = r#"{ "a": 1, "b": 2, "c": 3 }"#.parse::<JSONValue>().unwrap();
+    /// let json_object = json_value.as_object_mut().unwrap();
+    /// assert_eq!(json_object.remove("a").unwrap().get_number().unwrap(), 1.0);
+    /// assert!(json_object.remove("a").is_none());
+    /// assert_eq!(json_value, json!({ "b": 2, "c": 3 }));
+    /// #[cfg(feature = "preserve_order")]
+    /// assert_eq!(json_value.to_string(), r#"{ "b": 2, "c": 3 }"#);
+    /// ```
+    pub fn remove(&mut self, key: &str) -> Option<JSONValue> {
+        self.shift_remove(key)
+    }
+
+    /// Remove a key from the JSON Object and return its value,
+    /// moving the last key into its place instead of shifting every key after it.
+    /// Without the `preserve_order` feature, this is the same as [`JSONObject::remove`]
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use parson::{json, JSONValue};
+    ///
+    /// let mut json_value = r#"{ "a": 1, "b": 2, "c": 3 }"#.parse::<JSONValue>().unwrap();
+    /// json_value.as_object_mut().unwrap().swap_remove("a");
+    /// assert_eq!(json_value, json!({ "b": 2, "c": 3 }));
+    /// #[cfg(feature = "preserve_order")]
+    /// assert_eq!(json_value.to_string(), r#"{ "c": 3, "b": 2 }"#);
+    /// ```
+    pub fn swap_remove(&mut self, key: &str) -> Option<JSONValue> {
+        #[cfg(feature = "preserve_order")]
+        return self.data.swap_remove(key);
+
+        #[cfg(not(feature = "preserve_order"))]
+        return self.data.remove(key);
+    }
+
+    /// Remove a key from the JSON Object and return its value,
+    /// shifting every key after it to keep them in the order they were added.
+    /// Without the `preserve_order` feature, this is the same as [`JSONObject::remove`]
+    pub fn shift_remove(&mut self, key: &str) -> Option<JSONValue> {
+        #[cfg(feature = "preserve_order")]
+        return self.data.shift_remove(key);
+
+        #[cfg(not(feature = "preserve_order"))]
+        return self.data.remove(key);
+    }
+
+    /// Keep only the key value pairs for which `keep` returns true
+    pub fn retain<F: FnMut(&String, &mut JSONValue) -> bool>(&mut self, keep: F) {
+        self.data.retain(keep);
+    }
+
+    /// Remove every key value pair from the JSON Object
+    pub fn clear(&mut self) {
+        self.data.clear();
+    }
+
+    /// Sort the key value pairs of the JSON Object by their keys.
+    /// Without the `preserve_order` feature, this does nothing,
+    /// since the keys are either always sorted or never in any order
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use parson::JSONValue;
+    ///
+    /// let mut json_value = r#"{ "b": 1, "c": 2, "a": 3 }"#.parse::<JSONValue>().unwrap();
+    /// json_value.as_object_mut().unwrap().sort_keys();
+    /// #[cfg(feature = "preserve_order")]
+    /// assert_eq!(json_value.to_string(), r#"{ "a": 3, "b": 1, "c": 2 }"#);
+    /// ```
+    pub fn sort_keys(&mut self) {
+        #[cfg(feature = "preserve_order")]
+        self.data.sort_keys();
+    }
+
+    /// Get the entry of a key in the JSON Object for in-place manipulation
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use parson::{JSONArray, JSONNumber, JSONObject, JSONValue};
+    ///
+    /// let mut json_object = JSONObject::new();
+    /// json_object
+    ///     .entry("list".to_string())
+    ///     .or_insert_with(|| JSONValue::from_array(JSONArray::new()))
+    ///     .as_array_mut()
+    ///     .unwrap()
+    ///     .push(JSONValue::from_number(JSONNumber::new(1.0)));
+    /// json_object
+    ///     .entry("list".to_string())
+    ///     .or_insert_with(|| JSONValue::from_array(JSONArray::new()))
+    ///     .as_array_mut()
+    ///     .unwrap()
+    ///     .push(JSONValue::from_number(JSONNumber::new(2.0)));
+    /// assert_eq!(json_object.to_string(), r#"{ "list": [ 1, 2 ] }"#);
+    /// ```
+    pub fn entry(&mut self, key: String) -> Entry<'_> {
+        match self.data.entry(key) {
+            map::Entry::Occupied(entry) => Entry::Occupied(OccupiedEntry { entry }),
+            map::Entry::Vacant(entry) => Entry::Vacant(VacantEntry { entry }),
+        }
+    }
+
+    /// If the JSON Object has a value for a specific key
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.data.contains_key(key)
+    }
+
+    /// Iterate over the key value pairs in the JSON Object
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use parson::JSONValue;
+    ///
+    /// let json_value = r#"{ "a": 1, "b": 2 }"#.parse::<JSONValue>().unwrap();
+    /// let pairs = json_value
+    ///     .as_object()
+    ///     .unwrap()
+    ///     .iter()
+    ///     .map(|(key, value)| format!("{}={}", key, value))
+    ///     .collect::<Vec<_>>();
+    /// #[cfg(feature = "preserve_order")]
+    /// assert_eq!(pairs, ["a=1", "b=2"]);
+    /// assert_eq!(pairs.len(), 2);
+    /// ```
+    pub fn iter(&self) -> JSONObjectIter<'_> {
+        JSONObjectIter {
+            iter: self.data.iter(),
+        }
+    }
+
+    /// Iterate over the key value pairs in the JSON Object, with mutable references to the values
+    pub fn iter_mut(&mut self) -> JSONObjectIterMut<'_> {
+        JSONObjectIterMut {
+            iter: self.data.iter_mut(),
+        }
+    }
+
+    /// Iterate over the keys in the JSON Object
+    pub fn keys(&self) -> JSONObjectKeys<'_> {
+        JSONObjectKeys {
+            iter: self.data.keys(),
+        }
+    }
+
+    /// Iterate over the values in the JSON Object
+    pub fn values(&self) -> JSONObjectValues<'_> {
+        JSONObjectValues {
+            iter: self.data.values(),
+        }
+    }
+
+    /// Iterate over mutable references to the values in the JSON Object
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use parson::{json, JSONNull, JSONValue};
+    ///
+    /// let mut json_value = r#"{ "a": 1, "b": 2 }"#.parse::<JSONValue>().unwrap();
+    /// for value in json_value.as_object_mut().unwrap().values_mut() {
+    ///     *value = JSONValue::from_null(JSONNull::new());
+    /// }
+    /// assert_eq!(json_value, json!({ "a": null, "b": null }));
+    /// ```
+    pub fn values_mut(&mut self) -> JSONObjectValuesMut<'_> {
+        JSONObjectValuesMut {
+            iter: self.data.values_mut(),
+        }
+    }
+}
+
+impl IntoIterator for JSONObject {
+    type Item = (String, JSONValue);
+    type IntoIter = JSONObjectIntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        JSONObjectIntoIter {
+            iter: self.data.into_inner().into_iter(),
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a JSONObject {
+    type Item = (&'a String, &'a JSONValue);
+    type IntoIter = JSONObjectIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut JSONObject {
+    type Item = (&'a String, &'a mut JSONValue);
+    type IntoIter = JSONObjectIterMut<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// Collect key value pairs into a JSON Object, where later values replace earlier ones with the same key
+///
+/// # Example
+///
+/// ```
+/// use parson::{json, JSONBoolean, JSONObject, JSONValue};
+///
+/// let mut json_object = ["a", "b"]
+///     .into_iter()
+///     .map(|key| (key.to_string(), JSONValue::from_boolean(JSONBoolean::new(true))))
+///     .collect::<JSONObject>();
+/// json_object.extend(vec![("a".to_string(), JSONValue::from_boolean(JSONBoolean::new(false)))]);
+/// assert_eq!(JSONValue::from(json_object), json!({ "a": false, "b": true }));
+/// ```
+impl FromIterator<(String, JSONValue)> for JSONObject {
+    fn from_iter<I: IntoIterator<Item = (String, JSONValue)>>(iter: I) -> Self {
+        JSONObject {
+            data: Shared::new(iter.into_iter().collect()),
+        }
+    }
+}
+
+impl Extend<(String, JSONValue)> for JSONObject {
+    fn extend<I: IntoIterator<Item = (String, JSONValue)>>(&mut self, iter: I) {
+        self.data.extend(iter);
+    }
+}
+
+/// JSON Objects are equal when they have the same keys with equal values,
+/// no matter which order the keys were added in
+///
+/// # Example
+///
+/// ```
+/// use parson::JSONValue;
+///
+/// let a = r#"{ "a": 1, "b": [true, null] }"#.parse::<JSONValue>().unwrap();
+/// let b = r#"{ "b": [true, null], "a": 1.0 }"#.parse::<JSONValue>().unwrap();
+/// assert_eq!(a, b);
+/// assert_ne!(a, r#"{ "a": 1 }"#.parse::<JSONValue>().unwrap());
+/// ```
+impl PartialEq for JSONObject {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len()
+            && self
+                .iter()
+                .all(|(key, value)| other.get(key) == Some(value))
+    }
+}
+
+impl Eq for JSONObject {}
+
+impl Hash for JSONObject {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let entries = self.sorted_entries();
+        entries.len().hash(state);
+        for (key, value) in entries {
+            key.hash(state);
+            value.hash(state);
+        }
+    }
+}
+
+impl PartialOrd for JSONObject {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// JSON Objects are ordered by their key-value pairs sorted by key,
+/// compared one pair at a time, key first and then value
+impl Ord for JSONObject {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.sorted_entries().cmp(&other.sorted_entries())
+    }
+}
+
+impl JSONObject {
+    /// The key-value pairs in the order of their keys, which is how comparisons see them
+    fn sorted_entries(&self) -> Vec<(&String, &JSONValue)> {
+        let mut entries = self.iter().collect::<Vec<_>>();
+        entries.sort_by_key(|(key, _)| *key);
+        entries
+    }
+}
+
+/// Implements the iterator traits for a wrapper around an iterator of the underlying map
+macro_rules! delegate_iterator {
+    ($name:ident$(<$lifetime:lifetime>)?, $item:ty) => {
+        impl$(<$lifetime>)? Iterator for $name$(<$lifetime>)? {
+            type Item = $item;
+
+            fn next(&mut self) -> Option<Self::Item> {
+                self.iter.next()
+            }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                self.iter.size_hint()
+            }
+        }
+
+        #[cfg(any(feature = "preserve_order", not(feature = "hash_map")))]
+        impl$(<$lifetime>)? DoubleEndedIterator for $name$(<$lifetime>)? {
+            fn next_back(&mut self) -> Option<Self::Item> {
+                self.iter.next_back()
+            }
+        }
+
+        impl$(<$lifetime>)? ExactSizeIterator for $name$(<$lifetime>)? {}
+    };
+}
+
+/// An iterator over the key value pairs of a JSON Object
+pub struct JSONObjectIter<'a> {
+    iter: map::Iter<'a, String, JSONValue>,
+}
+
+delegate_iterator!(JSONObjectIter<'a>, (&'a String, &'a JSONValue));
+
+/// An iterator over the key value pairs of a JSON Object, with mutable references to the values
+pub struct JSONObjectIterMut<'a> {
+    iter: map::IterMut<'a, String, JSONValue>,
+}
+
+delegate_iterator!(JSONObjectIterMut<'a>, (&'a String, &'a mut JSONValue));
+
+/// An owning iterator over the key value pairs of a JSON Object
+pub struct JSONObjectIntoIter {
+    iter: map::IntoIter<String, JSONValue>,
+}
+
+delegate_iterator!(JSONObjectIntoIter, (String, JSONValue));
+
+/// An iterator over the keys of a JSON Object
+pub struct JSONObjectKeys<'a> {
+    iter: map::Keys<'a, String, JSONValue>,
+}
+
+delegate_iterator!(JSONObjectKeys<'a>, &'a String);
+
+/// An iterator over the values of a JSON Object
+pub struct JSONObjectValues<'a> {
+    iter: map::Values<'a, String, JSONValue>,
+}
+
+delegate_iterator!(JSONObjectValues<'a>, &'a JSONValue);
+
+/// An iterator over mutable references to the values of a JSON Object
+pub struct JSONObjectValuesMut<'a> {
+    iter: map::ValuesMut<'a, String, JSONValue>,
+}
+
+delegate_iterator!(JSONObjectValuesMut<'a>, &'a mut JSONValue);
+
+/// A view into a single key of a JSON Object, which may either be vacant or occupied
+pub enum Entry<'a> {
+    Occupied(OccupiedEntry<'a>),
+    Vacant(VacantEntry<'a>),
+}
+
+impl<'a> Entry<'a> {
+    /// Get the key of the entry
+    pub fn key(&self) -> &String {
+        match self {
+            Entry::Occupied(entry) => entry.key(),
+            Entry::Vacant(entry) => entry.key(),
+        }
+    }
+
+    /// Insert `default` if the entry is vacant, and get a mutable reference to its value
+    pub fn or_insert(self, default: JSONValue) -> &'a mut JSONValue {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default),
+        }
+    }
+
+    /// Insert the result of `default` if the entry is vacant, and get a mutable reference to its value
+    pub fn or_insert_with<F: FnOnce() -> JSONValue>(self, default: F) -> &'a mut JSONValue {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    /// Modify the value in place if the entry is occupied
+    pub fn and_modify<F: FnOnce(&mut JSONValue)>(self, modify: F) -> Self {
+        match self {
+            Entry::Occupied(mut entry) => {
+                modify(entry.get_mut());
+                Entry::Occupied(entry)
+            }
+            Entry::Vacant(entry) => Entry::Vacant(entry),
+        }
+    }
+}
+
+/// A view into a key of a JSON Object that has a value
+pub struct OccupiedEntry<'a> {
+    entry: map::OccupiedEntry<'a, String, JSONValue>,
+}
+
+impl<'a> OccupiedEntry<'a> {
+    /// Get the key of the entry
+    pub fn key(&self) -> &String {
+        self.entry.key()
+    }
+
+    /// Get a reference to the value of the entry
+    pub fn get(&self) -> &JSONValue {
+        self.entry.get()
+    }
+
+    /// Get a mutable reference to the value of the entry
+    pub fn get_mut(&mut self) -> &mut JSONValue {
+        self.entry.get_mut()
+    }
+
+    /// Convert the entry into a mutable reference to its value
+    pub fn into_mut(self) -> &'a mut JSONValue {
+        self.entry.into_mut()
+    }
+
+    /// Replace the value of the entry, returning the old value
+    pub fn insert(&mut self, value: JSONValue) -> JSONValue {
+        self.entry.insert(value)
+    }
+
+    /// Remove the entry from the JSON Object, keeping the other keys in order, and return its value
+    pub fn remove(self) -> JSONValue {
+        #[cfg(feature = "preserve_order")]
+        return self.entry.shift_remove();
+
+        #[cfg(not(feature = "preserve_order"))]
+        return self.entry.remove();
+    }
+}
+
+/// A view into a key of a JSON Object that has no value
+pub struct VacantEntry<'a> {
+    entry: map::VacantEntry<'a, String, JSONValue>,
+}
+
+impl<'a> VacantEntry<'a> {
+    /// Get the key of the entry
+    pub fn key(&self) -> &String {
+        self.entry.key()
+    }
+
+    /// Set the value of the entry, and get a mutable reference to it
+    pub fn insert(self, value: JSONValue) -> &'a mut JSONValue {
+        self.entry.insert(value)
+    }
 }
@@ -0,0 +1,451 @@
+use std::{fmt::Display, str::FromStr};
+
+use crate::{JSONError, JSONObject, JSONPointer, JSONValue};
+
+/// A JSON Patch as defined by RFC 6902, a list of operations that edit a JSON Value
+///
+/// A JSON Patch is applied all or nothing:
+/// if any operation fails, the JSON Value is left as it was before the patch.
+///
+/// # Example
+///
+/// ```
+/// use parson::{json, JSONPatch, JSONValue};
+///
+/// let patch = r#"[
+///     { "op": "test", "path": "/version", "value": 1 },
+///     { "op": "replace", "path": "/version", "value": 2 },
+///     { "op": "add", "path": "/tags/0", "value": "new" },
+///     { "op": "move", "from": "/legacy", "path": "/settings/mode" },
+///     { "op": "copy", "from": "/settings/mode", "path": "/mode" },
+///     { "op": "remove", "path": "/tags/2" }
+/// ]"#
+/// .parse::<JSONPatch>()
+/// .unwrap();
+/// assert_eq!(patch.len(), 6);
+///
+/// let mut json_value = json!({ "version": 1, "tags": ["a", "b"], "legacy": "fast", "settings": {} });
+/// patch.apply(&mut json_value).unwrap();
+/// assert_eq!(
+///     json_value,
+///     json!({ "version": 2, "tags": ["new", "a"], "settings": { "mode": "fast" }, "mode": "fast" })
+/// );
+///
+/// // The version no longer matches, so the whole patch fails and nothing changes
+/// let before = json_value.clone();
+/// let error = patch.apply(&mut json_value).unwrap_err();
+/// assert_eq!(
+///     error.get_message(),
+///     "Operation <0> (test /version) failed: Value <2> is not equal to <1> (at /version)"
+/// );
+/// assert_eq!(json_value, before);
+/// ```
+///
+/// The examples from appendix A of RFC 6902 all give the results the RFC expects
+///
+/// ```
+/// use parson::{apply_patch, JSONValue};
+///
+/// let tests = r#"[
+///     { "doc": { "foo": "bar" },
+///       "patch": [{ "op": "add", "path": "/baz", "value": "qux" }],
+///       "expected": { "baz": "qux", "foo": "bar" } },
+///     { "doc": { "foo": ["bar", "baz"] },
+///       "patch": [{ "op": "add", "path": "/foo/1", "value": "qux" }],
+///       "expected": { "foo": ["bar", "qux", "baz"] } },
+///     { "doc": { "baz": "qux", "foo": "bar" },
+///       "patch": [{ "op": "remove", "path": "/baz" }],
+///       "expected": { "foo": "bar" } },
+///     { "doc": { "foo": ["bar", "qux", "baz"] },
+///       "patch": [{ "op": "remove", "path": "/foo/1" }],
+///       "expected": { "foo": ["bar", "baz"] } },
+///     { "doc": { "baz": "qux", "foo": "bar" },
+///       "patch": [{ "op": "replace", "path": "/baz", "value": "boo" }],
+///       "expected": { "baz": "boo", "foo": "bar" } },
+///     { "doc": { "foo": { "bar": "baz", "waldo": "fred" }, "qux": { "corge": "grault" } },
+///       "patch": [{ "op": "move", "from": "/foo/waldo", "path": "/qux/thud" }],
+///       "expected": { "foo": { "bar": "baz" }, "qux": { "corge": "grault", "thud": "fred" } } },
+///     { "doc": { "foo": ["all", "grass", "cows", "eat"] },
+///       "patch": [{ "op": "move", "from": "/foo/1", "path": "/foo/3" }],
+///       "expected": { "foo": ["all", "cows", "eat", "grass"] } },
+///     { "doc": { "baz": "qux", "foo": ["a", 2, "c"] },
+///       "patch": [{ "op": "test", "path": "/baz", "value": "qux" },
+///                 { "op": "test", "path": "/foo/1", "value": 2 }],
+///       "expected": { "baz": "qux", "foo": ["a", 2, "c"] } },
+///     { "doc": { "baz": "qux" },
+///       "patch": [{ "op": "test", "path": "/baz", "value": "bar" }],
+///       "error": "Operation <0> (test /baz) failed: Value <\"qux\"> is not equal to <\"bar\"> (at /baz)" },
+///     { "doc": { "foo": "bar" },
+///       "patch": [{ "op": "add", "path": "/child", "value": { "grandchild": {} } }],
+///       "expected": { "foo": "bar", "child": { "grandchild": {} } } },
+///     { "doc": { "foo": "bar" },
+///       "patch": [{ "op": "add", "path": "/baz", "value": "qux", "xyz": 123 }],
+///       "expected": { "foo": "bar", "baz": "qux" } },
+///     { "doc": { "foo": "bar" },
+///       "patch": [{ "op": "add", "path": "/baz/bat", "value": "qux" }],
+///       "error": "Operation <0> (add /baz/bat) failed: Key <baz> not found (at /baz)" },
+///     { "doc": { "/": 9, "~1": 10 },
+///       "patch": [{ "op": "test", "path": "/~01", "value": 10 }],
+///       "expected": { "/": 9, "~1": 10 } },
+///     { "doc": { "/": 9, "~1": 10 },
+///       "patch": [{ "op": "test", "path": "/~01", "value": "10" }],
+///       "error": "Operation <0> (test /~01) failed: Value <10> is not equal to <\"10\"> (at /~01)" },
+///     { "doc": { "foo": ["bar"] },
+///       "patch": [{ "op": "add", "path": "/foo/-", "value": ["abc", "def"] }],
+///       "expected": { "foo": ["bar", ["abc", "def"]] } }
+/// ]"#
+/// .parse::<JSONValue>()
+/// .unwrap();
+///
+/// for test in tests.as_array().unwrap() {
+///     let mut doc = test["doc"].clone();
+///     match apply_patch(&mut doc, &test["patch"]) {
+///         Ok(()) => assert_eq!(doc, test["expected"]),
+///         Err(error) => {
+///             assert_eq!(Some(error.get_message().as_str()), test["error"].as_str());
+///             assert_eq!(doc, test["doc"]);
+///         }
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct JSONPatch {
+    operations: Vec<JSONPatchOperation>,
+}
+
+/// One operation of a [`JSONPatch`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum JSONPatchOperation {
+    /// Add a key to a JSON Object, insert an item into a JSON Array, or replace the whole value
+    Add { path: JSONPointer, value: JSONValue },
+    /// Remove an existing value
+    Remove { path: JSONPointer },
+    /// Replace an existing value
+    Replace { path: JSONPointer, value: JSONValue },
+    /// Remove a value and add it somewhere else
+    Move {
+        from: JSONPointer,
+        path: JSONPointer,
+    },
+    /// Add a copy of a value somewhere else
+    Copy {
+        from: JSONPointer,
+        path: JSONPointer,
+    },
+    /// Fail the patch unless a value equals `value`
+    Test { path: JSONPointer, value: JSONValue },
+}
+
+impl Display for JSONPatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", JSONValue::from(self))
+    }
+}
+
+impl FromStr for JSONPatch {
+    type Err = JSONError;
+
+    fn from_str(patch: &str) -> Result<Self, Self::Err> {
+        JSONPatch::try_from(&patch.parse::<JSONValue>()?)
+    }
+}
+
+impl JSONPatch {
+    /// Create an empty JSON Patch
+    pub fn new() -> Self {
+        JSONPatch::default()
+    }
+
+    /// Get the operations of the JSON Patch in order
+    pub fn operations(&self) -> &[JSONPatchOperation] {
+        &self.operations
+    }
+
+    /// Add an operation to the end of the JSON Patch
+    pub fn push(&mut self, operation: JSONPatchOperation) {
+        self.operations.push(operation);
+    }
+
+    /// Get the number of operations in the JSON Patch
+    pub fn len(&self) -> usize {
+        self.operations.len()
+    }
+
+    /// If the JSON Patch has no operations
+    pub fn is_empty(&self) -> bool {
+        self.operations.is_empty()
+    }
+
+    /// Apply every operation to `value` in order.
+    ///
+    /// If an operation fails, `value` is left unchanged,
+    /// and the error says which operation failed and where
+    pub fn apply(&self, value: &mut JSONValue) -> Result<(), JSONError> {
+        let mut patched = value.clone();
+        for (index, operation) in self.operations.iter().enumerate() {
+            operation.apply(&mut patched).map_err(|error| {
+                error.prefix_message(&format!(
+                    "Operation <{}> ({} {}) failed: ",
+                    index,
+                    operation.name(),
+                    operation.path()
+                ))
+            })?;
+        }
+        *value = patched;
+        Ok(())
+    }
+}
+
+impl JSONPatchOperation {
+    /// Get the name of the operation as written in the `op` member, such as `add`
+    pub fn name(&self) -> &'static str {
+        match self {
+            JSONPatchOperation::Add { .. } => "add",
+            JSONPatchOperation::Remove { .. } => "remove",
+            JSONPatchOperation::Replace { .. } => "replace",
+            JSONPatchOperation::Move { .. } => "move",
+            JSONPatchOperation::Copy { .. } => "copy",
+            JSONPatchOperation::Test { .. } => "test",
+        }
+    }
+
+    /// Get the JSON Pointer to the value the operation changes or tests
+    pub fn path(&self) -> &JSONPointer {
+        match self {
+            JSONPatchOperation::Add { path, .. }
+            | JSONPatchOperation::Remove { path }
+            | JSONPatchOperation::Replace { path, .. }
+            | JSONPatchOperation::Move { path, .. }
+            | JSONPatchOperation::Copy { path, .. }
+            | JSONPatchOperation::Test { path, .. } => path,
+        }
+    }
+
+    /// Apply this one operation to `value`.
+    ///
+    /// Unlike [`JSONPatch::apply`], a failed `move` can leave `value` partly changed
+    pub fn apply(&self, value: &mut JSONValue) -> Result<(), JSONError> {
+        match self {
+            JSONPatchOperation::Add {
+                path,
+                value: new_value,
+            } => {
+                path.insert(value, new_value.clone())?;
+            }
+            JSONPatchOperation::Remove { path } => {
+                path.remove(value)?;
+            }
+            JSONPatchOperation::Replace {
+                path,
+                value: new_value,
+            } => {
+                *path.resolve_mut(value)? = new_value.clone();
+            }
+            JSONPatchOperation::Move { from, path } => {
+                if from == path {
+                    from.resolve(value)?;
+                } else if path.starts_with(from) {
+                    return Err(JSONError::with_path(
+                        &format!("Cannot move <{}> into itself", from),
+                        &path.to_string(),
+                    ));
+                } else {
+                    let moved = from.remove(value)?;
+                    path.insert(value, moved)?;
+                }
+            }
+            JSONPatchOperation::Copy { from, path } => {
+                let copied = from.resolve(value)?.clone();
+                path.insert(value, copied)?;
+            }
+            JSONPatchOperation::Test {
+                path,
+                value: expected,
+            } => {
+                let actual = path.resolve(value)?;
+                if actual != expected {
+                    return Err(JSONError::with_path(
+                        &format!("Value <{}> is not equal to <{}>", actual, expected),
+                        &path.to_string(),
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromIterator<JSONPatchOperation> for JSONPatch {
+    fn from_iter<I: IntoIterator<Item = JSONPatchOperation>>(operations: I) -> Self {
+        JSONPatch {
+            operations: operations.into_iter().collect(),
+        }
+    }
+}
+
+impl IntoIterator for JSONPatch {
+    type Item = JSONPatchOperation;
+    type IntoIter = std::vec::IntoIter<JSONPatchOperation>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.operations.into_iter()
+    }
+}
+
+/// Read a JSON Patch document, failing with the path of the first malformed operation.
+/// Members an operation does not use are ignored
+impl TryFrom<&JSONValue> for JSONPatch {
+    type Error = JSONError;
+
+    fn try_from(value: &JSONValue) -> Result<Self, Self::Error> {
+        let Some(json_array) = value.as_array() else {
+            return Err(JSONError::with_path(
+                "A JSON Patch must be a JSON Array",
+                "",
+            ));
+        };
+
+        json_array
+            .iter()
+            .enumerate()
+            .map(|(index, operation)| {
+                read_operation(operation).map_err(|error| error.prefix_path(&index.to_string()))
+            })
+            .collect()
+    }
+}
+
+impl From<&JSONPatch> for JSONValue {
+    fn from(patch: &JSONPatch) -> Self {
+        JSONValue::from(
+            patch
+                .operations
+                .iter()
+                .map(JSONValue::from)
+                .collect::<Vec<_>>(),
+        )
+    }
+}
+
+impl From<JSONPatch> for JSONValue {
+    fn from(patch: JSONPatch) -> Self {
+        JSONValue::from(&patch)
+    }
+}
+
+impl From<&JSONPatchOperation> for JSONValue {
+    fn from(operation: &JSONPatchOperation) -> Self {
+        let mut json_object = JSONObject::new();
+        json_object.set("op".to_string(), JSONValue::from(operation.name()));
+        if let JSONPatchOperation::Move { from, .. } | JSONPatchOperation::Copy { from, .. } =
+            operation
+        {
+            json_object.set("from".to_string(), JSONValue::from(from.to_string()));
+        }
+        json_object.set(
+            "path".to_string(),
+            JSONValue::from(operation.path().to_string()),
+        );
+        if let JSONPatchOperation::Add { value, .. }
+        | JSONPatchOperation::Replace { value, .. }
+        | JSONPatchOperation::Test { value, .. } = operation
+        {
+            json_object.set("value".to_string(), value.clone());
+        }
+        JSONValue::from(json_object)
+    }
+}
+
+fn read_operation(operation: &JSONValue) -> Result<JSONPatchOperation, JSONError> {
+    let Some(json_object) = operation.as_object() else {
+        return Err(JSONError::with_path(
+            "A JSON Patch operation must be a JSON Object",
+            "",
+        ));
+    };
+
+    let member = |name: &str| {
+        json_object
+            .get(name)
+            .ok_or_else(|| JSONError::with_path(&format!("Operation is missing <{}>", name), ""))
+    };
+    let pointer = |name: &str| {
+        let written = <&str>::try_from(member(name)?).map_err(|error| error.prefix_path(name))?;
+        JSONPointer::parse(written).map_err(|_| {
+            JSONError::with_path(
+                &format!("Invalid JSON Pointer <{}>", written),
+                &format!("/{}", name),
+            )
+        })
+    };
+    let value = || member("value").cloned();
+
+    let op = <&str>::try_from(member("op")?).map_err(|error| error.prefix_path("op"))?;
+    Ok(match op {
+        "add" => JSONPatchOperation::Add {
+            path: pointer("path")?,
+            value: value()?,
+        },
+        "remove" => JSONPatchOperation::Remove {
+            path: pointer("path")?,
+        },
+        "replace" => JSONPatchOperation::Replace {
+            path: pointer("path")?,
+            value: value()?,
+        },
+        "move" => JSONPatchOperation::Move {
+            from: pointer("from")?,
+            path: pointer("path")?,
+        },
+        "copy" => JSONPatchOperation::Copy {
+            from: pointer("from")?,
+            path: pointer("path")?,
+        },
+        "test" => JSONPatchOperation::Test {
+            path: pointer("path")?,
+            value: value()?,
+        },
+        _ => {
+            return Err(JSONError::with_path(
+                &format!("Unknown operation <{}>", op),
+                "/op",
+            ))
+        }
+    })
+}
+
+/// Apply a JSON Patch document to a JSON Value, all or nothing
+///
+/// # Example
+///
+/// ```
+/// use parson::{apply_patch, json};
+///
+/// let mut json_value = json!({ "name": "api", "replicas": 1 });
+/// let patch = json!([
+///     { "op": "replace", "path": "/replicas", "value": 3 },
+///     { "op": "add", "path": "/labels", "value": { "tier": "backend" } }
+/// ]);
+/// apply_patch(&mut json_value, &patch).unwrap();
+/// assert_eq!(json_value, json!({ "name": "api", "replicas": 3, "labels": { "tier": "backend" } }));
+///
+/// let error = apply_patch(&mut json_value, &json!([
+///     { "op": "remove", "path": "/labels/tier" },
+///     { "op": "remove", "path": "/labels/tier" }
+/// ]))
+/// .unwrap_err();
+/// assert_eq!(
+///     error.get_message(),
+///     "Operation <1> (remove /labels/tier) failed: Key <tier> not found (at /labels/tier)"
+/// );
+/// assert_eq!(json_value["labels"]["tier"].as_str(), Some("backend"));
+///
+/// let error = apply_patch(&mut json_value, &json!([{ "op": "delete", "path": "/name" }])).unwrap_err();
+/// assert_eq!(error.get_message(), "Unknown operation <delete> (at /0/op)");
+/// let error = apply_patch(&mut json_value, &json!([{ "op": "copy", "path": "/name" }])).unwrap_err();
+/// assert_eq!(error.get_message(), "Operation is missing <from> (at /0)");
+/// ```
+pub fn apply_patch(value: &mut JSONValue, patch: &JSONValue) -> Result<(), JSONError> {
+    JSONPatch::try_from(patch)?.apply(value)
+}
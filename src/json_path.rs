@@ -0,0 +1,1363 @@
+use std::{borrow::Cow, fmt::Display, str::FromStr};
+
+#[cfg(feature = "regex")]
+use regex::Regex;
+
+use crate::{JSONError, JSONPointer, JSONValue};
+
+/// A JSONPath query as defined by RFC 9535, compiled once so it can be run against many JSON Values
+///
+/// Supports name, index, slice and wildcard selectors, descendant segments,
+/// filter expressions with comparisons and logical operators,
+/// and the `length`, `count`, `match`, `search` and `value` functions.
+/// `match` and `search` need the `regex` feature, and fail to parse without it.
+///
+/// # Example
+///
+/// ```
+/// use parson::{JSONPath, JSONValue};
+///
+/// let json_value = r#"{
+///     "orders": [
+///         { "id": "a", "total": 250, "items": ["x", "y"] },
+///         { "id": "b", "total": 80, "items": [] },
+///         { "id": "c", "total": 120, "items": ["z"] }
+///     ]
+/// }"#
+/// .parse::<JSONValue>()
+/// .unwrap();
+///
+/// let path = "$.orders[?@.total > 100].id".parse::<JSONPath>().unwrap();
+/// let nodes = path.query(&json_value);
+/// assert_eq!(
+///     nodes.iter().map(|node| node.value().as_str().unwrap()).collect::<Vec<_>>(),
+///     ["a", "c"]
+/// );
+/// assert_eq!(
+///     nodes.iter().map(|node| node.path()).collect::<Vec<_>>(),
+///     ["$['orders'][0]['id']", "$['orders'][2]['id']"]
+/// );
+/// assert_eq!(nodes[1].pointer().to_string(), "/orders/2/id");
+///
+/// let query = |path: &str| {
+///     JSONPath::parse(path)
+///         .unwrap()
+///         .query(&json_value)
+///         .iter()
+///         .map(|node| node.value().to_string())
+///         .collect::<Vec<_>>()
+/// };
+/// assert_eq!(query("$.orders[-1:].id"), [r#""c""#]);
+/// assert_eq!(query("$.orders[::-2].total"), ["120", "250"]);
+/// assert_eq!(query("$..items[*]"), [r#""x""#, r#""y""#, r#""z""#]);
+/// assert_eq!(query("$.orders[?length(@.items) == 0].id"), [r#""b""#]);
+/// assert_eq!(query("$.orders[?count(@.items[*]) > 1 || @.id == 'c'].id"), [r#""a""#, r#""c""#]);
+/// #[cfg(feature = "regex")]
+/// assert_eq!(query(r#"$.orders[?match(@.id, "[ab]")].total"#), ["250", "80"]);
+/// #[cfg(feature = "regex")]
+/// assert_eq!(query("$.orders[?search(value(@.items[0]), 'z')].id"), [r#""c""#]);
+/// assert_eq!(query("$.orders[?!@.items[0]].id"), [r#""b""#]);
+///
+/// let error = JSONPath::parse("$.orders[?length(@.items)]").unwrap_err();
+/// assert_eq!(
+///     error.get_message(),
+///     "Function <length> returns a value, which must be compared (line 1 column 11)"
+/// );
+///
+/// let nested = format!("$[?{}@{}]", "(".repeat(200), ")".repeat(200));
+/// assert_eq!(
+///     JSONPath::parse(&nested).unwrap_err().get_message(),
+///     "Filter expression nested too deeply (line 1 column 132)"
+/// );
+///
+/// #[cfg(not(feature = "regex"))]
+/// assert_eq!(
+///     JSONPath::parse("$[?match(@, 'a')]").unwrap_err().get_message(),
+///     "Function <match> needs the regex feature (line 1 column 4)"
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct JSONPath {
+    source: String,
+    query: Query,
+}
+
+impl Display for JSONPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+impl FromStr for JSONPath {
+    type Err = JSONError;
+
+    fn from_str(path: &str) -> Result<Self, Self::Err> {
+        JSONPath::parse(path)
+    }
+}
+
+impl JSONPath {
+    /// Compile a JSONPath query, failing with the position of the first syntax or type error
+    pub fn parse(path: &str) -> Result<Self, JSONError> {
+        let mut parser = PathParser {
+            chars: path.chars().collect(),
+            position: 0,
+            depth: 0,
+        };
+        let query = parser.parse_root()?;
+        Ok(JSONPath {
+            source: path.to_string(),
+            query,
+        })
+    }
+
+    /// Run the query against a JSON Value, returning the matching nodes in document order
+    pub fn query<'a>(&self, value: &'a JSONValue) -> Vec<JSONPathNode<'a>> {
+        let root = JSONPathNode {
+            location: vec![],
+            value,
+        };
+        select(&self.query.segments, value, vec![root])
+    }
+}
+
+impl JSONValue {
+    /// Compile and run a JSONPath query against the JSON Value
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use parson::JSONValue;
+    ///
+    /// let json_value = r#"{ "a": [{ "b": 1 }, { "b": 2 }] }"#.parse::<JSONValue>().unwrap();
+    /// let nodes = json_value.select("$.a[*].b").unwrap();
+    /// assert_eq!(nodes.len(), 2);
+    /// assert_eq!(nodes[1].path(), "$['a'][1]['b']");
+    /// ```
+    pub fn select(&self, path: &str) -> Result<Vec<JSONPathNode<'_>>, JSONError> {
+        Ok(JSONPath::parse(path)?.query(self))
+    }
+}
+
+/// A value matched by a JSONPath query, along with where it was found
+#[derive(Debug, Clone, PartialEq)]
+pub struct JSONPathNode<'a> {
+    location: Vec<Location<'a>>,
+    value: &'a JSONValue,
+}
+
+impl<'a> JSONPathNode<'a> {
+    /// Get the matched value
+    pub fn value(&self) -> &'a JSONValue {
+        self.value
+    }
+
+    /// Get the normalized path of the matched value, such as `$['orders'][0]['id']`
+    pub fn path(&self) -> String {
+        let mut path = String::from("$");
+        for location in &self.location {
+            match location {
+                Location::Key(key) => {
+                    path.push_str("['");
+                    for char in key.chars() {
+                        match char {
+                            '\u{8}' => path.push_str("\\b"),
+                            '\u{c}' => path.push_str("\\f"),
+                            '\n' => path.push_str("\\n"),
+                            '\r' => path.push_str("\\r"),
+                            '\t' => path.push_str("\\t"),
+                            '\'' => path.push_str("\\'"),
+                            '\\' => path.push_str("\\\\"),
+                            char if char < ' ' => path.push_str(&format!("\\u{:04x}", char as u32)),
+                            char => path.push(char),
+                        }
+                    }
+                    path.push_str("']");
+                }
+                Location::Index(index) => path.push_str(&format!("[{}]", index)),
+            }
+        }
+        path
+    }
+
+    /// Get a JSON Pointer to the matched value
+    pub fn pointer(&self) -> JSONPointer {
+        JSONPointer::from_tokens(self.location.iter().map(|location| match location {
+            Location::Key(key) => key.to_string(),
+            Location::Index(index) => index.to_string(),
+        }))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Location<'a> {
+    Key(&'a str),
+    Index(usize),
+}
+
+#[derive(Debug, Clone)]
+struct Query {
+    absolute: bool,
+    segments: Vec<Segment>,
+}
+
+impl Query {
+    /// If the query can only ever match one node, so it can be compared
+    fn is_singular(&self) -> bool {
+        self.segments.iter().all(|segment| {
+            !segment.descendant
+                && matches!(
+                    segment.selectors.as_slice(),
+                    [Selector::Name(_)] | [Selector::Index(_)]
+                )
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Segment {
+    descendant: bool,
+    selectors: Vec<Selector>,
+}
+
+#[derive(Debug, Clone)]
+enum Selector {
+    Name(String),
+    Wildcard,
+    Index(i64),
+    Slice(Option<i64>, Option<i64>, Option<i64>),
+    Filter(Box<Expression>),
+}
+
+#[derive(Debug, Clone)]
+enum Expression {
+    Or(Vec<Expression>),
+    And(Vec<Expression>),
+    Not(Box<Expression>),
+    Paren(Box<Expression>),
+    Comparison(Box<Expression>, Comparison, Box<Expression>),
+    Literal(JSONValue),
+    Query(Query),
+    Function(Function, Vec<Expression>),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+}
+
+#[derive(Debug, Clone)]
+enum Function {
+    Length,
+    Count,
+    /// Holds the compiled pattern if it was written as a string literal
+    Match(Option<Regex>),
+    Search(Option<Regex>),
+    Value,
+}
+
+/// The types of RFC 9535 function parameters and results
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Type {
+    Value,
+    Logical,
+    Nodes,
+}
+
+impl Function {
+    fn name(&self) -> &'static str {
+        match self {
+            Function::Length => "length",
+            Function::Count => "count",
+            Function::Match(_) => "match",
+            Function::Search(_) => "search",
+            Function::Value => "value",
+        }
+    }
+
+    fn parameters(&self) -> &'static [Type] {
+        match self {
+            Function::Length => &[Type::Value],
+            Function::Count | Function::Value => &[Type::Nodes],
+            Function::Match(_) | Function::Search(_) => &[Type::Value, Type::Value],
+        }
+    }
+
+    fn result(&self) -> Type {
+        match self {
+            Function::Length | Function::Count | Function::Value => Type::Value,
+            Function::Match(_) | Function::Search(_) => Type::Logical,
+        }
+    }
+}
+
+/// Largest integer an index or slice may use, so every index is exact in an f64
+const MAX_INTEGER: i64 = (1 << 53) - 1;
+
+/// Deepest that filter expressions may nest, through parentheses, function arguments
+/// and the queries inside them, so parsing and evaluating cannot overflow the stack
+const MAX_DEPTH: usize = 128;
+
+struct PathParser {
+    chars: Vec<char>,
+    position: usize,
+    depth: usize,
+}
+
+impl PathParser {
+    fn error<T>(&self, message: &str, position: usize) -> Result<T, JSONError> {
+        Err(JSONError::new(message, 1, position + 1))
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.position + offset).copied()
+    }
+
+    fn describe_next(&self) -> String {
+        match self.peek() {
+            Some(char) => format!("<{}>", char),
+            None => "end of path".to_string(),
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), JSONError> {
+        if self.peek() == Some(expected) {
+            self.position += 1;
+            Ok(())
+        } else {
+            self.error(
+                &format!("Expected <{}>, got {}", expected, self.describe_next()),
+                self.position,
+            )
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t' | '\n' | '\r')) {
+            self.position += 1;
+        }
+    }
+
+    fn parse_root(&mut self) -> Result<Query, JSONError> {
+        if self.peek() != Some('$') {
+            return self.error(
+                &format!("Expected <$>, got {}", self.describe_next()),
+                self.position,
+            );
+        }
+        self.position += 1;
+
+        let segments = self.parse_segments()?;
+        if self.position < self.chars.len() {
+            return self.error(
+                &format!("Unexpected {}", self.describe_next()),
+                self.position,
+            );
+        }
+        Ok(Query {
+            absolute: true,
+            segments,
+        })
+    }
+
+    fn parse_segments(&mut self) -> Result<Vec<Segment>, JSONError> {
+        let mut segments = vec![];
+        loop {
+            // Whitespace only belongs to the query if another segment follows it
+            let start = self.position;
+            self.skip_whitespace();
+            match self.peek() {
+                Some('.') if self.peek_at(1) == Some('.') => {
+                    self.position += 2;
+                    let selectors = match self.peek() {
+                        Some('[') => self.parse_bracketed()?,
+                        Some('*') => {
+                            self.position += 1;
+                            vec![Selector::Wildcard]
+                        }
+                        Some(char) if is_name_first(char) => {
+                            vec![Selector::Name(self.parse_name())]
+                        }
+                        _ => {
+                            return self.error(
+                                &format!(
+                                    "Expected a selector after <..>, got {}",
+                                    self.describe_next()
+                                ),
+                                self.position,
+                            )
+                        }
+                    };
+                    segments.push(Segment {
+                        descendant: true,
+                        selectors,
+                    });
+                }
+                Some('.') => {
+                    self.position += 1;
+                    let selector = match self.peek() {
+                        Some('*') => {
+                            self.position += 1;
+                            Selector::Wildcard
+                        }
+                        Some(char) if is_name_first(char) => Selector::Name(self.parse_name()),
+                        _ => {
+                            return self.error(
+                                &format!(
+                                    "Expected a member name after <.>, got {}",
+                                    self.describe_next()
+                                ),
+                                self.position,
+                            )
+                        }
+                    };
+                    segments.push(Segment {
+                        descendant: false,
+                        selectors: vec![selector],
+                    });
+                }
+                Some('[') => {
+                    let selectors = self.parse_bracketed()?;
+                    segments.push(Segment {
+                        descendant: false,
+                        selectors,
+                    });
+                }
+                _ => {
+                    self.position = start;
+                    return Ok(segments);
+                }
+            }
+        }
+    }
+
+    fn parse_name(&mut self) -> String {
+        let mut name = String::new();
+        while let Some(char) = self.peek() {
+            if !is_name_first(char) && !char.is_ascii_digit() {
+                break;
+            }
+            name.push(char);
+            self.position += 1;
+        }
+        name
+    }
+
+    fn parse_bracketed(&mut self) -> Result<Vec<Selector>, JSONError> {
+        self.expect('[')?;
+        let mut selectors = vec![];
+        loop {
+            self.skip_whitespace();
+            selectors.push(self.parse_selector()?);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => self.position += 1,
+                Some(']') => {
+                    self.position += 1;
+                    return Ok(selectors);
+                }
+                _ => {
+                    return self.error(
+                        &format!("Expected <,> or <]>, got {}", self.describe_next()),
+                        self.position,
+                    )
+                }
+            }
+        }
+    }
+
+    fn parse_selector(&mut self) -> Result<Selector, JSONError> {
+        match self.peek() {
+            Some(quote @ ('\'' | '"')) => Ok(Selector::Name(self.parse_string(quote)?)),
+            Some('*') => {
+                self.position += 1;
+                Ok(Selector::Wildcard)
+            }
+            Some('?') => {
+                self.position += 1;
+                self.skip_whitespace();
+                Ok(Selector::Filter(Box::new(self.parse_or(false)?)))
+            }
+            Some('-' | '0'..='9' | ':') => {
+                let start = self.parse_optional_integer()?;
+                self.skip_whitespace();
+                if self.peek() != Some(':') {
+                    return match start {
+                        Some(index) => Ok(Selector::Index(index)),
+                        None => self.error("Expected an index or a slice", self.position),
+                    };
+                }
+                self.position += 1;
+                self.skip_whitespace();
+                let end = self.parse_optional_integer()?;
+                self.skip_whitespace();
+                let mut step = None;
+                if self.peek() == Some(':') {
+                    self.position += 1;
+                    self.skip_whitespace();
+                    step = self.parse_optional_integer()?;
+                }
+                Ok(Selector::Slice(start, end, step))
+            }
+            _ => self.error(
+                &format!("Expected a selector, got {}", self.describe_next()),
+                self.position,
+            ),
+        }
+    }
+
+    fn parse_optional_integer(&mut self) -> Result<Option<i64>, JSONError> {
+        if !matches!(self.peek(), Some('-' | '0'..='9')) {
+            return Ok(None);
+        }
+
+        let start = self.position;
+        let mut lexeme = String::new();
+        if self.peek() == Some('-') {
+            lexeme.push('-');
+            self.position += 1;
+        }
+        while let Some(char @ '0'..='9') = self.peek() {
+            lexeme.push(char);
+            self.position += 1;
+        }
+
+        let digits = lexeme.trim_start_matches('-');
+        if digits.is_empty() || (digits.starts_with('0') && digits.len() > 1) || lexeme == "-0" {
+            return self.error(&format!("Invalid integer <{}>", lexeme), start);
+        }
+        match lexeme.parse::<i64>() {
+            Ok(integer) if (-MAX_INTEGER..=MAX_INTEGER).contains(&integer) => Ok(Some(integer)),
+            _ => self.error(&format!("Integer <{}> is out of range", lexeme), start),
+        }
+    }
+
+    fn parse_string(&mut self, quote: char) -> Result<String, JSONError> {
+        let start = self.position;
+        self.position += 1;
+        let mut string = String::new();
+        loop {
+            let Some(char) = self.peek() else {
+                return self.error("Unterminated string", start);
+            };
+            self.position += 1;
+            match char {
+                char if char == quote => return Ok(string),
+                '\\' => {
+                    let escape = self.peek();
+                    self.position += 1;
+                    match escape {
+                        Some('b') => string.push('\u{8}'),
+                        Some('f') => string.push('\u{c}'),
+                        Some('n') => string.push('\n'),
+                        Some('r') => string.push('\r'),
+                        Some('t') => string.push('\t'),
+                        Some('/') => string.push('/'),
+                        Some('\\') => string.push('\\'),
+                        Some('u') => string.push(self.parse_unicode_escape()?),
+                        Some(escape) if escape == quote => string.push(quote),
+                        _ => return self.error("Invalid escape sequence", self.position - 2),
+                    }
+                }
+                char if char < ' ' => {
+                    return self.error(
+                        &format!("Unescaped control character <{:?}> in string", char),
+                        self.position - 1,
+                    )
+                }
+                char => string.push(char),
+            }
+        }
+    }
+
+    fn parse_unicode_escape(&mut self) -> Result<char, JSONError> {
+        let start = self.position - 2;
+        let high = self.parse_hex_digits()?;
+        let code = if (0xD800..0xDC00).contains(&high) {
+            if self.peek() != Some('\\') || self.peek_at(1) != Some('u') {
+                return self.error("Unpaired surrogate in unicode escape", start);
+            }
+            self.position += 2;
+            let low = self.parse_hex_digits()?;
+            if !(0xDC00..0xE000).contains(&low) {
+                return self.error("Unpaired surrogate in unicode escape", start);
+            }
+            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+        } else {
+            high
+        };
+        match char::from_u32(code) {
+            Some(char) => Ok(char),
+            None => self.error("Unpaired surrogate in unicode escape", start),
+        }
+    }
+
+    fn parse_hex_digits(&mut self) -> Result<u32, JSONError> {
+        let start = self.position;
+        let mut code = 0;
+        for _ in 0..4 {
+            match self.peek().and_then(|char| char.to_digit(16)) {
+                Some(digit) => code = code * 16 + digit,
+                None => return self.error("Expected 4 hex digits in unicode escape", start),
+            }
+            self.position += 1;
+        }
+        Ok(code)
+    }
+
+    /// Parse `a || b`. In a function `argument`, a lone operand is returned as is
+    /// for the function to check, otherwise it must be a test or a comparison
+    fn parse_or(&mut self, argument: bool) -> Result<Expression, JSONError> {
+        if self.depth == MAX_DEPTH {
+            return self.error("Filter expression nested too deeply", self.position);
+        }
+        self.depth += 1;
+
+        let mut operands = vec![self.parse_and(argument)?];
+        loop {
+            let start = self.position;
+            self.skip_whitespace();
+            if self.peek() == Some('|') && self.peek_at(1) == Some('|') {
+                self.position += 2;
+                self.skip_whitespace();
+                let position = self.position;
+                let operand = self.parse_and(false)?;
+                self.check_testable(&operand, position)?;
+                operands.push(operand);
+            } else {
+                self.position = start;
+                break;
+            }
+        }
+
+        self.depth -= 1;
+        Ok(if operands.len() == 1 {
+            operands.pop().unwrap()
+        } else {
+            Expression::Or(operands)
+        })
+    }
+
+    fn parse_and(&mut self, argument: bool) -> Result<Expression, JSONError> {
+        let first_position = self.position;
+        let mut operands = vec![self.parse_unary(argument)?];
+        loop {
+            let start = self.position;
+            self.skip_whitespace();
+            if self.peek() == Some('&') && self.peek_at(1) == Some('&') {
+                self.position += 2;
+                self.skip_whitespace();
+                let position = self.position;
+                let operand = self.parse_unary(false)?;
+                self.check_testable(&operand, position)?;
+                operands.push(operand);
+            } else {
+                self.position = start;
+                break;
+            }
+        }
+        if operands.len() == 1 {
+            Ok(operands.pop().unwrap())
+        } else {
+            self.check_testable(&operands[0], first_position)?;
+            Ok(Expression::And(operands))
+        }
+    }
+
+    fn parse_unary(&mut self, argument: bool) -> Result<Expression, JSONError> {
+        if self.peek() == Some('!') {
+            self.position += 1;
+            self.skip_whitespace();
+            let position = self.position;
+            let operand = self.parse_primary()?;
+            self.check_testable(&operand, position)?;
+            return Ok(Expression::Not(Box::new(operand)));
+        }
+
+        let left_position = self.position;
+        let left = self.parse_primary()?;
+
+        let start = self.position;
+        self.skip_whitespace();
+        let comparison = match (self.peek(), self.peek_at(1)) {
+            (Some('='), Some('=')) => Some((Comparison::Equal, 2)),
+            (Some('!'), Some('=')) => Some((Comparison::NotEqual, 2)),
+            (Some('<'), Some('=')) => Some((Comparison::LessEqual, 2)),
+            (Some('>'), Some('=')) => Some((Comparison::GreaterEqual, 2)),
+            (Some('<'), _) => Some((Comparison::Less, 1)),
+            (Some('>'), _) => Some((Comparison::Greater, 1)),
+            _ => None,
+        };
+
+        match comparison {
+            Some((comparison, length)) => {
+                self.check_comparable(&left, left_position)?;
+                self.position += length;
+                self.skip_whitespace();
+                let right_position = self.position;
+                let right = self.parse_primary()?;
+                self.check_comparable(&right, right_position)?;
+                Ok(Expression::Comparison(
+                    Box::new(left),
+                    comparison,
+                    Box::new(right),
+                ))
+            }
+            None => {
+                self.position = start;
+                if !argument {
+                    self.check_testable(&left, left_position)?;
+                }
+                Ok(left)
+            }
+        }
+    }
+
+    fn parse_primary(&mut self) -> Result<Expression, JSONError> {
+        let start = self.position;
+        match self.peek() {
+            Some('(') => {
+                self.position += 1;
+                self.skip_whitespace();
+                let inner = self.parse_or(false)?;
+                self.skip_whitespace();
+                self.expect(')')?;
+                Ok(Expression::Paren(Box::new(inner)))
+            }
+            Some(identifier @ ('@' | '$')) => {
+                self.position += 1;
+                Ok(Expression::Query(Query {
+                    absolute: identifier == '$',
+                    segments: self.parse_segments()?,
+                }))
+            }
+            Some(quote @ ('\'' | '"')) => Ok(Expression::Literal(JSONValue::from(
+                self.parse_string(quote)?,
+            ))),
+            Some('-' | '0'..='9') => self.parse_number(),
+            Some('a'..='z') => {
+                let mut name = String::new();
+                while let Some(char @ ('a'..='z' | '0'..='9' | '_')) = self.peek() {
+                    name.push(char);
+                    self.position += 1;
+                }
+                if self.peek() == Some('(') {
+                    return self.parse_function(&name, start);
+                }
+                match name.as_str() {
+                    "true" => Ok(Expression::Literal(JSONValue::from(true))),
+                    "false" => Ok(Expression::Literal(JSONValue::from(false))),
+                    "null" => Ok(Expression::Literal(JSONValue::from(()))),
+                    _ => self.error(&format!("Unexpected <{}>", name), start),
+                }
+            }
+            _ => self.error(
+                &format!("Expected a filter expression, got {}", self.describe_next()),
+                start,
+            ),
+        }
+    }
+
+    fn parse_number(&mut self) -> Result<Expression, JSONError> {
+        let start = self.position;
+        let mut lexeme = String::new();
+        let take_digits = |parser: &mut PathParser, lexeme: &mut String| {
+            let mut count = 0;
+            while let Some(char @ '0'..='9') = parser.peek() {
+                lexeme.push(char);
+                parser.position += 1;
+                count += 1;
+            }
+            count
+        };
+
+        if self.peek() == Some('-') {
+            lexeme.push('-');
+            self.position += 1;
+        }
+        let integer = take_digits(self, &mut lexeme);
+        let digits = lexeme.trim_start_matches('-');
+        let mut valid = integer > 0 && (digits == "0" || !digits.starts_with('0'));
+        if self.peek() == Some('.') {
+            lexeme.push('.');
+            self.position += 1;
+            valid &= take_digits(self, &mut lexeme) > 0;
+        }
+        if matches!(self.peek(), Some('e' | 'E')) {
+            lexeme.push('e');
+            self.position += 1;
+            if let Some(sign @ ('+' | '-')) = self.peek() {
+                lexeme.push(sign);
+                self.position += 1;
+            }
+            valid &= take_digits(self, &mut lexeme) > 0;
+        }
+
+        match lexeme.parse::<f64>() {
+            Ok(number) if valid => Ok(Expression::Literal(JSONValue::from(number))),
+            _ => self.error(&format!("Invalid number <{}>", lexeme), start),
+        }
+    }
+
+    fn parse_function(&mut self, name: &str, start: usize) -> Result<Expression, JSONError> {
+        let function = match name {
+            "length" => Function::Length,
+            "count" => Function::Count,
+            "match" => Function::Match(None),
+            "search" => Function::Search(None),
+            "value" => Function::Value,
+            _ => return self.error(&format!("Unknown function <{}>", name), start),
+        };
+        #[cfg(not(feature = "regex"))]
+        if let Function::Match(_) | Function::Search(_) = function {
+            return self.error(
+                &format!("Function <{}> needs the regex feature", name),
+                start,
+            );
+        }
+
+        self.expect('(')?;
+        self.skip_whitespace();
+        let mut arguments = vec![];
+        if self.peek() != Some(')') {
+            loop {
+                let position = self.position;
+                let argument = self.parse_or(true)?;
+                if let Some(&parameter) = function.parameters().get(arguments.len()) {
+                    self.check_argument(
+                        &function,
+                        arguments.len(),
+                        parameter,
+                        &argument,
+                        position,
+                    )?;
+                }
+                arguments.push(argument);
+                self.skip_whitespace();
+                if self.peek() == Some(',') {
+                    self.position += 1;
+                    self.skip_whitespace();
+                } else {
+                    break;
+                }
+            }
+        }
+        self.expect(')')?;
+
+        if arguments.len() != function.parameters().len() {
+            return self.error(
+                &format!(
+                    "Function <{}> takes {} argument(s), got {}",
+                    name,
+                    function.parameters().len(),
+                    arguments.len()
+                ),
+                start,
+            );
+        }
+
+        let function = match (function, arguments.get(1)) {
+            (Function::Match(_), Some(Expression::Literal(pattern))) => Function::Match(
+                pattern
+                    .as_str()
+                    .and_then(|pattern| compile_pattern(pattern, true)),
+            ),
+            (Function::Search(_), Some(Expression::Literal(pattern))) => Function::Search(
+                pattern
+                    .as_str()
+                    .and_then(|pattern| compile_pattern(pattern, false)),
+            ),
+            (function, _) => function,
+        };
+        Ok(Expression::Function(function, arguments))
+    }
+
+    /// Check that an expression can stand on its own as a test in a filter
+    fn check_testable(&self, expression: &Expression, position: usize) -> Result<(), JSONError> {
+        match expression {
+            Expression::Literal(literal) => {
+                self.error(&format!("Literal <{}> must be compared", literal), position)
+            }
+            Expression::Function(function, _) if function.result() == Type::Value => self.error(
+                &format!(
+                    "Function <{}> returns a value, which must be compared",
+                    function.name()
+                ),
+                position,
+            ),
+            _ => Ok(()),
+        }
+    }
+
+    /// Check that an expression can be on either side of a comparison
+    fn check_comparable(&self, expression: &Expression, position: usize) -> Result<(), JSONError> {
+        match expression {
+            Expression::Literal(_) => Ok(()),
+            Expression::Query(query) if query.is_singular() => Ok(()),
+            Expression::Query(_) => self.error(
+                "Only queries that match at most one node can be compared",
+                position,
+            ),
+            Expression::Function(function, _) if function.result() == Type::Value => Ok(()),
+            Expression::Function(function, _) => self.error(
+                &format!(
+                    "Function <{}> returns a logical result, which cannot be compared",
+                    function.name()
+                ),
+                position,
+            ),
+            _ => self.error("Only values can be compared", position),
+        }
+    }
+
+    fn check_argument(
+        &self,
+        function: &Function,
+        index: usize,
+        parameter: Type,
+        argument: &Expression,
+        position: usize,
+    ) -> Result<(), JSONError> {
+        let valid = match (parameter, argument) {
+            (Type::Value, Expression::Literal(_)) => true,
+            (Type::Value, Expression::Query(query)) => query.is_singular(),
+            (Type::Value, Expression::Function(function, _)) => function.result() == Type::Value,
+            (Type::Value, _) => false,
+            (Type::Logical, Expression::Literal(_)) => false,
+            (Type::Logical, Expression::Function(function, _)) => function.result() != Type::Value,
+            (Type::Logical, _) => true,
+            (Type::Nodes, Expression::Query(_)) => true,
+            (Type::Nodes, Expression::Function(function, _)) => function.result() == Type::Nodes,
+            (Type::Nodes, _) => false,
+        };
+        if valid {
+            return Ok(());
+        }
+
+        let expected = match parameter {
+            Type::Value => "a value",
+            Type::Logical => "a logical expression",
+            Type::Nodes => "a query",
+        };
+        self.error(
+            &format!(
+                "Argument {} of function <{}> must be {}",
+                index + 1,
+                function.name(),
+                expected
+            ),
+            position,
+        )
+    }
+}
+
+/// Member name shorthands start with a letter, `_` or any non-ASCII character
+fn is_name_first(char: char) -> bool {
+    char.is_ascii_alphabetic() || char == '_' || char >= '\u{80}'
+}
+
+/// Stands in for `regex::Regex` without the `regex` feature,
+/// where `match` and `search` fail to parse so no pattern is ever compiled
+#[cfg(not(feature = "regex"))]
+#[derive(Debug, Clone)]
+enum Regex {}
+
+#[cfg(not(feature = "regex"))]
+impl Regex {
+    fn is_match(&self, _: &str) -> bool {
+        match *self {}
+    }
+}
+
+#[cfg(not(feature = "regex"))]
+fn compile_pattern(_: &str, _: bool) -> Option<Regex> {
+    None
+}
+
+/// Compile an I-Regexp (RFC 9485) into a `Regex`, or `None` if it is not a valid I-Regexp.
+///
+/// I-Regexp has no anchors, so `^` and `$` are ordinary characters,
+/// and `.` matches anything but line breaks
+#[cfg(feature = "regex")]
+fn compile_pattern(pattern: &str, full_match: bool) -> Option<Regex> {
+    let mut translated = String::new();
+    let mut chars = pattern.chars().peekable();
+    let mut in_class = false;
+
+    while let Some(char) = chars.next() {
+        match char {
+            '\\' => {
+                let escape = chars.next()?;
+                match escape {
+                    'p' | 'P' => {
+                        translated.push('\\');
+                        translated.push(escape);
+                        if chars.next()? != '{' {
+                            return None;
+                        }
+                        translated.push('{');
+                        loop {
+                            let char = chars.next()?;
+                            translated.push(char);
+                            if char == '}' {
+                                break;
+                            }
+                        }
+                    }
+                    'n' | 'r' | 't' | '(' | ')' | '*' | '+' | '-' | '.' | '?' | '[' | '\\'
+                    | ']' | '^' | '{' | '|' | '}' => {
+                        translated.push('\\');
+                        translated.push(escape);
+                    }
+                    _ => return None,
+                }
+            }
+            '[' if in_class => return None,
+            '[' => {
+                in_class = true;
+                translated.push('[');
+                if chars.peek() == Some(&'^') {
+                    chars.next();
+                    translated.push('^');
+                }
+            }
+            ']' if in_class => {
+                in_class = false;
+                translated.push(']');
+            }
+            '&' | '~' if in_class => {
+                translated.push('\\');
+                translated.push(char);
+            }
+            '.' if !in_class => translated.push_str("[^\\n\\r]"),
+            '^' | '$' if !in_class => {
+                translated.push('\\');
+                translated.push(char);
+            }
+            '(' if chars.peek() == Some(&'?') => return None,
+            char => translated.push(char),
+        }
+    }
+    if in_class {
+        return None;
+    }
+
+    let translated = if full_match {
+        format!("\\A(?:{})\\z", translated)
+    } else {
+        translated
+    };
+    Regex::new(&translated).ok()
+}
+
+/// A node a query moves through, which either keeps track of where it is or does not need to
+trait Node<'a> {
+    fn value(&self) -> &'a JSONValue;
+    fn child(&self, location: Location<'a>, value: &'a JSONValue) -> Self;
+}
+
+impl<'a> Node<'a> for JSONPathNode<'a> {
+    fn value(&self) -> &'a JSONValue {
+        self.value
+    }
+
+    fn child(&self, location: Location<'a>, value: &'a JSONValue) -> Self {
+        let mut child_location = self.location.clone();
+        child_location.push(location);
+        JSONPathNode {
+            location: child_location,
+            value,
+        }
+    }
+}
+
+impl<'a> Node<'a> for &'a JSONValue {
+    fn value(&self) -> &'a JSONValue {
+        self
+    }
+
+    fn child(&self, _: Location<'a>, value: &'a JSONValue) -> Self {
+        value
+    }
+}
+
+fn select<'a, N: Node<'a>>(segments: &[Segment], root: &'a JSONValue, mut nodes: Vec<N>) -> Vec<N> {
+    for segment in segments {
+        let mut selected = vec![];
+        for node in &nodes {
+            if segment.descendant {
+                visit_descendants(node, &mut |descendant| {
+                    for selector in &segment.selectors {
+                        apply_selector(selector, descendant, root, &mut selected);
+                    }
+                });
+            } else {
+                for selector in &segment.selectors {
+                    apply_selector(selector, node, root, &mut selected);
+                }
+            }
+        }
+        nodes = selected;
+    }
+    nodes
+}
+
+/// Visit a node and then everything inside it, in document order
+fn visit_descendants<'a, N: Node<'a>>(node: &N, visit: &mut dyn FnMut(&N)) {
+    visit(node);
+    let value = node.value();
+    if let Some(json_array) = value.as_array() {
+        for (index, item) in json_array.iter().enumerate() {
+            visit_descendants(&node.child(Location::Index(index), item), visit);
+        }
+    } else if let Some(json_object) = value.as_object() {
+        for (key, item) in json_object {
+            visit_descendants(&node.child(Location::Key(key), item), visit);
+        }
+    }
+}
+
+fn apply_selector<'a, N: Node<'a>>(
+    selector: &Selector,
+    node: &N,
+    root: &'a JSONValue,
+    selected: &mut Vec<N>,
+) {
+    let value = node.value();
+    match selector {
+        Selector::Name(name) => {
+            if let Some((key, item)) = value
+                .as_object()
+                .and_then(|json_object| json_object.get_key_value(name))
+            {
+                selected.push(node.child(Location::Key(key), item));
+            }
+        }
+        Selector::Wildcard => {
+            if let Some(json_array) = value.as_array() {
+                for (index, item) in json_array.iter().enumerate() {
+                    selected.push(node.child(Location::Index(index), item));
+                }
+            } else if let Some(json_object) = value.as_object() {
+                for (key, item) in json_object {
+                    selected.push(node.child(Location::Key(key), item));
+                }
+            }
+        }
+        Selector::Index(index) => {
+            if let Some(json_array) = value.as_array() {
+                let length = json_array.len() as i64;
+                let index = if *index < 0 { length + index } else { *index };
+                if (0..length).contains(&index) {
+                    let index = index as usize;
+                    selected
+                        .push(node.child(Location::Index(index), json_array.get(index).unwrap()));
+                }
+            }
+        }
+        Selector::Slice(start, end, step) => {
+            if let Some(json_array) = value.as_array() {
+                for index in slice_indexes(json_array.len() as i64, *start, *end, step.unwrap_or(1))
+                {
+                    selected
+                        .push(node.child(Location::Index(index), json_array.get(index).unwrap()));
+                }
+            }
+        }
+        Selector::Filter(expression) => {
+            if let Some(json_array) = value.as_array() {
+                for (index, item) in json_array.iter().enumerate() {
+                    if test(expression, root, item) {
+                        selected.push(node.child(Location::Index(index), item));
+                    }
+                }
+            } else if let Some(json_object) = value.as_object() {
+                for (key, item) in json_object {
+                    if test(expression, root, item) {
+                        selected.push(node.child(Location::Key(key), item));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The indexes a slice selects, following section 2.3.4.2.2 of RFC 9535
+fn slice_indexes(length: i64, start: Option<i64>, end: Option<i64>, step: i64) -> Vec<usize> {
+    let normalize = |index: i64| if index >= 0 { index } else { length + index };
+    let mut indexes = vec![];
+    if step > 0 {
+        let lower = normalize(start.unwrap_or(0)).clamp(0, length);
+        let upper = normalize(end.unwrap_or(length)).clamp(0, length);
+        let mut index = lower;
+        while index < upper {
+            indexes.push(index as usize);
+            index += step;
+        }
+    } else if step < 0 {
+        let upper = normalize(start.unwrap_or(length - 1)).clamp(-1, length - 1);
+        let lower = normalize(end.unwrap_or(-length - 1)).clamp(-1, length - 1);
+        let mut index = upper;
+        while lower < index {
+            indexes.push(index as usize);
+            index += step;
+        }
+    }
+    indexes
+}
+
+/// Evaluate a filter expression against the current node `@`
+fn test(expression: &Expression, root: &JSONValue, current: &JSONValue) -> bool {
+    match expression {
+        Expression::Or(operands) => operands.iter().any(|operand| test(operand, root, current)),
+        Expression::And(operands) => operands.iter().all(|operand| test(operand, root, current)),
+        Expression::Not(operand) => !test(operand, root, current),
+        Expression::Paren(inner) => test(inner, root, current),
+        Expression::Comparison(left, comparison, right) => compare(
+            evaluate(left, root, current).as_deref(),
+            *comparison,
+            evaluate(right, root, current).as_deref(),
+        ),
+        Expression::Query(query) => !select_values(query, root, current).is_empty(),
+        Expression::Function(function, arguments) => {
+            call_logical(function, arguments, root, current)
+        }
+        // Rejected while parsing
+        Expression::Literal(_) => false,
+    }
+}
+
+/// Evaluate an expression of value type, where `None` is the absence of a value
+fn evaluate<'a>(
+    expression: &'a Expression,
+    root: &'a JSONValue,
+    current: &'a JSONValue,
+) -> Option<Cow<'a, JSONValue>> {
+    match expression {
+        Expression::Literal(literal) => Some(Cow::Borrowed(literal)),
+        Expression::Query(query) => select_values(query, root, current)
+            .first()
+            .map(|value| Cow::Borrowed(*value)),
+        Expression::Function(function, arguments) => call_value(function, arguments, root, current),
+        _ => None,
+    }
+}
+
+fn select_values<'a>(
+    query: &Query,
+    root: &'a JSONValue,
+    current: &'a JSONValue,
+) -> Vec<&'a JSONValue> {
+    let start = if query.absolute { root } else { current };
+    select(&query.segments, root, vec![start])
+}
+
+fn compare(left: Option<&JSONValue>, comparison: Comparison, right: Option<&JSONValue>) -> bool {
+    let equal = || left == right;
+    let less = |left: Option<&JSONValue>, right: Option<&JSONValue>| match (
+        left.and_then(JSONValue::as_f64),
+        right.and_then(JSONValue::as_f64),
+    ) {
+        (Some(left), Some(right)) => left < right,
+        _ => match (
+            left.and_then(JSONValue::as_str),
+            right.and_then(JSONValue::as_str),
+        ) {
+            (Some(left), Some(right)) => left < right,
+            _ => false,
+        },
+    };
+
+    match comparison {
+        Comparison::Equal => equal(),
+        Comparison::NotEqual => !equal(),
+        Comparison::Less => less(left, right),
+        Comparison::LessEqual => less(left, right) || equal(),
+        Comparison::Greater => less(right, left),
+        Comparison::GreaterEqual => less(right, left) || equal(),
+    }
+}
+
+fn call_value<'a>(
+    function: &'a Function,
+    arguments: &'a [Expression],
+    root: &'a JSONValue,
+    current: &'a JSONValue,
+) -> Option<Cow<'a, JSONValue>> {
+    match function {
+        Function::Length => {
+            let argument = evaluate(&arguments[0], root, current)?;
+            let length = if let Some(string) = argument.as_str() {
+                string.chars().count()
+            } else if let Some(json_array) = argument.as_array() {
+                json_array.len()
+            } else if let Some(json_object) = argument.as_object() {
+                json_object.len()
+            } else {
+                return None;
+            };
+            Some(Cow::Owned(JSONValue::from(length)))
+        }
+        Function::Count => {
+            let count = select_nodes(&arguments[0], root, current).len();
+            Some(Cow::Owned(JSONValue::from(count)))
+        }
+        Function::Value => match select_nodes(&arguments[0], root, current).as_slice() {
+            [value] => Some(Cow::Borrowed(*value)),
+            _ => None,
+        },
+        Function::Match(_) | Function::Search(_) => None,
+    }
+}
+
+fn call_logical(
+    function: &Function,
+    arguments: &[Expression],
+    root: &JSONValue,
+    current: &JSONValue,
+) -> bool {
+    let (compiled, full_match) = match function {
+        Function::Match(compiled) => (compiled, true),
+        Function::Search(compiled) => (compiled, false),
+        _ => return false,
+    };
+
+    let string = evaluate(&arguments[0], root, current);
+    let Some(string) = string.as_deref().and_then(JSONValue::as_str) else {
+        return false;
+    };
+
+    if let Some(regex) = compiled {
+        return regex.is_match(string);
+    }
+    let pattern = evaluate(&arguments[1], root, current);
+    match pattern
+        .as_deref()
+        .and_then(JSONValue::as_str)
+        .and_then(|pattern| compile_pattern(pattern, full_match))
+    {
+        Some(regex) => regex.is_match(string),
+        None => false,
+    }
+}
+
+/// Evaluate an argument of nodes type
+fn select_nodes<'a>(
+    expression: &Expression,
+    root: &'a JSONValue,
+    current: &'a JSONValue,
+) -> Vec<&'a JSONValue> {
+    match expression {
+        Expression::Query(query) => select_values(query, root, current),
+        _ => vec![],
+    }
+}
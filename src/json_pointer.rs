@@ -0,0 +1,504 @@
+use std::{fmt::Display, str::FromStr};
+
+use crate::{JSONError, JSONObject, JSONRef, JSONValue};
+
+/// A JSON Pointer as defined by RFC 6901, which addresses a value inside a JSON Value
+/// with a list of object keys and array indexes, written like `/spec/containers/0/image`
+///
+/// In the written form, `~` is escaped as `~0` and `/` is escaped as `~1`.
+/// The token `-` refers to the position past the last item of a JSON Array.
+///
+/// # Example
+///
+/// ```
+/// use parson::JSONPointer;
+///
+/// let pointer = "/a~1b/m~0n/-".parse::<JSONPointer>().unwrap();
+/// assert_eq!(pointer.tokens(), ["a/b", "m~n", "-"]);
+/// assert_eq!(pointer.to_string(), "/a~1b/m~0n/-");
+/// assert_eq!(pointer.parent().unwrap().to_string(), "/a~1b/m~0n");
+/// assert_eq!(JSONPointer::root().join("0").join("key").to_string(), "/0/key");
+///
+/// assert_eq!(
+///     "a/b".parse::<JSONPointer>().unwrap_err().get_message(),
+///     "JSON Pointer <a/b> must be empty or start with </>"
+/// );
+/// assert_eq!(
+///     "/a/b~2/c".parse::<JSONPointer>().unwrap_err().get_message(),
+///     "Invalid escape in token <b~2> (at /a/b~2)"
+/// );
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct JSONPointer {
+    tokens: Vec<String>,
+}
+
+impl Display for JSONPointer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for token in &self.tokens {
+            write!(f, "/{}", escape(token))?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for JSONPointer {
+    type Err = JSONError;
+
+    fn from_str(pointer: &str) -> Result<Self, Self::Err> {
+        JSONPointer::parse(pointer)
+    }
+}
+
+impl JSONPointer {
+    /// Create a JSON Pointer to the whole JSON Value
+    pub fn root() -> Self {
+        JSONPointer::default()
+    }
+
+    /// Parse a JSON Pointer from its written form
+    pub fn parse(pointer: &str) -> Result<Self, JSONError> {
+        if pointer.is_empty() {
+            return Ok(JSONPointer::root());
+        }
+        if !pointer.starts_with('/') {
+            return Err(JSONError::with_path(
+                &format!("JSON Pointer <{}> must be empty or start with </>", pointer),
+                "",
+            ));
+        }
+
+        let mut tokens = vec![];
+        let mut written = String::new();
+        for token in pointer[1..].split('/') {
+            written.push('/');
+            written.push_str(token);
+            match unescape(token) {
+                Some(token) => tokens.push(token),
+                None => {
+                    // Reported against the written path, since the token has no unescaped form
+                    return Err(JSONError::with_path(
+                        &format!("Invalid escape in token <{}>", token),
+                        &written,
+                    ));
+                }
+            }
+        }
+        Ok(JSONPointer { tokens })
+    }
+
+    /// Create a JSON Pointer from unescaped tokens
+    pub fn from_tokens<I: IntoIterator<Item = S>, S: Into<String>>(tokens: I) -> Self {
+        JSONPointer {
+            tokens: tokens.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// Get the unescaped tokens of the JSON Pointer
+    pub fn tokens(&self) -> &[String] {
+        &self.tokens
+    }
+
+    /// If the JSON Pointer refers to the whole JSON Value
+    pub fn is_root(&self) -> bool {
+        self.tokens.is_empty()
+    }
+
+    /// Add an unescaped token to the end of the JSON Pointer
+    pub fn push<S: Into<String>>(&mut self, token: S) {
+        self.tokens.push(token.into());
+    }
+
+    /// Create a JSON Pointer to a child of the value this JSON Pointer refers to
+    pub fn join<S: Into<String>>(&self, token: S) -> Self {
+        let mut pointer = self.clone();
+        pointer.push(token);
+        pointer
+    }
+
+    /// Get a JSON Pointer to the container of the value this JSON Pointer refers to,
+    /// or `None` for the root
+    pub fn parent(&self) -> Option<JSONPointer> {
+        let (_, tokens) = self.tokens.split_last()?;
+        Some(JSONPointer {
+            tokens: tokens.to_vec(),
+        })
+    }
+
+    /// Get the last token of the JSON Pointer, or `None` for the root
+    pub fn last(&self) -> Option<&str> {
+        self.tokens.last().map(String::as_str)
+    }
+
+    /// If this JSON Pointer refers to the same value as `other` or a value inside it
+    pub fn starts_with(&self, other: &JSONPointer) -> bool {
+        self.tokens.starts_with(&other.tokens)
+    }
+
+    /// Get the value this JSON Pointer refers to in `value`
+    pub fn resolve<'a>(&self, value: &'a JSONValue) -> Result<&'a JSONValue, JSONError> {
+        let mut current = value;
+        for (depth, token) in self.tokens.iter().enumerate() {
+            current = step(current, token).map_err(|message| self.error(depth, &message))?;
+        }
+        Ok(current)
+    }
+
+    /// Get a mutable reference to the value this JSON Pointer refers to in `value`
+    pub fn resolve_mut<'a>(
+        &self,
+        value: &'a mut JSONValue,
+    ) -> Result<&'a mut JSONValue, JSONError> {
+        let mut current = value;
+        for (depth, token) in self.tokens.iter().enumerate() {
+            current = step_mut(current, token).map_err(|message| self.error(depth, &message))?;
+        }
+        Ok(current)
+    }
+
+    /// Set the value this JSON Pointer refers to in `value`, returning the value it replaced.
+    ///
+    /// The last token may add a new key to a JSON Object,
+    /// or add an item to the end of a JSON Array with `-` or the array's length.
+    /// With `create_intermediates`, missing keys and nulls on the way are filled in with empty JSON Objects,
+    /// and `-` on the way adds an empty JSON Object to the end of a JSON Array
+    pub fn set(
+        &self,
+        value: &mut JSONValue,
+        new_value: JSONValue,
+        create_intermediates: bool,
+    ) -> Result<Option<JSONValue>, JSONError> {
+        let Some((last, parents)) = self.tokens.split_last() else {
+            return Ok(Some(std::mem::replace(value, new_value)));
+        };
+
+        let mut current = value;
+        for (depth, token) in parents.iter().enumerate() {
+            current = if create_intermediates {
+                step_or_create(current, token)
+            } else {
+                step_mut(current, token)
+            }
+            .map_err(|message| self.error(depth, &message))?;
+        }
+
+        let depth = parents.len();
+        if current.is_null() && create_intermediates {
+            *current = JSONValue::from_object(JSONObject::new());
+        }
+        if let Some(json_object) = current.as_object_mut() {
+            match json_object.get_mut(last) {
+                Some(old) => Ok(Some(std::mem::replace(old, new_value))),
+                None => {
+                    json_object.set(last.clone(), new_value);
+                    Ok(None)
+                }
+            }
+        } else if let Some(json_array) = current.as_array_mut() {
+            let index = parse_index(last, json_array.len(), true)
+                .map_err(|message| self.error(depth, &message))?;
+            if index == json_array.len() {
+                json_array.push(new_value);
+                Ok(None)
+            } else {
+                let item = json_array.get_mut(index).unwrap();
+                Ok(Some(std::mem::replace(item, new_value)))
+            }
+        } else {
+            Err(self.error(depth, &cannot_index(current, last)))
+        }
+    }
+
+    /// Insert a value where this JSON Pointer refers to in `value`, the way a JSON Patch `add` does,
+    /// returning the value it replaced.
+    ///
+    /// Unlike [`set`](JSONPointer::set), an index into a JSON Array shifts the items from that index up
+    /// instead of replacing one. The container of the value must already exist
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use parson::{json, JSONPointer};
+    ///
+    /// let mut json_value = json!({ "tags": ["a", "c"] });
+    /// JSONPointer::parse("/tags/1").unwrap().insert(&mut json_value, json!("b")).unwrap();
+    /// JSONPointer::parse("/tags/-").unwrap().insert(&mut json_value, json!("d")).unwrap();
+    /// assert_eq!(json_value.to_string(), r#"{ "tags": [ "a", "b", "c", "d" ] }"#);
+    /// ```
+    pub fn insert(
+        &self,
+        value: &mut JSONValue,
+        new_value: JSONValue,
+    ) -> Result<Option<JSONValue>, JSONError> {
+        let Some((last, parents)) = self.tokens.split_last() else {
+            return Ok(Some(std::mem::replace(value, new_value)));
+        };
+
+        let depth = parents.len();
+        let parent = JSONPointer::from_tokens(parents.iter().cloned()).resolve_mut(value)?;
+        if let Some(json_array) = parent.as_array_mut() {
+            let index = parse_index(last, json_array.len(), true)
+                .map_err(|message| self.error(depth, &message))?;
+            json_array.insert(index, new_value);
+            Ok(None)
+        } else if let Some(json_object) = parent.as_object_mut() {
+            match json_object.get_mut(last) {
+                Some(old) => Ok(Some(std::mem::replace(old, new_value))),
+                None => {
+                    json_object.set(last.clone(), new_value);
+                    Ok(None)
+                }
+            }
+        } else {
+            Err(self.error(depth, &cannot_index(parent, last)))
+        }
+    }
+
+    /// Remove the value this JSON Pointer refers to from `value` and return it.
+    ///
+    /// Keys after a removed key keep their order, and items after a removed item shift down
+    pub fn remove(&self, value: &mut JSONValue) -> Result<JSONValue, JSONError> {
+        let Some((last, parents)) = self.tokens.split_last() else {
+            return Err(JSONError::with_path(
+                "Cannot remove the root of a JSON Value",
+                "",
+            ));
+        };
+
+        let depth = parents.len();
+        let parent = JSONPointer::from_tokens(parents.iter().cloned()).resolve_mut(value)?;
+        if let Some(json_object) = parent.as_object_mut() {
+            json_object
+                .shift_remove(last)
+                .ok_or_else(|| self.error(depth, &format!("Key <{}> not found", last)))
+        } else if let Some(json_array) = parent.as_array_mut() {
+            let index = parse_index(last, json_array.len(), false)
+                .map_err(|message| self.error(depth, &message))?;
+            Ok(json_array.remove(index))
+        } else {
+            Err(self.error(depth, &cannot_index(parent, last)))
+        }
+    }
+
+    /// An error about the token at `depth`, with the path up to and including it
+    pub(crate) fn error(&self, depth: usize, message: &str) -> JSONError {
+        let path = JSONPointer::from_tokens(self.tokens[..=depth].iter().cloned());
+        JSONError::with_path(message, &path.to_string())
+    }
+}
+
+impl JSONValue {
+    /// Get the value a JSON Pointer refers to inside the JSON Value
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use parson::JSONValue;
+    ///
+    /// let json_value = r#"{ "spec": { "containers": [{ "image": "nginx" }] }, "a/b": 1, "m~n": 2 }"#
+    ///     .parse::<JSONValue>()
+    ///     .unwrap();
+    /// assert_eq!(json_value.pointer("/spec/containers/0/image").unwrap().as_str(), Some("nginx"));
+    /// assert_eq!(json_value.pointer("/a~1b").unwrap().as_f64(), Some(1.0));
+    /// assert_eq!(json_value.pointer("/m~0n").unwrap().as_f64(), Some(2.0));
+    /// assert!(json_value.pointer("").unwrap().is_object());
+    ///
+    /// let error = json_value.pointer("/spec/containers/1/image").unwrap_err();
+    /// assert_eq!(error.get_path(), Some("/spec/containers/1"));
+    /// assert_eq!(
+    ///     error.get_message(),
+    ///     "Index <1> is out of range for a JSON Array of length <1> (at /spec/containers/1)"
+    /// );
+    /// assert_eq!(
+    ///     json_value.pointer("/spec/containers/01").unwrap_err().get_message(),
+    ///     "Invalid array index <01> (at /spec/containers/01)"
+    /// );
+    /// assert_eq!(
+    ///     json_value.pointer("/a~1b/c").unwrap_err().get_message(),
+    ///     "Cannot index into a JSON Number with <c> (at /a~1b/c)"
+    /// );
+    /// ```
+    pub fn pointer(&self, pointer: &str) -> Result<&JSONValue, JSONError> {
+        JSONPointer::parse(pointer)?.resolve(self)
+    }
+
+    /// Get a mutable reference to the value a JSON Pointer refers to inside the JSON Value
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use parson::JSONValue;
+    ///
+    /// let mut json_value = r#"{ "tags": ["a", "b"] }"#.parse::<JSONValue>().unwrap();
+    /// *json_value.pointer_mut("/tags/1").unwrap() = JSONValue::from("c");
+    /// assert_eq!(json_value.to_string(), r#"{ "tags": [ "a", "c" ] }"#);
+    /// ```
+    pub fn pointer_mut(&mut self, pointer: &str) -> Result<&mut JSONValue, JSONError> {
+        JSONPointer::parse(pointer)?.resolve_mut(self)
+    }
+
+    /// Set the value a JSON Pointer refers to inside the JSON Value, returning the value it replaced.
+    /// See [`JSONPointer::set`] for how missing keys and the `-` token are handled
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use parson::{json, JSONValue};
+    ///
+    /// let mut json_value = json!({ "tags": ["a"] });
+    /// json_value.set_pointer("/tags/-", json!("b"), false).unwrap();
+    /// json_value.set_pointer("/tags/0", json!("z"), false).unwrap();
+    /// assert_eq!(json_value, json!({ "tags": ["z", "b"] }));
+    ///
+    /// assert!(json_value.set_pointer("/meta/labels/app", json!("web"), false).is_err());
+    /// json_value.set_pointer("/meta/labels/app", json!("web"), true).unwrap();
+    /// assert_eq!(
+    ///     json_value,
+    ///     json!({ "tags": ["z", "b"], "meta": { "labels": { "app": "web" } } })
+    /// );
+    /// ```
+    pub fn set_pointer(
+        &mut self,
+        pointer: &str,
+        value: JSONValue,
+        create_intermediates: bool,
+    ) -> Result<Option<JSONValue>, JSONError> {
+        JSONPointer::parse(pointer)?.set(self, value, create_intermediates)
+    }
+
+    /// Remove the value a JSON Pointer refers to inside the JSON Value and return it
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use parson::{json, JSONValue};
+    ///
+    /// let mut json_value = json!({ "a": [1, 2, 3], "b": true });
+    /// assert_eq!(json_value.remove_pointer("/a/0").unwrap().as_f64(), Some(1.0));
+    /// assert_eq!(json_value.remove_pointer("/b").unwrap().as_bool(), Some(true));
+    /// assert_eq!(json_value.to_string(), r#"{ "a": [ 2, 3 ] }"#);
+    /// assert_eq!(
+    ///     json_value.remove_pointer("/a/-").unwrap_err().get_message(),
+    ///     "Index <-> refers past the end of the JSON Array (at /a/-)"
+    /// );
+    /// ```
+    pub fn remove_pointer(&mut self, pointer: &str) -> Result<JSONValue, JSONError> {
+        JSONPointer::parse(pointer)?.remove(self)
+    }
+}
+
+fn escape(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+fn unescape(token: &str) -> Option<String> {
+    let mut result = String::with_capacity(token.len());
+    let mut chars = token.chars();
+    while let Some(char) = chars.next() {
+        if char == '~' {
+            match chars.next() {
+                Some('0') => result.push('~'),
+                Some('1') => result.push('/'),
+                _ => return None,
+            }
+        } else {
+            result.push(char);
+        }
+    }
+    Some(result)
+}
+
+/// Parse an array index token, where `-` and `length` are only allowed if `allow_end` is set
+pub(crate) fn parse_index(token: &str, length: usize, allow_end: bool) -> Result<usize, String> {
+    if token == "-" {
+        return if allow_end {
+            Ok(length)
+        } else {
+            Err("Index <-> refers past the end of the JSON Array".to_string())
+        };
+    }
+
+    let valid = !token.is_empty()
+        && token.bytes().all(|byte| byte.is_ascii_digit())
+        && (token == "0" || !token.starts_with('0'));
+    let index = match token.parse::<usize>() {
+        Ok(index) if valid => index,
+        _ => return Err(format!("Invalid array index <{}>", token)),
+    };
+
+    if index < length || (allow_end && index == length) {
+        Ok(index)
+    } else {
+        Err(format!(
+            "Index <{}> is out of range for a JSON Array of length <{}>",
+            index, length
+        ))
+    }
+}
+
+/// Describe the type of a JSON Value for an error message
+pub(crate) fn type_name(value: &JSONValue) -> &'static str {
+    match value.get_ref() {
+        JSONRef::String(_) => "JSON String",
+        JSONRef::Number(_) => "JSON Number",
+        JSONRef::Boolean(_) => "JSON Boolean",
+        JSONRef::Null(_) => "JSON Null",
+        JSONRef::Array(_) => "JSON Array",
+        JSONRef::Object(_) => "JSON Object",
+    }
+}
+
+pub(crate) fn cannot_index(value: &JSONValue, token: &str) -> String {
+    format!("Cannot index into a {} with <{}>", type_name(value), token)
+}
+
+fn step<'a>(value: &'a JSONValue, token: &str) -> Result<&'a JSONValue, String> {
+    if let Some(json_object) = value.as_object() {
+        json_object
+            .get(token)
+            .ok_or_else(|| format!("Key <{}> not found", token))
+    } else if let Some(json_array) = value.as_array() {
+        let index = parse_index(token, json_array.len(), false)?;
+        Ok(json_array.get(index).unwrap())
+    } else {
+        Err(cannot_index(value, token))
+    }
+}
+
+fn step_mut<'a>(value: &'a mut JSONValue, token: &str) -> Result<&'a mut JSONValue, String> {
+    if value.is_object() {
+        let json_object = value.as_object_mut().unwrap();
+        json_object
+            .get_mut(token)
+            .ok_or_else(|| format!("Key <{}> not found", token))
+    } else if value.is_array() {
+        let json_array = value.as_array_mut().unwrap();
+        let index = parse_index(token, json_array.len(), false)?;
+        Ok(json_array.get_mut(index).unwrap())
+    } else {
+        Err(cannot_index(value, token))
+    }
+}
+
+/// Step into a container like [`step_mut`], filling in whatever is missing with empty JSON Objects
+fn step_or_create<'a>(value: &'a mut JSONValue, token: &str) -> Result<&'a mut JSONValue, String> {
+    if value.is_null() {
+        *value = JSONValue::from_object(JSONObject::new());
+    }
+
+    if value.is_object() {
+        let json_object = value.as_object_mut().unwrap();
+        Ok(json_object
+            .entry(token.to_string())
+            .or_insert_with(|| JSONValue::from_object(JSONObject::new())))
+    } else if value.is_array() {
+        let json_array = value.as_array_mut().unwrap();
+        let index = parse_index(token, json_array.len(), true)?;
+        if index == json_array.len() {
+            json_array.push(JSONValue::from_object(JSONObject::new()));
+        }
+        Ok(json_array.get_mut(index).unwrap())
+    } else {
+        Err(cannot_index(value, token))
+    }
+}
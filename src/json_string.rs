@@ -1,37 +1,57 @@
-use std::fmt::Display;
+use std::fmt::{self, Display};
 
-#[derive(Debug, Clone)]
+use crate::{serializer::Serializer, shared::Shared, CompactFormatter, Formatter};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct JSONString {
-    data: String,
+    data: Shared<String>,
 }
 
+/// Convert JSON String to a Rust owned string
+///
+/// # Example
+///
+/// ```
+/// use parson::JSONString;
+///
+/// assert_eq!(JSONString::new("value".to_string()).to_string(), "\"value\"");
+/// assert_eq!(JSONString::new("\"\n".to_string()).to_string(), r#""\"\n""#);
+/// ```
 impl Display for JSONString {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.to_string())
+        self.serialize(&mut Serializer::new(f, &mut CompactFormatter))
     }
 }
 
 impl JSONString {
     /// Create a new empty JSON String
     pub fn new(data: String) -> Self {
-        JSONString { data }
+        JSONString {
+            data: Shared::new(data),
+        }
     }
 
-    /// Convert JSON String to a Rust owned string
-    ///
-    /// # Example
-    ///
-    /// ```
-    /// use parson::JSONString;
-    ///
-    /// assert_eq!(JSONString::new("value".to_string()).to_string(), "\"value\"");
-    /// ```
-    pub fn to_string(&self) -> String {
-        format!("\"{}\"", self.data.clone())
+    pub(crate) fn serialize<W: fmt::Write, F: Formatter>(
+        &self,
+        serializer: &mut Serializer<W, F>,
+    ) -> fmt::Result {
+        serializer
+            .formatter
+            .write_string(serializer.writer, &self.data)
     }
 
     /// Get a Rust owned string from the JSON String
     pub fn get_string(&self) -> String {
-        self.data.clone()
+        self.data.to_string()
+    }
+
+    /// Get a Rust string slice from the JSON String without cloning it
+    pub fn as_str(&self) -> &str {
+        &self.data
+    }
+
+    /// Convert the JSON String into the Rust owned string inside it without cloning it
+    pub fn into_string(self) -> String {
+        self.data.into_inner()
     }
 }
@@ -1,11 +1,20 @@
 use std::{
-    fmt::{Debug, Display},
+    cmp::Ordering,
+    fmt::{self, Debug, Display},
+    hash::{Hash, Hasher},
+    io,
+    ops::{Index, IndexMut},
     str::FromStr,
 };
 
 use crate::{
-    json_err, json_error::JSONError, lexer::Lexer, parser::Parser, JSONArray, JSONBoolean,
-    JSONNull, JSONNumber, JSONObject, JSONString,
+    json_err,
+    json_error::JSONError,
+    lexer::Lexer,
+    parser::Parser,
+    serializer::{IoWriter, Serializer},
+    CompactFormatter, Formatter, JSONArray, JSONBoolean, JSONNull, JSONNumber, JSONObject,
+    JSONString, PrettyFormatter,
 };
 
 #[derive(Debug, Clone)]
@@ -18,11 +27,131 @@ pub enum JSONType {
     Object(JSONObject),
 }
 
-#[derive(Debug, Clone)]
+impl JSONType {
+    /// Where the type sorts relative to the other types
+    fn rank(&self) -> u8 {
+        match self {
+            JSONType::Null(_) => 0,
+            JSONType::Boolean(_) => 1,
+            JSONType::Number(_) => 2,
+            JSONType::String(_) => 3,
+            JSONType::Array(_) => 4,
+            JSONType::Object(_) => 5,
+        }
+    }
+}
+
+impl PartialEq for JSONType {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for JSONType {}
+
+impl Hash for JSONType {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.rank().hash(state);
+        match self {
+            JSONType::String(json_string) => json_string.hash(state),
+            JSONType::Number(json_number) => json_number.hash(state),
+            JSONType::Boolean(json_boolean) => json_boolean.hash(state),
+            JSONType::Null(json_null) => json_null.hash(state),
+            JSONType::Array(json_array) => json_array.hash(state),
+            JSONType::Object(json_object) => json_object.hash(state),
+        }
+    }
+}
+
+impl PartialOrd for JSONType {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// JSON Values of different types are ordered by type, as
+/// null < boolean < number < string < array < object.
+/// JSON Values of the same type are ordered by their data:
+/// `false` before `true`, numbers numerically with NaN last,
+/// strings by their bytes, arrays one item at a time,
+/// and objects one key-value pair at a time after sorting by key
+///
+/// # Example
+///
+/// ```
+/// use std::collections::{BTreeSet, HashSet};
+///
+/// use parson::JSONValue;
+///
+/// let mut json_value = r#"[{}, [], "b", "a", 2, -1, true, false, null, [0], { "a": 0 }]"#
+///     .parse::<JSONValue>()
+///     .unwrap();
+/// json_value.as_array_mut().unwrap().as_mut_slice().sort();
+/// assert_eq!(
+///     json_value.to_string(),
+///     r#"[ null, false, true, -1, 2, "a", "b", [  ], [ 0 ], {  }, { "a": 0 } ]"#
+/// );
+///
+/// let values = r#"[1, 1.0, -0, 0, { "a": 1, "b": 2 }, { "b": 2, "a": 1 }]"#
+///     .parse::<JSONValue>()
+///     .unwrap()
+///     .into_array()
+///     .unwrap();
+/// assert_eq!(values.iter().collect::<HashSet<_>>().len(), 3);
+/// assert_eq!(values.iter().collect::<BTreeSet<_>>().len(), 3);
+/// ```
+impl Ord for JSONType {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (JSONType::String(a), JSONType::String(b)) => a.cmp(b),
+            (JSONType::Number(a), JSONType::Number(b)) => a.cmp(b),
+            (JSONType::Boolean(a), JSONType::Boolean(b)) => a.cmp(b),
+            (JSONType::Null(a), JSONType::Null(b)) => a.cmp(b),
+            (JSONType::Array(a), JSONType::Array(b)) => a.cmp(b),
+            (JSONType::Object(a), JSONType::Object(b)) => a.cmp(b),
+            _ => self.rank().cmp(&other.rank()),
+        }
+    }
+}
+
+/// A borrowed view of the data in a JSON Value, which can be matched on without cloning it
+///
+/// # Example
+///
+/// ```
+/// use parson::{JSONRef, JSONValue};
+///
+/// let json_value = r#"{ "a": 1, "b": [true] }"#.parse::<JSONValue>().unwrap();
+/// let json_object = json_value.as_object().unwrap();
+/// for key in ["a", "b"] {
+///     match json_object.get(key).unwrap().get_ref() {
+///         JSONRef::Number(json_number) => assert_eq!(json_number.get_number(), 1.0),
+///         JSONRef::Array(json_array) => assert_eq!(json_array.len(), 1),
+///         _ => unreachable!(),
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub enum JSONRef<'a> {
+    String(&'a JSONString),
+    Number(&'a JSONNumber),
+    Boolean(&'a JSONBoolean),
+    Null(&'a JSONNull),
+    Array(&'a JSONArray),
+    Object(&'a JSONObject),
+}
+
+/// JSON Values compare and hash the way [`JSONType`] does
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct JSONValue {
     data: JSONType,
 }
 
+/// The JSON Value that indexing returns when a key or index is missing
+static NULL: JSONValue = JSONValue {
+    data: JSONType::Null(JSONNull),
+};
+
 impl FromStr for JSONValue {
     type Err = JSONError;
 
@@ -45,7 +174,137 @@ impl FromStr for JSONValue {
 
 impl Display for JSONValue {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.to_string())
+        self.serialize(&mut Serializer::new(f, &mut CompactFormatter))
+    }
+}
+
+/// Get the value of a key in the JSON Object inside the JSON Value.
+///
+/// Returns a JSON Null instead of panicking if the JSON Value is not a JSON Object
+/// or the key is missing, so lookups can be chained safely.
+///
+/// # Example
+///
+/// ```
+/// use parson::JSONValue;
+///
+/// let json_value = r#"{ "data": { "items": [{ "id": 7 }] } }"#.parse::<JSONValue>().unwrap();
+/// assert_eq!(json_value["data"]["items"][0]["id"].as_f64(), Some(7.0));
+/// assert!(json_value["data"]["missing"][3]["id"].is_null());
+/// ```
+impl Index<&str> for JSONValue {
+    type Output = JSONValue;
+
+    fn index(&self, key: &str) -> &Self::Output {
+        match &self.data {
+            JSONType::Object(json_object) => json_object.get(key).unwrap_or(&NULL),
+            _ => &NULL,
+        }
+    }
+}
+
+/// Get the value at an index in the JSON Array inside the JSON Value.
+///
+/// Returns a JSON Null instead of panicking if the JSON Value is not a JSON Array
+/// or the index is out of bounds, so lookups can be chained safely.
+impl Index<usize> for JSONValue {
+    type Output = JSONValue;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        match &self.data {
+            JSONType::Array(json_array) => json_array.get(index).unwrap_or(&NULL),
+            _ => &NULL,
+        }
+    }
+}
+
+/// Get a mutable reference to the value of a key in the JSON Object inside the JSON Value.
+///
+/// A JSON Null is replaced with an empty JSON Object first,
+/// and a missing key is added with a JSON Null value, so documents can be built up in place.
+///
+/// # Panics
+///
+/// Panics if the JSON Value is neither a JSON Object nor a JSON Null
+///
+/// # Example
+///
+/// ```
+/// use parson::{JSONNull, JSONNumber, JSONValue};
+///
+/// let mut json_value = JSONValue::from_null(JSONNull::new());
+/// json_value["data"]["items"][1]["id"] = JSONValue::from_number(JSONNumber::new(7.0));
+/// assert_eq!(
+///     json_value.to_string(),
+///     r#"{ "data": { "items": [ null, { "id": 7 } ] } }"#
+/// );
+/// ```
+impl IndexMut<&str> for JSONValue {
+    fn index_mut(&mut self, key: &str) -> &mut Self::Output {
+        if self.is_null() {
+            *self = JSONValue::from_object(JSONObject::new());
+        }
+
+        match &mut self.data {
+            JSONType::Object(json_object) => json_object
+                .entry(key.to_string())
+                .or_insert_with(|| JSONValue::from_null(JSONNull::new())),
+            _ => panic!(
+                "Cannot index into a non-object JSON Value with key <{}>",
+                key
+            ),
+        }
+    }
+}
+
+/// Get a mutable reference to the value at an index in the JSON Array inside the JSON Value.
+///
+/// A JSON Null is replaced with an empty JSON Array first,
+/// and the JSON Array is padded with JSON Nulls up to the index if it is out of bounds,
+/// so documents can be built up in place.
+/// Writing to index `n` of a short JSON Array therefore allocates `n + 1` items.
+///
+/// # Panics
+///
+/// Panics if the JSON Value is neither a JSON Array nor a JSON Null,
+/// before the JSON Value is changed
+///
+/// # Example
+///
+/// ```
+/// use parson::{JSONNull, JSONNumber, JSONValue};
+/// use std::panic::{catch_unwind, AssertUnwindSafe};
+///
+/// let mut json_value = JSONValue::from_null(JSONNull::new());
+/// json_value[2] = JSONValue::from_number(JSONNumber::new(3.0));
+/// json_value[0] = JSONValue::from_number(JSONNumber::new(1.0));
+/// assert_eq!(json_value.to_string(), "[ 1, null, 3 ]");
+///
+/// let mut json_value = JSONValue::from_number(JSONNumber::new(1.0));
+/// let result = catch_unwind(AssertUnwindSafe(|| {
+///     json_value[0] = JSONValue::from_null(JSONNull::new());
+/// }));
+/// assert!(result.is_err());
+/// assert_eq!(json_value.to_string(), "1");
+/// ```
+impl IndexMut<usize> for JSONValue {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        if self.is_null() {
+            *self = JSONValue::from_array(JSONArray::new());
+        }
+
+        match &mut self.data {
+            JSONType::Array(json_array) => {
+                while json_array.len() <= index {
+                    json_array.push(JSONValue::from_null(JSONNull::new()));
+                }
+                json_array.get_mut(index).unwrap()
+            }
+            _ => panic!(
+                "Cannot index into a non-array JSON Value with index <{}>",
+                index
+            ),
+        }
     }
 }
 
@@ -151,18 +410,35 @@ impl JSONValue {
     /// # Example
     ///
     /// ```
-    ///	use parson::{JSONNull, JSONType, JSONValue};
+    /// use parson::{JSONNull, JSONType, JSONValue};
     ///
     /// let json_value = JSONValue::from_null(JSONNull::new());
     /// assert!(match json_value.get_type() {
-    /// 	JSONType::Null(json_null) => true,
-    /// 	_ => false,
+    ///     JSONType::Null(json_null) => true,
+    ///     _ => false,
     /// });
     /// ```
     pub fn get_type(&self) -> JSONType {
         self.data.clone()
     }
 
+    /// Get a borrowed view of the data in the JSON Value without cloning it
+    pub fn get_ref(&self) -> JSONRef<'_> {
+        match &self.data {
+            JSONType::String(json_string) => JSONRef::String(json_string),
+            JSONType::Number(json_number) => JSONRef::Number(json_number),
+            JSONType::Boolean(json_boolean) => JSONRef::Boolean(json_boolean),
+            JSONType::Null(json_null) => JSONRef::Null(json_null),
+            JSONType::Array(json_array) => JSONRef::Array(json_array),
+            JSONType::Object(json_object) => JSONRef::Object(json_object),
+        }
+    }
+
+    /// Convert the JSON Value into the data inside it without cloning it
+    pub fn into_type(self) -> JSONType {
+        self.data
+    }
+
     /// Cast the JSON Value to a Rust owned string
     pub fn get_string(&self) -> Result<String, JSONError> {
         match &self.data {
@@ -211,6 +487,127 @@ impl JSONValue {
         }
     }
 
+    /// Borrow the Rust string slice inside the JSON Value, if it holds a JSON String
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use parson::JSONValue;
+    ///
+    /// let json_value = r#""value""#.parse::<JSONValue>().unwrap();
+    /// assert_eq!(json_value.as_str(), Some("value"));
+    /// assert_eq!(json_value.as_f64(), None);
+    /// ```
+    pub fn as_str(&self) -> Option<&str> {
+        match &self.data {
+            JSONType::String(json_string) => Some(json_string.as_str()),
+            _ => None,
+        }
+    }
+
+    /// Get the Rust f64 inside the JSON Value, if it holds a JSON Number
+    pub fn as_f64(&self) -> Option<f64> {
+        match &self.data {
+            JSONType::Number(json_number) => Some(json_number.get_number()),
+            _ => None,
+        }
+    }
+
+    /// Get the Rust bool inside the JSON Value, if it holds a JSON Boolean
+    pub fn as_bool(&self) -> Option<bool> {
+        match &self.data {
+            JSONType::Boolean(json_boolean) => Some(json_boolean.get_boolean()),
+            _ => None,
+        }
+    }
+
+    /// Borrow the JSON Null inside the JSON Value, if it holds one
+    pub fn as_null(&self) -> Option<&JSONNull> {
+        match &self.data {
+            JSONType::Null(json_null) => Some(json_null),
+            _ => None,
+        }
+    }
+
+    /// Borrow the JSON Array inside the JSON Value, if it holds one
+    pub fn as_array(&self) -> Option<&JSONArray> {
+        match &self.data {
+            JSONType::Array(json_array) => Some(json_array),
+            _ => None,
+        }
+    }
+
+    /// Borrow the JSON Object inside the JSON Value, if it holds one
+    pub fn as_object(&self) -> Option<&JSONObject> {
+        match &self.data {
+            JSONType::Object(json_object) => Some(json_object),
+            _ => None,
+        }
+    }
+
+    /// Convert the JSON Value into the Rust owned string inside it, if it holds a JSON String
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use parson::JSONValue;
+    ///
+    /// let json_value = r#""value""#.parse::<JSONValue>().unwrap();
+    /// assert_eq!(json_value.into_string(), Some("value".to_string()));
+    /// ```
+    pub fn into_string(self) -> Option<String> {
+        match self.data {
+            JSONType::String(json_string) => Some(json_string.into_string()),
+            _ => None,
+        }
+    }
+
+    /// Convert the JSON Value into the JSON Array inside it, if it holds one
+    pub fn into_array(self) -> Option<JSONArray> {
+        match self.data {
+            JSONType::Array(json_array) => Some(json_array),
+            _ => None,
+        }
+    }
+
+    /// Convert the JSON Value into the JSON Object inside it, if it holds one
+    pub fn into_object(self) -> Option<JSONObject> {
+        match self.data {
+            JSONType::Object(json_object) => Some(json_object),
+            _ => None,
+        }
+    }
+
+    /// Get a mutable reference to the JSON Array inside the JSON Value, if it holds one
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use parson::{JSONNull, JSONValue};
+    ///
+    /// let mut json_value = "[]".parse::<JSONValue>().unwrap();
+    /// json_value
+    ///     .as_array_mut()
+    ///     .unwrap()
+    ///     .push(JSONValue::from_null(JSONNull::new()));
+    /// assert_eq!(json_value.to_string(), "[ null ]");
+    /// assert!(json_value.as_object_mut().is_none());
+    /// ```
+    pub fn as_array_mut(&mut self) -> Option<&mut JSONArray> {
+        match &mut self.data {
+            JSONType::Array(json_array) => Some(json_array),
+            _ => None,
+        }
+    }
+
+    /// Get a mutable reference to the JSON Object inside the JSON Value, if it holds one
+    pub fn as_object_mut(&mut self) -> Option<&mut JSONObject> {
+        match &mut self.data {
+            JSONType::Object(json_object) => Some(json_object),
+            _ => None,
+        }
+    }
+
     /// If the JSON Value is a JSON String
     ///
     /// # Example
@@ -223,10 +620,7 @@ impl JSONValue {
     /// assert!(json_value.is_string());
     /// ```
     pub fn is_string(&self) -> bool {
-        match self.data {
-            JSONType::String(_) => true,
-            _ => false,
-        }
+        matches!(self.data, JSONType::String(_))
     }
 
     /// If the JSON Value is a JSON Number
@@ -241,10 +635,7 @@ impl JSONValue {
     /// assert!(json_value.is_number());
     /// ```
     pub fn is_number(&self) -> bool {
-        match self.data {
-            JSONType::Number(_) => true,
-            _ => false,
-        }
+        matches!(self.data, JSONType::Number(_))
     }
 
     /// If the JSON Value is a JSON Boolean
@@ -259,10 +650,7 @@ impl JSONValue {
     /// assert!(json_value.is_boolean());
     /// ```
     pub fn is_boolean(&self) -> bool {
-        match self.data {
-            JSONType::Boolean(_) => true,
-            _ => false,
-        }
+        matches!(self.data, JSONType::Boolean(_))
     }
 
     /// If the JSON Value is a JSON Null
@@ -277,10 +665,7 @@ impl JSONValue {
     /// assert!(json_value.is_null());
     /// ```
     pub fn is_null(&self) -> bool {
-        match self.data {
-            JSONType::Null(_) => true,
-            _ => false,
-        }
+        matches!(self.data, JSONType::Null(_))
     }
 
     /// If the JSON Value is a JSON Array
@@ -295,10 +680,7 @@ impl JSONValue {
     /// assert!(json_value.is_array());
     /// ```
     pub fn is_array(&self) -> bool {
-        match self.data {
-            JSONType::Array(_) => true,
-            _ => false,
-        }
+        matches!(self.data, JSONType::Array(_))
     }
 
     /// If the JSON Value is a JSON Object
@@ -313,32 +695,154 @@ impl JSONValue {
     /// assert!(json_value.is_object());
     /// ```
     pub fn is_object(&self) -> bool {
-        match self.data {
-            JSONType::Object(_) => true,
-            _ => false,
-        }
+        matches!(self.data, JSONType::Object(_))
     }
 
-    /// Convert JSON Value to a Rust owned string
-    pub fn to_string(&self) -> String {
-        match &self.data {
-            JSONType::String(json_string) => json_string.to_string(),
-            JSONType::Number(json_number) => json_number.to_string(),
-            JSONType::Boolean(json_boolean) => json_boolean.to_string(),
-            JSONType::Null(json_null) => json_null.to_string(),
-            JSONType::Array(json_array) => json_array.to_string(),
-            JSONType::Object(json_object) => json_object.to_string(),
-        }
+    /// Format JSON Value to a Rust owned string
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use parson::JSONValue;
+    ///
+    /// let json_value = r#"{ "key": [1, 2] }"#.parse::<JSONValue>().unwrap();
+    /// assert_eq!(json_value.format_string(0, 2), "{\n  \"key\": [\n    1,\n    2\n  ]\n}");
+    /// ```
+    pub fn format_string(&self, indents: i32, spaces: i32) -> String {
+        let mut result = String::new();
+        let mut formatter =
+            PrettyFormatter::with_spaces(spaces as usize).at_level(indents as usize);
+        self.serialize(&mut Serializer::new(&mut result, &mut formatter))
+            .expect("writing to a String cannot fail");
+        result
     }
 
-    pub fn format_string(&self, indents: i32, spaces: i32) -> String {
+    /// Write the JSON Value to an `io::Write` on a single line,
+    /// without building the output in memory first
+    ///
+    /// Writes are buffered internally and `writer` is flushed at the end,
+    /// so a `File` or `TcpStream` needs no `BufWriter` around it
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use parson::JSONValue;
+    ///
+    /// let json_value = r#"{ "key": [1, 2] }"#.parse::<JSONValue>().unwrap();
+    /// let mut output = vec![];
+    /// json_value.write_to(&mut output).unwrap();
+    /// assert_eq!(output, br#"{ "key": [ 1, 2 ] }"#);
+    /// ```
+    pub fn write_to<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.write_with_formatter(writer, &mut CompactFormatter)
+    }
+
+    /// Write the JSON Value to an `io::Write` with `spaces` spaces per indentation level,
+    /// without building the output in memory first
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use parson::JSONValue;
+    ///
+    /// let json_value = r#"{ "key": [1, 2] }"#.parse::<JSONValue>().unwrap();
+    /// let mut output = vec![];
+    /// json_value.write_pretty_to(&mut output, 2).unwrap();
+    /// assert_eq!(output, json_value.format_string(0, 2).as_bytes());
+    /// ```
+    pub fn write_pretty_to<W: io::Write>(&self, writer: &mut W, spaces: usize) -> io::Result<()> {
+        self.write_with_formatter(writer, &mut PrettyFormatter::with_spaces(spaces))
+    }
+
+    /// Write the JSON Value to a `fmt::Write` on a single line
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use parson::JSONValue;
+    ///
+    /// let json_value = "[true, null]".parse::<JSONValue>().unwrap();
+    /// let mut output = String::from("value: ");
+    /// json_value.write_to_fmt(&mut output).unwrap();
+    /// assert_eq!(output, "value: [ true, null ]");
+    /// ```
+    pub fn write_to_fmt<W: fmt::Write>(&self, writer: &mut W) -> fmt::Result {
+        self.write_with_formatter_to_fmt(writer, &mut CompactFormatter)
+    }
+
+    /// Write the JSON Value to a `fmt::Write` with `spaces` spaces per indentation level
+    pub fn write_pretty_to_fmt<W: fmt::Write>(&self, writer: &mut W, spaces: usize) -> fmt::Result {
+        self.write_with_formatter_to_fmt(writer, &mut PrettyFormatter::with_spaces(spaces))
+    }
+
+    /// Write the JSON Value to an `io::Write` in the style of a custom [`Formatter`]
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use parson::{JSONValue, PrettyFormatter};
+    ///
+    /// let json_value = "[1]".parse::<JSONValue>().unwrap();
+    /// let mut output = vec![];
+    /// json_value
+    ///     .write_with_formatter(&mut output, &mut PrettyFormatter::with_indent("\t"))
+    ///     .unwrap();
+    /// assert_eq!(output, b"[\n\t1\n]");
+    /// ```
+    pub fn write_with_formatter<W: io::Write, F: Formatter>(
+        &self,
+        writer: &mut W,
+        formatter: &mut F,
+    ) -> io::Result<()> {
+        IoWriter::run(writer, |writer| {
+            self.serialize(&mut Serializer::new(writer, formatter))
+        })
+    }
+
+    /// Write the JSON Value to a `fmt::Write` in the style of a custom [`Formatter`]
+    pub fn write_with_formatter_to_fmt<W: fmt::Write, F: Formatter>(
+        &self,
+        writer: &mut W,
+        formatter: &mut F,
+    ) -> fmt::Result {
+        self.serialize(&mut Serializer::new(writer, formatter))
+    }
+
+    /// Convert JSON Value to a Rust owned string in the style of a custom [`Formatter`]
+    pub fn to_string_with_formatter<F: Formatter>(&self, formatter: &mut F) -> String {
+        let mut result = String::new();
+        self.write_with_formatter_to_fmt(&mut result, formatter)
+            .expect("writing to a String cannot fail");
+        result
+    }
+
+    /// Convert JSON Value to a byte vector on a single line
+    pub fn to_vec(&self) -> Vec<u8> {
+        let mut result = vec![];
+        self.write_to(&mut result)
+            .expect("writing to a Vec cannot fail");
+        result
+    }
+
+    /// Convert JSON Value to a byte vector with `spaces` spaces per indentation level
+    pub fn to_vec_pretty(&self, spaces: usize) -> Vec<u8> {
+        let mut result = vec![];
+        self.write_pretty_to(&mut result, spaces)
+            .expect("writing to a Vec cannot fail");
+        result
+    }
+
+    pub(crate) fn serialize<W: fmt::Write, F: Formatter>(
+        &self,
+        serializer: &mut Serializer<W, F>,
+    ) -> fmt::Result {
         match &self.data {
-            JSONType::String(json_string) => json_string.to_string(),
-            JSONType::Number(json_number) => json_number.to_string(),
-            JSONType::Boolean(json_boolean) => json_boolean.to_string(),
-            JSONType::Null(json_null) => json_null.to_string(),
-            JSONType::Array(json_array) => json_array.format_string(indents, spaces),
-            JSONType::Object(json_object) => json_object.format_string(indents, spaces),
+            JSONType::String(json_string) => json_string.serialize(serializer),
+            JSONType::Number(json_number) => json_number.serialize(serializer),
+            JSONType::Boolean(json_boolean) => json_boolean.serialize(serializer),
+            JSONType::Null(json_null) => json_null.serialize(serializer),
+            JSONType::Array(json_array) => json_array.serialize(serializer),
+            JSONType::Object(json_object) => json_object.serialize(serializer),
         }
     }
 }
@@ -0,0 +1,234 @@
+use std::{fmt, io};
+
+use crate::{
+    serializer::{IoWriter, Serializer},
+    Formatter, JSONValue,
+};
+
+/// Pretty prints a JSON Value, keeping a JSON Array or JSON Object on one line when it fits
+/// within the maximum line width and breaking it onto one line per element when it does not
+///
+/// # Example
+///
+/// ```
+/// use parson::{JSONValue, LayoutPrinter};
+///
+/// let json_value = r#"{ "point": [1, 2, 3], "tags": ["a very long tag", "another long tag"] }"#
+///     .parse::<JSONValue>()
+///     .unwrap();
+/// #[cfg(feature = "preserve_order")]
+/// assert_eq!(
+///     LayoutPrinter::new().max_width(50).print(&json_value),
+///     "{\n  \"point\": [1, 2, 3],\n  \"tags\": [\"a very long tag\", \"another long tag\"]\n}"
+/// );
+/// #[cfg(feature = "preserve_order")]
+/// assert_eq!(
+///     LayoutPrinter::new().max_width(30).print(&json_value),
+///     "{\n  \"point\": [1, 2, 3],\n  \"tags\": [\n    \"a very long tag\",\n    \"another long tag\"\n  ]\n}"
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct LayoutPrinter {
+    max_width: usize,
+    indent: String,
+    trailing_newline: bool,
+}
+
+impl Default for LayoutPrinter {
+    fn default() -> Self {
+        LayoutPrinter {
+            max_width: 80,
+            indent: "  ".to_string(),
+            trailing_newline: false,
+        }
+    }
+}
+
+impl LayoutPrinter {
+    /// Create a new Layout Printer with a maximum width of 80, indenting with 2 spaces
+    pub fn new() -> Self {
+        LayoutPrinter::default()
+    }
+
+    /// Set the number of characters a line may take before a container is broken up
+    pub fn max_width(mut self, max_width: usize) -> Self {
+        self.max_width = max_width;
+        self
+    }
+
+    /// Set the string written once per level of nesting
+    pub fn indent(mut self, indent: &str) -> Self {
+        self.indent = indent.to_string();
+        self
+    }
+
+    /// Set whether the output ends with a line break
+    pub fn trailing_newline(mut self, trailing_newline: bool) -> Self {
+        self.trailing_newline = trailing_newline;
+        self
+    }
+
+    /// Print the JSON Value to a Rust owned string
+    pub fn print(&self, value: &JSONValue) -> String {
+        let mut result = String::new();
+        self.write_to_fmt(&mut result, value)
+            .expect("writing to a String cannot fail");
+        result
+    }
+
+    /// Print the JSON Value to an `io::Write`
+    pub fn write_to<W: io::Write>(&self, writer: &mut W, value: &JSONValue) -> io::Result<()> {
+        IoWriter::run(writer, |writer| self.write_to_fmt(writer, value))
+    }
+
+    /// Print the JSON Value to a `fmt::Write`
+    pub fn write_to_fmt<W: fmt::Write>(&self, writer: &mut W, value: &JSONValue) -> fmt::Result {
+        self.write_value(writer, value, 0, 0, 0)?;
+        if self.trailing_newline {
+            writer.write_char('\n')?;
+        }
+        Ok(())
+    }
+
+    /// Write a value that starts at `column`, leaving room for `suffix` characters after it
+    fn write_value<W: fmt::Write>(
+        &self,
+        writer: &mut W,
+        value: &JSONValue,
+        level: usize,
+        column: usize,
+        suffix: usize,
+    ) -> fmt::Result {
+        let remaining = self.max_width.saturating_sub(column + suffix);
+        if let Ok(json_array) = value.get_array() {
+            if !json_array.is_empty() && !fits(value, remaining) {
+                let length = json_array.len();
+                writer.write_char('[')?;
+                for (index, item) in json_array.iter().enumerate() {
+                    self.write_newline(writer, level + 1)?;
+                    let suffix = if index + 1 < length { 1 } else { 0 };
+                    let column = self.indent_width(level + 1);
+                    self.write_value(writer, item, level + 1, column, suffix)?;
+                    if suffix > 0 {
+                        writer.write_char(',')?;
+                    }
+                }
+                self.write_newline(writer, level)?;
+                return writer.write_char(']');
+            }
+        } else if let Ok(json_object) = value.get_object() {
+            if !json_object.is_empty() && !fits(value, remaining) {
+                let length = json_object.len();
+                writer.write_char('{')?;
+                for (index, (key, item)) in json_object.iter().enumerate() {
+                    self.write_newline(writer, level + 1)?;
+                    InlineFormatter::default().write_string(writer, key)?;
+                    writer.write_str(": ")?;
+                    let suffix = if index + 1 < length { 1 } else { 0 };
+                    let column = self.indent_width(level + 1) + width_of_key(key) + 2;
+                    self.write_value(writer, item, level + 1, column, suffix)?;
+                    if suffix > 0 {
+                        writer.write_char(',')?;
+                    }
+                }
+                self.write_newline(writer, level)?;
+                return writer.write_char('}');
+            }
+        }
+
+        value.serialize(&mut Serializer::new(
+            writer,
+            &mut InlineFormatter::default(),
+        ))
+    }
+
+    fn indent_width(&self, level: usize) -> usize {
+        self.indent.chars().count() * level
+    }
+
+    fn write_newline<W: fmt::Write>(&self, writer: &mut W, level: usize) -> fmt::Result {
+        writer.write_char('\n')?;
+        for _ in 0..level {
+            writer.write_str(&self.indent)?;
+        }
+        Ok(())
+    }
+}
+
+/// If the value written on a single line takes at most `width` characters
+fn fits(value: &JSONValue, width: usize) -> bool {
+    let mut limit = WidthLimit { remaining: width };
+    value
+        .serialize(&mut Serializer::new(
+            &mut limit,
+            &mut InlineFormatter::default(),
+        ))
+        .is_ok()
+}
+
+fn width_of_key(key: &str) -> usize {
+    let mut limit = WidthLimit {
+        remaining: usize::MAX,
+    };
+    InlineFormatter::default()
+        .write_string(&mut limit, key)
+        .expect("counting the width of a key cannot fail");
+    usize::MAX - limit.remaining
+}
+
+/// Counts written characters, failing as soon as there are more than it has room for
+struct WidthLimit {
+    remaining: usize,
+}
+
+impl fmt::Write for WidthLimit {
+    fn write_str(&mut self, string: &str) -> fmt::Result {
+        let width = string.chars().count();
+        if width > self.remaining {
+            return Err(fmt::Error);
+        }
+        self.remaining -= width;
+        Ok(())
+    }
+}
+
+/// Writes a container on one line the way it would be typed by hand,
+/// as `[1, 2]` and `{ "key": 1 }`
+#[derive(Default)]
+struct InlineFormatter {
+    object_has_keys: Vec<bool>,
+}
+
+impl Formatter for InlineFormatter {
+    fn begin_array<W: ?Sized + fmt::Write>(&mut self, writer: &mut W) -> fmt::Result {
+        writer.write_char('[')
+    }
+
+    fn end_array<W: ?Sized + fmt::Write>(&mut self, writer: &mut W) -> fmt::Result {
+        writer.write_char(']')
+    }
+
+    fn begin_object<W: ?Sized + fmt::Write>(&mut self, writer: &mut W) -> fmt::Result {
+        self.object_has_keys.push(false);
+        writer.write_char('{')
+    }
+
+    fn end_object<W: ?Sized + fmt::Write>(&mut self, writer: &mut W) -> fmt::Result {
+        if self.object_has_keys.pop().unwrap_or(false) {
+            writer.write_str(" }")
+        } else {
+            writer.write_char('}')
+        }
+    }
+
+    fn begin_object_key<W: ?Sized + fmt::Write>(
+        &mut self,
+        writer: &mut W,
+        first: bool,
+    ) -> fmt::Result {
+        if let Some(has_keys) = self.object_has_keys.last_mut() {
+            *has_keys = true;
+        }
+        writer.write_str(if first { " " } else { ", " })
+    }
+}
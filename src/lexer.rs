@@ -19,7 +19,7 @@ impl Lexer {
         let mut line = 1;
         let mut column = 1;
 
-        while json.len() > 0 {
+        while !json.is_empty() {
             if let Some(result) = self.lex_string(&mut json, line, &mut column) {
                 if let Ok(string_token) = result {
                     tokens.push(string_token);
@@ -120,48 +120,105 @@ impl Lexer {
     ) -> Option<Result<Token, JSONError>> {
         let mut data = String::new();
 
-        if &string[..1] != "\"" {
+        if !string.starts_with('"') {
             return None;
         }
 
-        let mut escape = false;
+        let start_column = *column;
+        let mut chars = string.char_indices().skip(1);
 
         *column += 1;
-        for char in string[1..].chars() {
-            if escape {
-                if ['r', 'n', 't', '\\', '\"'].contains(&char) {
-                    *column += 1;
-                    data.push(char);
-                    escape = false;
-                } else {
-                    json_err!(
-                        Some; "Invalid escape of character <{}>", char.encode_utf8(&mut [0, 0]); line, *column
-                    )
+        while let Some((index, char)) = chars.next() {
+            *column += 1;
+            match char {
+                '"' => {
+                    *string = string[index + 1..].to_string();
+                    return Some(Ok(Token::new(TokenType::String(data), line, start_column)));
                 }
-            } else {
-                if char == '\\' {
-                    *column += 1;
-                    data.push(char);
-                    escape = true;
-                } else if char == '"' {
-                    let length = data.len();
-                    *column += 1;
-                    *string = string[length + 2..].to_string();
-                    return Some(Ok(Token::new(
-                        TokenType::String(data),
-                        line,
-                        *column - length,
-                    )));
-                } else {
+                '\\' => {
+                    let escaped = if let Some((_, escaped)) = chars.next() {
+                        escaped
+                    } else {
+                        break;
+                    };
                     *column += 1;
-                    data.push(char);
+                    match escaped {
+                        '"' => data.push('"'),
+                        '\\' => data.push('\\'),
+                        '/' => data.push('/'),
+                        'b' => data.push('\u{8}'),
+                        'f' => data.push('\u{c}'),
+                        'n' => data.push('\n'),
+                        'r' => data.push('\r'),
+                        't' => data.push('\t'),
+                        'u' => match self.lex_unicode_escape(&mut chars, line, column) {
+                            Ok(char) => data.push(char),
+                            Err(error) => json_err!(Some; error),
+                        },
+                        char => {
+                            json_err!(Some; "Invalid escape of character <{}>", char; line, *column)
+                        }
+                    }
                 }
+                char => data.push(char),
             }
         }
 
         json_err!(Some; "Unexpected end of string"; line, *column)
     }
 
+    /// Lex the hex digits after a `\u`, along with the low half of a surrogate pair if needed
+    fn lex_unicode_escape(
+        &self,
+        chars: &mut impl Iterator<Item = (usize, char)>,
+        line: usize,
+        column: &mut usize,
+    ) -> Result<char, JSONError> {
+        let high = self.lex_hex_digits(chars, line, column)?;
+        if !(0xD800..0xDC00).contains(&high) {
+            return match char::from_u32(high) {
+                Some(char) => Ok(char),
+                None => json_err!("Invalid unicode escape <\\u{:04x}>", high; line, *column),
+            };
+        }
+
+        let backslash = chars.next().map(|(_, char)| char);
+        let u = chars.next().map(|(_, char)| char);
+        if backslash != Some('\\') || u != Some('u') {
+            json_err!("Expected low surrogate after <\\u{:04x}>", high; line, *column);
+        }
+        *column += 2;
+
+        let low = self.lex_hex_digits(chars, line, column)?;
+        if !(0xDC00..0xE000).contains(&low) {
+            json_err!("Invalid low surrogate <\\u{:04x}>", low; line, *column);
+        }
+
+        Ok(char::from_u32(0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)).unwrap())
+    }
+
+    fn lex_hex_digits(
+        &self,
+        chars: &mut impl Iterator<Item = (usize, char)>,
+        line: usize,
+        column: &mut usize,
+    ) -> Result<u32, JSONError> {
+        let mut code = 0;
+        for _ in 0..4 {
+            match chars.next().map(|(_, char)| char) {
+                Some(char) if char.is_ascii_hexdigit() => {
+                    *column += 1;
+                    code = code * 16 + char.to_digit(16).unwrap();
+                }
+                Some(char) => {
+                    json_err!("Invalid hex digit in unicode escape <{}>", char; line, *column)
+                }
+                None => json_err!("Unexpected end of string"; line, *column),
+            }
+        }
+        Ok(code)
+    }
+
     fn lex_number(
         &self,
         string: &mut String,
@@ -172,14 +229,14 @@ impl Lexer {
 
         for char in string.chars() {
             let last_char = data.chars().last();
-            if char.is_digit(10) {
+            if char.is_ascii_digit() {
                 *column += 1;
                 data.push(char);
                 continue;
             }
 
             if char == '-' {
-                if data == "" || last_char.unwrap() == 'e' || last_char.unwrap() == 'E' {
+                if data.is_empty() || last_char.unwrap() == 'e' || last_char.unwrap() == 'E' {
                     *column += 1;
                     data.push(char);
                     continue;
@@ -193,8 +250,8 @@ impl Lexer {
             }
 
             if char == 'e' || char == 'E' {
-                if data != ""
-                    && last_char.unwrap().is_digit(10)
+                if !data.is_empty()
+                    && last_char.unwrap().is_ascii_digit()
                     && !data.contains('e')
                     && !data.contains('E')
                 {
@@ -211,8 +268,8 @@ impl Lexer {
             }
 
             if char == '.' {
-                if data != ""
-                    && last_char.unwrap().is_digit(10)
+                if !data.is_empty()
+                    && last_char.unwrap().is_ascii_digit()
                     && !data.contains("e")
                     && !data.contains('E')
                 {
@@ -231,7 +288,7 @@ impl Lexer {
             break;
         }
 
-        if data.len() == 0 {
+        if data.is_empty() {
             return None;
         }
 
@@ -7,25 +7,110 @@
 //! - JSON Number is parsed into a Rust f64
 //! - JSON Boolean is parsed into a Rust bool
 //! - JSON Null is not parsable into Rust since there is no Null value
+//!
+//! ## Features
+//!
+//! - `preserve_order` (default): JSON Objects keep their keys in the order they were added,
+//!   backed by an `IndexMap`.
+//! - `hash_map`: without `preserve_order`, JSON Objects are backed by a `HashMap`
+//!   and their keys are in no particular order.
+//!   Without either feature, JSON Objects are backed by a `BTreeMap` and their keys are sorted.
+//!   Getting, setting and converting to a `HashMap` behave the same on every backend.
+//! - `shared`: JSON Strings, JSON Arrays and JSON Objects keep their data behind an `Arc`,
+//!   so cloning a JSON Value is O(1) however large it is.
+//!   Mutating a clone copies only the containers on the path to the change,
+//!   and the rest stays shared with the original. JSON Values stay `Send + Sync` either way.
+//!
+//! ```
+//! use parson::JSONValue;
+//!
+//! fn assert_send_sync<T: Send + Sync>(_: &T) {}
+//!
+//! let original = r#"{ "a": { "b": [1, 2] }, "c": "unchanged" }"#.parse::<JSONValue>().unwrap();
+//! let mut snapshot = original.clone();
+//! snapshot["a"]["b"][0] = JSONValue::from(10);
+//! assert_eq!(original["a"]["b"].to_string(), "[ 1, 2 ]");
+//! assert_eq!(snapshot["a"]["b"].to_string(), "[ 10, 2 ]");
+//!
+//! assert_send_sync(&snapshot);
+//! std::thread::spawn(move || assert_eq!(snapshot["c"].as_str(), Some("unchanged")))
+//!     .join()
+//!     .unwrap();
+//! ```
 
+pub use formatter::CompactFormatter;
+pub use formatter::EscapeFormatter;
+pub use formatter::Formatter;
+pub use formatter::PrettyFormatter;
 pub use json_array::JSONArray;
 pub use json_boolean::JSONBoolean;
+pub use json_crdt::JSONCrdt;
+pub use json_crdt::JSONCrdtOperation;
+pub use json_deep_merge::JSONDeepMerge;
+pub use json_deep_merge::JSONLayers;
+pub use json_deep_merge::JSONMergeStrategy;
+pub use json_deep_merge::JSONOrigin;
+pub use json_diff::diff;
+pub use json_diff::JSONDiff;
+pub use json_editor::JSONEditor;
 pub use json_error::JSONError;
+pub use json_filter::JSONFilter;
+pub use json_merge3::merge3;
+pub use json_merge3::JSONConflict;
+pub use json_merge3::JSONMerge;
+pub use json_merge3::JSONMerger;
+pub use json_merge3::JSONResolution;
+pub use json_merge_patch::create_merge_patch;
+pub use json_merge_patch::merge_patch;
 pub use json_null::JSONNull;
 pub use json_number::JSONNumber;
+pub use json_object::Entry;
 pub use json_object::JSONObject;
+pub use json_object::JSONObjectIntoIter;
+pub use json_object::JSONObjectIter;
+pub use json_object::JSONObjectIterMut;
+pub use json_object::JSONObjectKeys;
+pub use json_object::JSONObjectValues;
+pub use json_object::JSONObjectValuesMut;
+pub use json_object::OccupiedEntry;
+pub use json_object::VacantEntry;
+pub use json_patch::apply_patch;
+pub use json_patch::JSONPatch;
+pub use json_patch::JSONPatchOperation;
+pub use json_path::JSONPath;
+pub use json_path::JSONPathNode;
+pub use json_pointer::JSONPointer;
 pub use json_string::JSONString;
+pub use json_value::JSONRef;
 pub use json_value::JSONType;
 pub use json_value::JSONValue;
+pub use layout_printer::LayoutPrinter;
 
+mod canonical;
+mod convert;
+mod formatter;
 mod json_array;
 mod json_boolean;
+mod json_crdt;
+mod json_deep_merge;
+mod json_diff;
+mod json_editor;
 mod json_error;
+mod json_filter;
+mod json_merge3;
+mod json_merge_patch;
 mod json_null;
 mod json_number;
 mod json_object;
+mod json_patch;
+mod json_path;
+mod json_pointer;
 mod json_string;
 mod json_value;
+mod layout_printer;
 mod lexer;
+mod macros;
 mod parser;
+mod serializer;
+mod shared;
 mod token;
@@ -0,0 +1,187 @@
+/// Build a JSON Value from JSON-like syntax, with Rust expressions in place of any value
+///
+/// Anything that converts into a JSON Value with `From` can be interpolated.
+/// Object keys are either string literals or any expression that converts into a `String`,
+/// and keep the order they are written in with the `preserve_order` feature.
+///
+/// # Example
+///
+/// ```
+/// use parson::{json, JSONValue};
+///
+/// let id = 42;
+/// let tag = String::from("b");
+/// let json_value = json!({
+///     "id": id,
+///     "tags": ["a", tag, null],
+///     "meta": null,
+///     "nested": { "empty": {}, "list": [], "sum": id + 1 },
+///     "negative": -1.5,
+/// });
+/// assert_eq!(
+///     json_value,
+///     r#"{ "id": 42, "tags": ["a", "b", null], "meta": null, "nested": { "empty": {}, "list": [], "sum": 43 }, "negative": -1.5 }"#
+///         .parse::<JSONValue>()
+///         .unwrap()
+/// );
+///
+/// let key = "dynamic";
+/// assert_eq!(json!({ key: true })["dynamic"].as_bool(), Some(true));
+/// assert!(json!(null).is_null());
+/// assert_eq!(json!([json!(1), Some("x"), None::<i32>]).to_string(), r#"[ 1, "x", null ]"#);
+/// ```
+///
+/// A key without a value does not compile
+///
+/// ```compile_fail
+/// parson::json!({ "key" });
+/// ```
+#[macro_export]
+macro_rules! json {
+    ($($json:tt)+) => {
+        $crate::json_internal!($($json)+)
+    };
+}
+
+#[macro_export]
+#[doc(hidden)]
+macro_rules! json_internal {
+    // Array elements are munched one at a time into `[$($elements,)*]`
+
+    (@array [$($elements:expr,)*]) => {
+        ::std::vec![$($elements,)*]
+    };
+
+    (@array [$($elements:expr),*]) => {
+        ::std::vec![$($elements),*]
+    };
+
+    (@array [$($elements:expr,)*] null $($rest:tt)*) => {
+        $crate::json_internal!(@array [$($elements,)* $crate::json_internal!(null)] $($rest)*)
+    };
+
+    (@array [$($elements:expr,)*] [$($array:tt)*] $($rest:tt)*) => {
+        $crate::json_internal!(@array [$($elements,)* $crate::json_internal!([$($array)*])] $($rest)*)
+    };
+
+    (@array [$($elements:expr,)*] {$($object:tt)*} $($rest:tt)*) => {
+        $crate::json_internal!(@array [$($elements,)* $crate::json_internal!({$($object)*})] $($rest)*)
+    };
+
+    (@array [$($elements:expr,)*] $next:expr, $($rest:tt)*) => {
+        $crate::json_internal!(@array [$($elements,)* $crate::json_internal!($next),] $($rest)*)
+    };
+
+    (@array [$($elements:expr,)*] $last:expr) => {
+        $crate::json_internal!(@array [$($elements,)* $crate::json_internal!($last)])
+    };
+
+    (@array [$($elements:expr),*] , $($rest:tt)*) => {
+        $crate::json_internal!(@array [$($elements,)*] $($rest)*)
+    };
+
+    (@array [$($elements:expr),*] $unexpected:tt $($rest:tt)*) => {
+        $crate::json_unexpected!($unexpected)
+    };
+
+    // Object entries are munched by collecting the key tokens in `($($key)*)` up to the colon,
+    // then the value up to the comma, then setting `[$($key)+]` on `$object`.
+    // The tokens left to munch are repeated at the end so errors can point at them.
+
+    (@object $object:ident () () ()) => {};
+
+    (@object $object:ident [$($key:tt)+] ($value:expr) , $($rest:tt)*) => {
+        $object.set(($($key)+).into(), $value);
+        $crate::json_internal!(@object $object () ($($rest)*) ($($rest)*));
+    };
+
+    (@object $object:ident [$($key:tt)+] ($value:expr) $unexpected:tt $($rest:tt)*) => {
+        $crate::json_unexpected!($unexpected);
+    };
+
+    (@object $object:ident [$($key:tt)+] ($value:expr)) => {
+        $object.set(($($key)+).into(), $value);
+    };
+
+    (@object $object:ident ($($key:tt)+) (: null $($rest:tt)*) $copy:tt) => {
+        $crate::json_internal!(@object $object [$($key)+] ($crate::json_internal!(null)) $($rest)*);
+    };
+
+    (@object $object:ident ($($key:tt)+) (: [$($array:tt)*] $($rest:tt)*) $copy:tt) => {
+        $crate::json_internal!(@object $object [$($key)+] ($crate::json_internal!([$($array)*])) $($rest)*);
+    };
+
+    (@object $object:ident ($($key:tt)+) (: {$($map:tt)*} $($rest:tt)*) $copy:tt) => {
+        $crate::json_internal!(@object $object [$($key)+] ($crate::json_internal!({$($map)*})) $($rest)*);
+    };
+
+    (@object $object:ident ($($key:tt)+) (: $value:expr , $($rest:tt)*) $copy:tt) => {
+        $crate::json_internal!(@object $object [$($key)+] ($crate::json_internal!($value)) , $($rest)*);
+    };
+
+    (@object $object:ident ($($key:tt)+) (: $value:expr) $copy:tt) => {
+        $crate::json_internal!(@object $object [$($key)+] ($crate::json_internal!($value)));
+    };
+
+    // A key with a colon but no value, or with no colon at all
+    (@object $object:ident ($($key:tt)+) (:) $copy:tt) => {
+        $crate::json_internal!();
+    };
+
+    (@object $object:ident ($($key:tt)+) () $copy:tt) => {
+        $crate::json_internal!();
+    };
+
+    (@object $object:ident () (: $($rest:tt)*) ($colon:tt $($copy:tt)*)) => {
+        $crate::json_unexpected!($colon);
+    };
+
+    (@object $object:ident ($($key:tt)*) (, $($rest:tt)*) ($comma:tt $($copy:tt)*)) => {
+        $crate::json_unexpected!($comma);
+    };
+
+    (@object $object:ident () (($key:expr) : $($rest:tt)*) $copy:tt) => {
+        $crate::json_internal!(@object $object ($key) (: $($rest)*) (: $($rest)*));
+    };
+
+    (@object $object:ident ($($key:tt)*) ($tt:tt $($rest:tt)*) $copy:tt) => {
+        $crate::json_internal!(@object $object ($($key)* $tt) ($($rest)*) ($($rest)*));
+    };
+
+    // Values
+
+    (null) => {
+        $crate::JSONValue::from(())
+    };
+
+    ([]) => {
+        $crate::JSONValue::from($crate::JSONArray::new())
+    };
+
+    ([ $($tt:tt)+ ]) => {
+        $crate::JSONValue::from($crate::json_internal!(@array [] $($tt)+))
+    };
+
+    ({}) => {
+        $crate::JSONValue::from($crate::JSONObject::new())
+    };
+
+    ({ $($tt:tt)+ }) => {
+        $crate::JSONValue::from({
+            let mut object = $crate::JSONObject::new();
+            $crate::json_internal!(@object object () ($($tt)+) ($($tt)+));
+            object
+        })
+    };
+
+    ($other:expr) => {
+        $crate::JSONValue::from($other)
+    };
+}
+
+/// Fails to match on purpose, so the compiler points at the token that was not expected
+#[macro_export]
+#[doc(hidden)]
+macro_rules! json_unexpected {
+    () => {};
+}
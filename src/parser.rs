@@ -1,9 +1,15 @@
+use std::collections::BTreeMap;
+
 use crate::{
     json_err,
     token::{Token, TokenType},
-    JSONArray, JSONBoolean, JSONError, JSONNull, JSONNumber, JSONObject, JSONString, JSONValue,
+    JSONArray, JSONBoolean, JSONError, JSONNull, JSONNumber, JSONObject, JSONPointer, JSONString,
+    JSONValue,
 };
 
+/// The line and column each value starts at, or for a value in a JSON Object, where its key starts
+pub(crate) type Positions = BTreeMap<JSONPointer, (usize, usize)>;
+
 pub struct Parser {
     tokens: Vec<Token>,
 }
@@ -97,27 +103,27 @@ impl Parser {
     fn parse_value(
         &self,
         tokens: &mut Vec<Token>,
-        stop_token_types: &Vec<TokenType>,
+        stop_token_types: &[TokenType],
     ) -> Option<Result<JSONValue, JSONError>> {
-        if let Some(result) = self.parse_string(tokens, &stop_token_types) {
+        if let Some(result) = self.parse_string(tokens, stop_token_types) {
             if let Ok(results) = result {
                 Some(Ok(JSONValue::from_string(results.0)))
             } else {
                 json_err!(Some; result.unwrap_err())
             }
-        } else if let Some(result) = self.parse_number(tokens, &stop_token_types) {
+        } else if let Some(result) = self.parse_number(tokens, stop_token_types) {
             if let Ok(results) = result {
                 Some(Ok(JSONValue::from_number(results.0)))
             } else {
                 json_err!(Some; result.unwrap_err())
             }
-        } else if let Some(result) = self.parse_boolean(tokens, &stop_token_types) {
+        } else if let Some(result) = self.parse_boolean(tokens, stop_token_types) {
             if let Ok(results) = result {
                 Some(Ok(JSONValue::from_boolean(results.0)))
             } else {
                 json_err!(Some; result.unwrap_err())
             }
-        } else if let Some(result) = self.parse_null(tokens, &stop_token_types) {
+        } else if let Some(result) = self.parse_null(tokens, stop_token_types) {
             if let Ok(results) = result {
                 Some(Ok(JSONValue::from_null(results.0)))
             } else {
@@ -143,7 +149,7 @@ impl Parser {
     fn parse_string(
         &self,
         tokens: &mut Vec<Token>,
-        stop_token_types: &Vec<TokenType>,
+        stop_token_types: &[TokenType],
     ) -> Option<Result<(JSONString, Token), JSONError>> {
         let tokens_cl = tokens.clone();
         if let Some(token) = tokens_cl.first() {
@@ -169,7 +175,7 @@ impl Parser {
     fn parse_number(
         &self,
         tokens: &mut Vec<Token>,
-        stop_token_types: &Vec<TokenType>,
+        stop_token_types: &[TokenType],
     ) -> Option<Result<(JSONNumber, Token), JSONError>> {
         let tokens_cl = tokens.clone();
         if let Some(token) = tokens_cl.first() {
@@ -195,7 +201,7 @@ impl Parser {
     fn parse_boolean(
         &self,
         tokens: &mut Vec<Token>,
-        stop_token_types: &Vec<TokenType>,
+        stop_token_types: &[TokenType],
     ) -> Option<Result<(JSONBoolean, Token), JSONError>> {
         let tokens_cl = tokens.clone();
 
@@ -222,7 +228,7 @@ impl Parser {
     fn parse_null(
         &self,
         tokens: &mut Vec<Token>,
-        stop_token_types: &Vec<TokenType>,
+        stop_token_types: &[TokenType],
     ) -> Option<Result<(JSONNull, Token), JSONError>> {
         let tokens_cl = tokens.clone();
 
@@ -254,11 +260,9 @@ impl Parser {
                     }
                 }
 
-                while tokens.len() > 0 {
-                    let json_value = self.parse_value(
-                        tokens,
-                        &vec![TokenType::CloseSquareBracket, TokenType::Comma],
-                    );
+                while !tokens.is_empty() {
+                    let json_value = self
+                        .parse_value(tokens, &[TokenType::CloseSquareBracket, TokenType::Comma]);
                     if let Some(json_value) = json_value {
                         if let Ok(json_value) = json_value {
                             array.push(json_value);
@@ -310,7 +314,7 @@ impl Parser {
                     }
                 }
 
-                while tokens.len() > 0 {
+                while !tokens.is_empty() {
                     let tokens_cl = tokens.clone();
                     let token = tokens_cl.first().unwrap();
                     let key = if let TokenType::String(string) = &token.token_type {
@@ -339,10 +343,8 @@ impl Parser {
                         json_err!(Some; "Unexpected end of file"; token.line, token.column)
                     }
 
-                    let json_value = self.parse_value(
-                        tokens,
-                        &vec![TokenType::CloseCurlyBracket, TokenType::Comma],
-                    );
+                    let json_value =
+                        self.parse_value(tokens, &[TokenType::CloseCurlyBracket, TokenType::Comma]);
                     if let Some(json_value) = json_value {
                         if let Ok(json_value) = json_value {
                             object.set(key, json_value);
@@ -380,4 +382,50 @@ impl Parser {
         }
         None
     }
+
+    /// Find where each value starts. The tokens must already have parsed successfully
+    pub(crate) fn positions(&self) -> Positions {
+        let mut positions = Positions::new();
+        let mut index = 0;
+        if let Some(token) = self.tokens.first() {
+            positions.insert(JSONPointer::root(), (token.line, token.column));
+        }
+        self.record_positions(&mut index, &JSONPointer::root(), &mut positions);
+        positions
+    }
+
+    fn record_positions(&self, index: &mut usize, path: &JSONPointer, positions: &mut Positions) {
+        let Some(token) = self.tokens.get(*index) else {
+            return;
+        };
+        *index += 1;
+        let container_end = match token.token_type {
+            TokenType::OpenCurlyBracket => TokenType::CloseCurlyBracket,
+            TokenType::OpenSquareBracket => TokenType::CloseSquareBracket,
+            _ => return,
+        };
+
+        let mut item = 0;
+        while let Some(token) = self.tokens.get(*index) {
+            match &token.token_type {
+                end if *end == container_end => {
+                    *index += 1;
+                    return;
+                }
+                TokenType::Comma => *index += 1,
+                TokenType::String(key) if container_end == TokenType::CloseCurlyBracket => {
+                    let member = path.join(key.as_str());
+                    positions.insert(member.clone(), (token.line, token.column));
+                    *index += 2;
+                    self.record_positions(index, &member, positions);
+                }
+                _ => {
+                    let member = path.join(item.to_string());
+                    positions.insert(member.clone(), (token.line, token.column));
+                    self.record_positions(index, &member, positions);
+                    item += 1;
+                }
+            }
+        }
+    }
 }
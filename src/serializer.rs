@@ -0,0 +1,58 @@
+use std::{
+    fmt,
+    io::{self, BufWriter, Write},
+};
+
+use crate::Formatter;
+
+/// Streams a JSON Value into a `fmt::Write`, asking a [`Formatter`] for everything between the data
+pub(crate) struct Serializer<'a, W: fmt::Write, F: Formatter> {
+    pub writer: &'a mut W,
+    pub formatter: &'a mut F,
+}
+
+impl<'a, W: fmt::Write, F: Formatter> Serializer<'a, W, F> {
+    pub fn new(writer: &'a mut W, formatter: &'a mut F) -> Self {
+        Serializer { writer, formatter }
+    }
+}
+
+/// Adapts an `io::Write` into a `fmt::Write`, holding on to the `io::Error`
+/// that a `fmt::Error` has no room for.
+///
+/// Output is buffered, so the many small fragments of a JSON document
+/// don't each become a write on the underlying writer
+pub(crate) struct IoWriter<'a, W: io::Write> {
+    writer: BufWriter<&'a mut W>,
+    error: Option<io::Error>,
+}
+
+impl<'a, W: io::Write> IoWriter<'a, W> {
+    /// Run `write` against the adapted writer and flush it,
+    /// surfacing the original `io::Error` on failure
+    pub fn run<F>(writer: &'a mut W, write: F) -> io::Result<()>
+    where
+        F: FnOnce(&mut IoWriter<'a, W>) -> fmt::Result,
+    {
+        let mut io_writer = IoWriter {
+            writer: BufWriter::new(writer),
+            error: None,
+        };
+
+        match write(&mut io_writer) {
+            Ok(()) => io_writer.writer.flush(),
+            Err(_) => Err(io_writer
+                .error
+                .unwrap_or_else(|| io::Error::other("formatter error"))),
+        }
+    }
+}
+
+impl<'a, W: io::Write> fmt::Write for IoWriter<'a, W> {
+    fn write_str(&mut self, string: &str) -> fmt::Result {
+        self.writer.write_all(string.as_bytes()).map_err(|error| {
+            self.error = Some(error);
+            fmt::Error
+        })
+    }
+}
@@ -0,0 +1,62 @@
+use std::{
+    fmt::{self, Debug},
+    ops::{Deref, DerefMut},
+};
+
+#[cfg(feature = "shared")]
+type Inner<T> = std::sync::Arc<T>;
+
+#[cfg(not(feature = "shared"))]
+type Inner<T> = T;
+
+/// Storage for the data of a JSON String, JSON Array or JSON Object.
+///
+/// With the `shared` feature, the data is reference counted so cloning is O(1),
+/// and it is copied the first time it is mutated while shared.
+/// Without the feature, this holds the data directly.
+#[derive(Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub(crate) struct Shared<T>(Inner<T>);
+
+impl<T> Shared<T> {
+    pub fn new(data: T) -> Self {
+        Shared(Inner::from(data))
+    }
+}
+
+impl<T: Clone> Shared<T> {
+    /// Take the data out, cloning it only if it is still shared
+    pub fn into_inner(self) -> T {
+        #[cfg(feature = "shared")]
+        return std::sync::Arc::try_unwrap(self.0).unwrap_or_else(|data| (*data).clone());
+
+        #[cfg(not(feature = "shared"))]
+        return self.0;
+    }
+}
+
+impl<T> Deref for Shared<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+/// Mutating the data first gives this handle its own copy if any other handle shares it.
+/// Nested containers are shared handles themselves,
+/// so only the containers on the path to the mutation are copied
+impl<T: Clone> DerefMut for Shared<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        #[cfg(feature = "shared")]
+        return std::sync::Arc::make_mut(&mut self.0);
+
+        #[cfg(not(feature = "shared"))]
+        return &mut self.0;
+    }
+}
+
+impl<T: Debug> Debug for Shared<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&**self, f)
+    }
+}